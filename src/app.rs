@@ -2,6 +2,7 @@ use std::any::Any;
 use std::sync::Arc;
 
 use database_tree::Table;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
 use tokio::sync::{OwnedRwLockReadGuard, RwLock};
 use tui::style::{Color, Style};
 use tui::widgets::Block;
@@ -19,11 +20,12 @@ use crate::components::{
     CommandInfo, Component as _, Drawable, DrawableComponent as _, EventState,
 };
 use crate::config::Connection;
-use crate::database::{MySqlPool, Pool, PostgresPool, SqlitePool};
+use crate::database::{connect, Dialect, Pool};
 use crate::event::Key;
 use crate::{
     components::{
-        command, ConnectionsComponent, DatabasesComponent, ErrorComponent, HelpComponent,
+        command, ConnectionsComponent, CopyAsComponent, DatabasesComponent, ErrorComponent,
+        ExportComponent, HelpComponent, ImportComponent, TableValueComponent,
     },
     config::Config,
     handle_message,
@@ -31,12 +33,17 @@ use crate::{
 
 #[derive(Default)]
 pub struct AppState {
-    pub shared_pool: Option<Box<dyn Pool>>,
+    pub shared_pool: Option<Arc<dyn Pool>>,
     pub selected_database: Option<String>,
     pub selected_table: Option<Table>,
 }
 
 impl AppState {
+    /// The dialect of the connected database, if any.
+    pub fn dialect(&self) -> Option<Dialect> {
+        self.shared_pool.as_ref().map(|pool| pool.dialect())
+    }
+
     pub async fn pool_completion_src(&self) -> Option<PoolFilterableCompletionSource> {
         if let Some(pool) = &self.shared_pool {
             PoolFilterableCompletionSource::new(pool, &self.selected_database, &self.selected_table)
@@ -51,11 +58,30 @@ impl AppState {
 // pub type ReadOnlyAppState = OwnedRwLockReadGuard<AppState>;
 // pub type ReadWriteAppState = OwnedRwLockWriteGuard<AppState>;
 #[derive(Clone)]
-pub struct AppStateRef(Arc<RwLock<AppState>>);
+pub struct AppStateRef {
+    state: Arc<RwLock<AppState>>,
+    message_sender: MessageSender,
+}
 
 impl AppStateRef {
     pub async fn read(&self) -> OwnedRwLockReadGuard<AppState> {
-        self.0.clone().read_owned().await
+        self.state.clone().read_owned().await
+    }
+
+    /// Returns a sender that background tasks may use to post messages to the global message queue.
+    pub fn message_sender(&self) -> MessageSender {
+        self.message_sender.clone()
+    }
+}
+
+#[cfg(test)]
+impl AppStateRef {
+    /// Returns a state without a connection. Messages sent from background tasks are dropped.
+    pub fn detached() -> Self {
+        Self {
+            state: Arc::new(RwLock::new(AppState::default())),
+            message_sender: GlobalMessageQueue::new().sender(),
+        }
     }
 }
 
@@ -69,10 +95,24 @@ pub trait AppMessage: Send + Sync {
 /// Global event queue. Stores queued events until the
 pub struct GlobalMessageQueue {
     event_queue: Vec<Box<dyn AppMessage>>,
+    sender: UnboundedSender<Box<dyn AppMessage>>,
+    receiver: UnboundedReceiver<Box<dyn AppMessage>>,
 }
 
 impl GlobalMessageQueue {
+    fn new() -> Self {
+        let (sender, receiver) = unbounded_channel();
+        Self {
+            event_queue: vec![],
+            sender,
+            receiver,
+        }
+    }
+
     fn drain(&mut self) -> Vec<Box<dyn AppMessage>> {
+        while let Ok(message) = self.receiver.try_recv() {
+            self.event_queue.push(message);
+        }
         if self.event_queue.is_empty() {
             return vec![];
         }
@@ -82,6 +122,23 @@ impl GlobalMessageQueue {
     pub fn push(&mut self, message: Box<dyn AppMessage>) {
         self.event_queue.push(message);
     }
+
+    /// Returns a sender that can outlive the current event handler, e.g. to report the result of
+    /// a spawned task.
+    pub fn sender(&self) -> MessageSender {
+        MessageSender(self.sender.clone())
+    }
+}
+
+/// Cloneable handle used to post messages to the `GlobalMessageQueue` from background tasks.
+#[derive(Clone)]
+pub struct MessageSender(UnboundedSender<Box<dyn AppMessage>>);
+
+impl MessageSender {
+    pub fn send(&self, message: Box<dyn AppMessage>) {
+        // The receiver only goes away when the app shuts down, at which point the message is moot.
+        let _ = self.0.send(message);
+    }
 }
 
 pub enum Focus {
@@ -94,6 +151,10 @@ pub struct App<B: Backend> {
     focus: Focus,
     tab_panel: TabPanel<B>,
     help: HelpComponent,
+    export: ExportComponent,
+    copy_as: CopyAsComponent,
+    import: ImportComponent,
+    value: TableValueComponent,
     databases: DatabasesComponent,
     connections: ConnectionsComponent,
     left_main_chunk_percentage: u16,
@@ -105,22 +166,30 @@ pub struct App<B: Backend> {
 impl<B: Backend> App<B> {
     pub async fn new(config: Config) -> App<B> {
         let app_state = Arc::new(RwLock::new(AppState::default()));
+        let message_queue = GlobalMessageQueue::new();
+        let app_state_ref = AppStateRef {
+            state: app_state.clone(),
+            message_sender: message_queue.sender(),
+        };
         let config_clone = config.clone();
         App {
             app_state: app_state.clone(),
             config: config.clone(),
-            connections: ConnectionsComponent::new(config.key_config.clone(), config.conn),
-            tab_panel: TabPanel::new(config_clone, AppStateRef(app_state.clone())).await,
-            help: HelpComponent::new(config.key_config.clone()),
-            databases: DatabasesComponent::new(
+            connections: ConnectionsComponent::new(
                 config.key_config.clone(),
-                AppStateRef(app_state.clone()),
+                config.conn,
+                config.path,
             ),
+            tab_panel: TabPanel::new(config_clone, app_state_ref.clone()).await,
+            help: HelpComponent::new(config.key_config.clone()),
+            export: ExportComponent::new(config.key_config.clone(), app_state_ref.clone()),
+            copy_as: CopyAsComponent::new(config.key_config.clone(), app_state_ref.clone()),
+            import: ImportComponent::new(config.key_config.clone(), app_state_ref.clone()),
+            value: TableValueComponent::new(config.key_config.clone()),
+            databases: DatabasesComponent::new(config.key_config.clone(), app_state_ref),
             error: ErrorComponent::new(config.key_config),
             focus: Focus::ConnectionList,
-            message_queue: GlobalMessageQueue {
-                event_queue: vec![],
-            },
+            message_queue,
             left_main_chunk_percentage: 15,
         }
     }
@@ -157,6 +226,10 @@ impl<B: Backend> App<B> {
                     .draw(f, content_chunk, matches!(self.focus, Focus::TabPanel))?;
             }
         }
+        self.export.draw(f, Rect::default(), false)?;
+        self.copy_as.draw(f, Rect::default(), false)?;
+        self.import.draw(f, Rect::default(), false)?;
+        self.value.draw(f, Rect::default(), false)?;
         self.error.draw(f, Rect::default(), false)?;
         self.help.draw(f, Rect::default(), false)?;
         Ok(())
@@ -180,6 +253,7 @@ impl<B: Backend> App<B> {
             CommandInfo::new(command::extend_or_shorten_widget_width(
                 &self.config.key_config,
             )),
+            CommandInfo::new(command::cancel_query(&self.config.key_config)),
         ];
 
         self.databases.commands(&mut res);
@@ -188,20 +262,13 @@ impl<B: Backend> App<B> {
         res
     }
 
-    async fn get_pool_from_conn(&mut self, conn: &Connection) -> anyhow::Result<Box<dyn Pool>> {
-        return if conn.is_mysql() {
-            Ok(Box::new(
-                MySqlPool::new(conn.database_url()?.as_str()).await?,
-            ))
-        } else if conn.is_postgres() {
-            Ok(Box::new(
-                PostgresPool::new(conn.database_url()?.as_str()).await?,
-            ))
-        } else {
-            Ok(Box::new(
-                SqlitePool::new(conn.database_url()?.as_str()).await?,
-            ))
-        };
+    /// Connects straight to `connection`, skipping the connection list.
+    pub async fn connect(&mut self, connection: Connection) -> anyhow::Result<()> {
+        self.message_queue
+            .push(Box::new(ConnectionEvent::ConnectionChanged(Some(
+                connection,
+            ))));
+        self.dispatch_messages().await
     }
 
     pub async fn event(&mut self, key: Key) -> anyhow::Result<EventState> {
@@ -217,15 +284,23 @@ impl<B: Backend> App<B> {
         self.dispatch_messages().await?;
         return result;
     }
-    async fn on_conn_changed(&mut self, conn: &Connection) {
-        if let Some(new_pool) = self.get_pool_from_conn(conn).await.ok() {
-            let mut whandle = self.app_state.write().await;
-            if let Some(current_pool) = &(*whandle).shared_pool {
-                current_pool.close().await;
-            }
-            (*whandle).shared_pool = Some(new_pool);
+
+    /// Called on every tick of the event loop. Delivers messages posted by background tasks.
+    pub async fn tick(&mut self) -> anyhow::Result<()> {
+        self.dispatch_messages().await
+    }
+    /// Connects to `conn`, replacing the current connection. If connecting fails, the current
+    /// connection is kept and the error is returned.
+    async fn on_conn_changed(&mut self, conn: &Connection) -> anyhow::Result<()> {
+        let new_pool = connect(conn).await?;
+        let mut whandle = self.app_state.write().await;
+        if let Some(current_pool) = &(*whandle).shared_pool {
+            current_pool.close().await;
         }
+        (*whandle).shared_pool = Some(new_pool);
+        (*whandle).selected_database = conn.database.clone();
         self.focus = Focus::DatabaseList;
+        Ok(())
     }
 
     async fn handle_messages(
@@ -236,11 +311,7 @@ impl<B: Backend> App<B> {
             handle_message!(m, ConnectionEvent,
                 ConnectionEvent::ConnectionChanged(conn_opt) => {
                     if let Some(conn) = conn_opt {
-                        {
-                            let mut whandle = self.app_state.write().await;
-                            (*whandle).selected_database = conn.database.clone();
-                        }
-                        self.on_conn_changed(conn).await;
+                        self.on_conn_changed(conn).await?;
                     }
                 }
             );
@@ -268,6 +339,10 @@ impl<B: Backend> App<B> {
                 self.databases.handle_messages(&messages),
                 self.tab_panel.handle_messages(&messages),
                 self.connections.handle_messages(&messages),
+                self.export.handle_messages(&messages),
+                self.copy_as.handle_messages(&messages),
+                self.import.handle_messages(&messages),
+                self.value.handle_messages(&messages),
             ])
             .await
             .drain(0..)
@@ -287,6 +362,42 @@ impl<B: Backend> App<B> {
             return Ok(EventState::Consumed);
         }
 
+        if self
+            .export
+            .event(key, &mut self.message_queue)
+            .await?
+            .is_consumed()
+        {
+            return Ok(EventState::Consumed);
+        }
+
+        if self
+            .copy_as
+            .event(key, &mut self.message_queue)
+            .await?
+            .is_consumed()
+        {
+            return Ok(EventState::Consumed);
+        }
+
+        if self
+            .import
+            .event(key, &mut self.message_queue)
+            .await?
+            .is_consumed()
+        {
+            return Ok(EventState::Consumed);
+        }
+
+        if self
+            .value
+            .event(key, &mut self.message_queue)
+            .await?
+            .is_consumed()
+        {
+            return Ok(EventState::Consumed);
+        }
+
         if !matches!(self.focus, Focus::ConnectionList)
             && self
                 .help
@@ -327,44 +438,6 @@ impl<B: Backend> App<B> {
                 {
                     return Ok(EventState::Consumed);
                 }
-                // TODO: Reimplement this section in the records tab
-                // match self.tab.selected_tab {
-                //     TabType::Records => {
-                //
-                //         if self.record_table.table.eod {
-                //             return Ok(EventState::Consumed);
-                //         }
-                //
-                //         if let Some(index) = self.record_table.table.selected_row.selected() {
-                //             if index.saturating_add(1) % RECORDS_LIMIT_PER_PAGE as usize == 0 {
-                //                 if let Some((database, table)) =
-                //                     self.databases.tree().selected_table()
-                //                 {
-                //                     let (_, records) = self
-                //                         .pool
-                //                         .as_ref()
-                //                         .unwrap()
-                //                         .get_records(
-                //                             &database,
-                //                             &table,
-                //                             index as u16,
-                //                             if self.record_table.filter.input_str().is_empty() {
-                //                                 None
-                //                             } else {
-                //                                 Some(self.record_table.filter.input_str())
-                //                             },
-                //                         )
-                //                         .await?;
-                //                     if !records.is_empty() {
-                //                         self.record_table.table.rows.extend(records);
-                //                     } else {
-                //                         self.record_table.table.end()
-                //                     }
-                //                 }
-                //             }
-                //         };
-                //     }
-                // };
             }
         }
 
@@ -0,0 +1,145 @@
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use unicode_width::UnicodeWidthStr;
+
+use crate::config::Connection;
+use crate::database::{connect, ExecuteResult, Value};
+use crate::export::{export, ExportFormat};
+
+/// How the rows returned by a non-interactive query are printed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutputFormat {
+    Table,
+    Csv,
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(format: &str) -> Result<Self> {
+        match format {
+            "table" => Ok(Self::Table),
+            "csv" => Ok(Self::Csv),
+            "json" => Ok(Self::Json),
+            _ => Err(anyhow!("unknown output format `{}`", format)),
+        }
+    }
+}
+
+/// Runs `query` on `connection` and prints the result of each statement to stdout.
+pub async fn run(connection: &Connection, query: &str, format: OutputFormat) -> Result<()> {
+    let pool = connect(connection).await?;
+    let results = pool.execute(&query.to_string()).await;
+    pool.close().await;
+    for result in results? {
+        match result {
+            ExecuteResult::Read { headers, rows, .. } => {
+                print!("{}", render(format, &headers, &rows)?)
+            }
+            ExecuteResult::Write { updated_rows } => {
+                println!("Query OK, {} rows affected", updated_rows)
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn render(format: OutputFormat, headers: &[String], rows: &[Vec<Value>]) -> Result<String> {
+    match format {
+        OutputFormat::Table => Ok(table(headers, rows)),
+        OutputFormat::Csv => export(ExportFormat::Csv, headers, rows, None),
+        OutputFormat::Json => export(ExportFormat::Json, headers, rows, None),
+    }
+}
+
+/// Renders the rows as a table with aligned columns, followed by the number of rows.
+fn table(headers: &[String], rows: &[Vec<Value>]) -> String {
+    let rows = rows
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|cell| cell.to_string().replace('\n', "\\n").replace('\t', "\\t"))
+                .collect::<Vec<String>>()
+        })
+        .collect::<Vec<Vec<String>>>();
+    let widths = headers
+        .iter()
+        .enumerate()
+        .map(|(column, header)| {
+            rows.iter()
+                .filter_map(|row| row.get(column))
+                .map(|cell| cell.width())
+                .chain(std::iter::once(header.width()))
+                .max()
+                .unwrap_or_default()
+        })
+        .collect::<Vec<usize>>();
+    let border = format!(
+        "+{}+\n",
+        widths
+            .iter()
+            .map(|width| "-".repeat(width + 2))
+            .collect::<Vec<String>>()
+            .join("+")
+    );
+    let line = |cells: &[String]| {
+        format!(
+            "| {} |\n",
+            cells
+                .iter()
+                .zip(widths.iter())
+                .map(|(cell, width)| format!("{}{}", cell, " ".repeat(width - cell.width())))
+                .collect::<Vec<String>>()
+                .join(" | ")
+        )
+    };
+
+    let mut table = border.clone();
+    table.push_str(&line(headers));
+    table.push_str(&border);
+    for row in &rows {
+        table.push_str(&line(row));
+    }
+    if !rows.is_empty() {
+        table.push_str(&border);
+    }
+    table.push_str(&format!("{} rows\n", rows.len()));
+    table
+}
+
+#[cfg(test)]
+mod test {
+    use super::{render, OutputFormat};
+    use crate::database::Value;
+
+    #[test]
+    fn test_render_table() {
+        let headers = vec!["id".to_string(), "name".to_string()];
+        let rows = vec![
+            vec![Value::Int(1), Value::from("alice")],
+            vec![Value::Int(10), Value::from("a\nb")],
+        ];
+        assert_eq!(
+            render(OutputFormat::Table, &headers, &rows).unwrap(),
+            "+----+-------+\n\
+             | id | name  |\n\
+             +----+-------+\n\
+             | 1  | alice |\n\
+             | 10 | a\\nb  |\n\
+             +----+-------+\n\
+             2 rows\n"
+        );
+        assert_eq!(
+            render(OutputFormat::Table, &headers, &[]).unwrap(),
+            "+----+------+\n| id | name |\n+----+------+\n0 rows\n"
+        );
+    }
+
+    #[test]
+    fn test_output_format_from_str() {
+        assert_eq!("csv".parse::<OutputFormat>().unwrap(), OutputFormat::Csv);
+        assert!("xml".parse::<OutputFormat>().is_err());
+    }
+}
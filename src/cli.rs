@@ -1,6 +1,9 @@
+use std::path::PathBuf;
+
 use structopt::StructOpt;
 
-use crate::config::CliConfig;
+use crate::batch::OutputFormat;
+use crate::config::{CliConfig, Config, Connection};
 
 /// A cross-platform TUI database management tool written in Rust
 #[derive(StructOpt, Debug)]
@@ -8,6 +11,52 @@ use crate::config::CliConfig;
 pub struct Cli {
     #[structopt(flatten)]
     pub config: CliConfig,
+
+    /// Connect to a database URL, e.g. postgres://user@localhost/db, instead of picking a
+    /// connection from the config file
+    #[structopt(conflicts_with_all = &["connection", "sqlite"])]
+    pub database_url: Option<String>,
+
+    /// Connect to a SQLite database file
+    #[structopt(long, parse(from_os_str), conflicts_with = "connection")]
+    pub sqlite: Option<PathBuf>,
+
+    /// Connect to the connection with this name from the config file
+    #[structopt(long)]
+    pub connection: Option<String>,
+
+    /// Run a query, print its result and exit instead of starting the TUI
+    #[structopt(long, short, conflicts_with = "file")]
+    pub execute: Option<String>,
+
+    /// Run the query read from a file, print its result and exit instead of starting the TUI
+    #[structopt(long, short, parse(from_os_str))]
+    pub file: Option<PathBuf>,
+
+    /// Output format of the rows returned by --execute and --file
+    #[structopt(long, default_value = "table", possible_values = &["table", "csv", "json"])]
+    pub format: OutputFormat,
+}
+
+impl Cli {
+    /// The connection given with a database URL, --sqlite or --connection, if any.
+    pub fn connection(&self, config: &Config) -> anyhow::Result<Option<Connection>> {
+        Ok(match (&self.database_url, &self.sqlite, &self.connection) {
+            (Some(database_url), _, _) => Some(Connection::from_url(database_url)?),
+            (None, Some(path), _) => Some(Connection::sqlite(path.clone())),
+            (None, None, Some(name)) => Some(config.connection(name)?.clone()),
+            (None, None, None) => None,
+        })
+    }
+
+    /// The query passed with --execute or --file, if gobang should run it non-interactively.
+    pub fn query(&self) -> anyhow::Result<Option<String>> {
+        Ok(match (&self.execute, &self.file) {
+            (Some(query), _) => Some(query.clone()),
+            (None, Some(path)) => Some(std::fs::read_to_string(path)?),
+            (None, None) => None,
+        })
+    }
 }
 
 pub fn parse() -> Cli {
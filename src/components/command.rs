@@ -3,6 +3,7 @@ static CMD_GROUP_GENERAL: &str = "-- General --";
 static CMD_GROUP_TABLE: &str = "-- Table --";
 static CMD_GROUP_DATABASES: &str = "-- Databases --";
 static CMD_GROUP_PROPERTIES: &str = "-- Properties --";
+static CMD_GROUP_EDITOR: &str = "-- Editor --";
 
 #[macro_export]
 macro_rules! command {
@@ -126,6 +127,211 @@ pub fn extend_or_shorten_widget_width(key: &KeyConfig) -> CommandText {
     )
 }
 
+pub fn cancel_query(key: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!("Cancel running query [{}]", key.cancel_query),
+        CMD_GROUP_GENERAL,
+    )
+}
+
+pub fn edit_cell(key: &KeyConfig) -> CommandText {
+    CommandText::new(format!("Edit cell [{}]", key.edit_cell), CMD_GROUP_TABLE)
+}
+
+pub fn set_null(key: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!("Set the edited value to NULL [{}]", key.set_null),
+        CMD_GROUP_TABLE,
+    )
+}
+
+pub fn insert_delete_row(key: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!("Insert/delete row [{},{}]", key.insert_row, key.delete_row),
+        CMD_GROUP_TABLE,
+    )
+}
+
+pub fn commit_discard_changes(key: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!(
+            "Commit/discard pending changes [{},{}]",
+            key.commit_changes, key.discard_changes
+        ),
+        CMD_GROUP_TABLE,
+    )
+}
+
+pub fn toggle_pending_changes(key: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!("Show/hide pending changes [{}]", key.toggle_pending_changes),
+        CMD_GROUP_TABLE,
+    )
+}
+
+pub fn export(key: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!("Export rows to a file [{}]", key.export),
+        CMD_GROUP_TABLE,
+    )
+}
+
+pub fn inspect_value(key: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!("Show the full value of the cell [{}]", key.inspect_value),
+        CMD_GROUP_TABLE,
+    )
+}
+
+pub fn toggle_record_view(key: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!("Toggle the record view [{}]", key.toggle_record_view),
+        CMD_GROUP_TABLE,
+    )
+}
+
+pub fn sort_by_column(key: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!(
+            "Sort the loaded rows by the column [{}]",
+            key.sort_by_column
+        ),
+        CMD_GROUP_TABLE,
+    )
+}
+
+pub fn order_by(key: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!("Enter the ORDER BY columns [{}]", key.order_by),
+        CMD_GROUP_TABLE,
+    )
+}
+
+pub fn sort_in_database(key: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!(
+            "Sort the whole table by the column [{}]",
+            key.sort_in_database
+        ),
+        CMD_GROUP_TABLE,
+    )
+}
+
+pub fn hide_show_columns(key: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!(
+            "Hide the column [{}], show all columns [{}]",
+            key.hide_column, key.show_all_columns
+        ),
+        CMD_GROUP_TABLE,
+    )
+}
+
+pub fn move_column(key: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!(
+            "Move the column left/right [{},{}]",
+            key.move_column_left, key.move_column_right
+        ),
+        CMD_GROUP_TABLE,
+    )
+}
+
+pub fn search(key: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!(
+            "Search the loaded rows [{}], next/previous match [{},{}]",
+            key.search, key.next_match, key.previous_match
+        ),
+        CMD_GROUP_TABLE,
+    )
+}
+
+pub fn freeze_columns(key: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!("Pin the columns up to the column [{}]", key.freeze_columns),
+        CMD_GROUP_TABLE,
+    )
+}
+
+pub fn follow_foreign_key(key: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!(
+            "Open the row referenced by the cell [{}], go back [{}]",
+            key.follow_foreign_key, key.go_back
+        ),
+        CMD_GROUP_TABLE,
+    )
+}
+
+pub fn show_references(key: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!(
+            "Show the rows referencing the row [{}]",
+            key.show_references
+        ),
+        CMD_GROUP_TABLE,
+    )
+}
+
+pub fn execute_statement(key: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!(
+            "Execute the selection or the statement under the cursor [{}]",
+            key.execute_statement
+        ),
+        CMD_GROUP_EDITOR,
+    )
+}
+
+pub fn execute_all(key: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!("Execute all the statements [{}]", key.execute_all),
+        CMD_GROUP_EDITOR,
+    )
+}
+
+pub fn result_sets(key: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!(
+            "Switch result sets [{},{}]",
+            key.previous_result_set, key.next_result_set
+        ),
+        CMD_GROUP_EDITOR,
+    )
+}
+
+pub fn freeze_primary_key(key: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!("Pin the primary key [{}]", key.freeze_primary_key),
+        CMD_GROUP_TABLE,
+    )
+}
+
+pub fn resize_column(key: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!(
+            "Widen/narrow the column [{},{}]",
+            key.widen_column, key.narrow_column
+        ),
+        CMD_GROUP_TABLE,
+    )
+}
+
+pub fn copy_as(key: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!("Copy the selected cells as... [{}]", key.copy_as),
+        CMD_GROUP_TABLE,
+    )
+}
+
+pub fn import(key: &KeyConfig) -> CommandText {
+    CommandText::new(
+        format!("Import a CSV file into the table [{}]", key.import),
+        CMD_GROUP_DATABASES,
+    )
+}
+
 pub fn tab_columns(key: &KeyConfig) -> CommandText {
     CommandText::new(format!("Columns [{}]", key.tab_columns), CMD_GROUP_TABLE)
 }
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use anyhow::Result;
 use async_trait::async_trait;
 use futures::try_join;
@@ -40,7 +42,7 @@ pub struct PoolFilterableCompletionSource {
 
 impl PoolFilterableCompletionSource {
     pub async fn new(
-        pool: &Box<dyn Pool>,
+        pool: &Arc<dyn Pool>,
         database: &Option<String>,
         table: &Option<Table>,
     ) -> anyhow::Result<Self> {
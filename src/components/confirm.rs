@@ -0,0 +1,127 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use tui::{
+    backend::Backend,
+    layout::{Alignment, Rect},
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::app::GlobalMessageQueue;
+use crate::components::command::CommandInfo;
+use crate::config::KeyConfig;
+use crate::event::Key;
+
+use super::{Component, DrawableComponent, EventState};
+
+/// Popup showing a statement which is about to be executed. The owner runs the statement once
+/// `event` reports that it has been confirmed.
+pub struct ConfirmComponent {
+    pub statement: String,
+    confirmed: bool,
+    visible: bool,
+    key_config: KeyConfig,
+}
+
+impl ConfirmComponent {
+    pub fn new(key_config: KeyConfig) -> Self {
+        Self {
+            statement: String::new(),
+            confirmed: false,
+            visible: false,
+            key_config,
+        }
+    }
+
+    pub fn set(&mut self, statement: String) -> Result<()> {
+        self.statement = statement;
+        self.confirmed = false;
+        self.show()
+    }
+
+    /// Returns the statement if the user confirmed it, resetting the confirmation.
+    pub fn take_confirmed(&mut self) -> Option<String> {
+        if self.confirmed {
+            self.confirmed = false;
+            Some(std::mem::take(&mut self.statement))
+        } else {
+            None
+        }
+    }
+}
+
+impl DrawableComponent for ConfirmComponent {
+    fn draw<B: Backend>(&self, f: &mut Frame<B>, _area: Rect, _focused: bool) -> Result<()> {
+        if self.visible {
+            let width = 80;
+            let height = (self.statement.lines().count() as u16 + 4).max(8);
+            let mut text = self
+                .statement
+                .lines()
+                .map(Spans::from)
+                .collect::<Vec<Spans>>();
+            text.extend(vec![
+                Spans::from(""),
+                Spans::from(Span::styled(
+                    format!(
+                        "Press {} to execute, {} to cancel",
+                        self.key_config.enter, self.key_config.exit_popup
+                    ),
+                    Style::default().fg(Color::DarkGray),
+                )),
+            ]);
+            let confirm = Paragraph::new(text)
+                .block(Block::default().title("Confirm").borders(Borders::ALL))
+                .alignment(Alignment::Left)
+                .wrap(Wrap { trim: false });
+            let area = Rect::new(
+                (f.size().width.saturating_sub(width)) / 2,
+                (f.size().height.saturating_sub(height)) / 2,
+                width.min(f.size().width),
+                height.min(f.size().height),
+            );
+            f.render_widget(Clear, area);
+            f.render_widget(confirm, area);
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Component for ConfirmComponent {
+    fn commands(&self, _out: &mut Vec<CommandInfo>) {}
+
+    async fn event(
+        &mut self,
+        key: Key,
+        _message_queue: &mut GlobalMessageQueue,
+    ) -> Result<EventState> {
+        if self.visible {
+            if key == self.key_config.enter {
+                self.confirmed = true;
+                self.hide();
+            } else if key == self.key_config.exit_popup {
+                self.statement = String::new();
+                self.hide();
+            }
+            return Ok(EventState::Consumed);
+        }
+        Ok(EventState::NotConsumed)
+    }
+
+    fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    fn hide(&mut self) {
+        self.visible = false;
+    }
+
+    fn show(&mut self) -> Result<()> {
+        self.visible = true;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,242 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Margin, Rect},
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use crate::app::GlobalMessageQueue;
+use crate::components::command::CommandInfo;
+use crate::config::{Connection, KeyConfig};
+use crate::event::Key;
+use crate::ui::textbox::TextBox;
+
+use super::{Component, DrawableComponent, EventState};
+
+/// Popup form used to add or edit a connection of the config file.
+pub struct ConnectionFormComponent {
+    title: String,
+    values: Vec<String>,
+    selected: usize,
+    input: TextBox,
+    submitted: Option<Connection>,
+    error: Option<String>,
+    visible: bool,
+    key_config: KeyConfig,
+}
+
+impl ConnectionFormComponent {
+    const PASSWORD_FIELD: usize = 5;
+
+    pub fn new(key_config: KeyConfig) -> Self {
+        Self {
+            title: String::new(),
+            values: vec![],
+            selected: 0,
+            input: TextBox::default(),
+            submitted: None,
+            error: None,
+            visible: false,
+            key_config,
+        }
+    }
+
+    /// Opens the form, filled with the fields of `connection` if there is one.
+    pub fn set<S: Into<String>>(
+        &mut self,
+        title: S,
+        connection: Option<&Connection>,
+    ) -> Result<()> {
+        self.title = title.into();
+        self.values = match connection {
+            Some(connection) => connection.form_values(),
+            None => vec![String::new(); Connection::FORM_FIELDS.len()],
+        };
+        self.submitted = None;
+        self.error = None;
+        self.selected = 0;
+        self.input.reset();
+        self.select(0);
+        self.show()
+    }
+
+    /// Returns the connection if the form was submitted with valid values.
+    pub fn take_submitted(&mut self) -> Option<Connection> {
+        self.submitted.take()
+    }
+
+    fn select(&mut self, index: usize) {
+        if let Some(value) = self.values.get_mut(self.selected) {
+            *value = self.input.get_text();
+        }
+        self.selected = index.min(self.values.len().saturating_sub(1));
+        if let Some(value) = self.values.get(self.selected) {
+            self.input.set_label(Connection::FORM_FIELDS[self.selected]);
+            self.input.set_str(value);
+        }
+    }
+
+    fn submit(&mut self) {
+        self.select(self.selected);
+        match Connection::from_form_values(&self.values) {
+            Ok(connection) => {
+                self.submitted = Some(connection);
+                self.hide();
+            }
+            Err(err) => self.error = Some(err.to_string()),
+        }
+    }
+}
+
+impl DrawableComponent for ConnectionFormComponent {
+    fn draw<B: Backend>(&self, f: &mut Frame<B>, _area: Rect, _focused: bool) -> Result<()> {
+        if self.visible {
+            let width = 80;
+            let height = self.values.len() as u16 + 8;
+            let area = Rect::new(
+                (f.size().width.saturating_sub(width)) / 2,
+                (f.size().height.saturating_sub(height)) / 2,
+                width.min(f.size().width),
+                height.min(f.size().height),
+            );
+            f.render_widget(Clear, area);
+            f.render_widget(
+                Block::default()
+                    .title(self.title.as_str())
+                    .borders(Borders::ALL),
+                area,
+            );
+
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Min(1),
+                    Constraint::Length(3),
+                    Constraint::Length(1),
+                    Constraint::Length(1),
+                ])
+                .split(area.inner(&Margin {
+                    vertical: 1,
+                    horizontal: 1,
+                }));
+
+            let lines = Connection::FORM_FIELDS
+                .iter()
+                .zip(self.values.iter())
+                .enumerate()
+                .map(|(index, (field, value))| {
+                    let style = if index == self.selected {
+                        Style::default().bg(Color::Rgb(0xea, 0x59, 0x0b))
+                    } else {
+                        Style::default()
+                    };
+                    let value = if index == self.selected {
+                        self.input.get_text()
+                    } else if index == Self::PASSWORD_FIELD {
+                        "*".repeat(value.chars().count())
+                    } else {
+                        value.clone()
+                    };
+                    Spans::from(vec![
+                        Span::styled(field.to_string(), style.add_modifier(Modifier::BOLD)),
+                        Span::raw(format!(" = {}", value)),
+                    ])
+                })
+                .collect::<Vec<Spans>>();
+            f.render_widget(Paragraph::new(lines), chunks[0]);
+
+            self.input.draw(f, chunks[1], true)?;
+
+            if let Some(error) = &self.error {
+                f.render_widget(
+                    Paragraph::new(error.as_str()).style(Style::default().fg(Color::Red)),
+                    chunks[2],
+                );
+            }
+            f.render_widget(
+                Paragraph::new(format!(
+                    "{}/{}: select field, {}: save, {}: cancel, type: mysql, postgres or sqlite",
+                    self.key_config.move_up,
+                    self.key_config.move_down,
+                    self.key_config.enter,
+                    self.key_config.exit_popup
+                ))
+                .style(Style::default().fg(Color::DarkGray)),
+                chunks[3],
+            );
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Component for ConnectionFormComponent {
+    fn commands(&self, _out: &mut Vec<CommandInfo>) {}
+
+    async fn event(
+        &mut self,
+        key: Key,
+        message_queue: &mut GlobalMessageQueue,
+    ) -> Result<EventState> {
+        if !self.visible {
+            return Ok(EventState::NotConsumed);
+        }
+        if key == self.key_config.exit_popup {
+            self.hide();
+        } else if key == self.key_config.enter {
+            self.submit();
+        } else if key == self.key_config.move_up {
+            self.select(self.selected.saturating_sub(1));
+        } else if key == self.key_config.move_down || key == Key::Tab {
+            self.select(self.selected + 1);
+        } else {
+            self.input.event(key, message_queue).await?;
+        }
+        Ok(EventState::Consumed)
+    }
+
+    fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    fn hide(&mut self) {
+        self.visible = false;
+    }
+
+    fn show(&mut self) -> Result<()> {
+        self.visible = true;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ConnectionFormComponent, KeyConfig};
+
+    #[test]
+    fn test_submit() {
+        let mut form = ConnectionFormComponent::new(KeyConfig::default());
+        form.set("Add connection", None).unwrap();
+        form.submit();
+        assert!(form.take_submitted().is_none());
+        assert_eq!(
+            form.error.as_deref(),
+            Some("type must be mysql, postgres or sqlite")
+        );
+
+        form.input.set_str(&"sqlite".to_string());
+        form.select(7);
+        form.input.set_str(&"/tmp/shop.db".to_string());
+        form.submit();
+        assert_eq!(
+            form.take_submitted().unwrap().database_url().unwrap(),
+            "sqlite:///tmp/shop.db"
+        );
+        assert!(!form.visible);
+    }
+}
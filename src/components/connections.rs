@@ -1,21 +1,24 @@
 use std::any::Any;
+use std::path::PathBuf;
 
 use anyhow::Result;
 use async_trait::async_trait;
 use tui::{
     backend::Backend,
-    layout::Rect,
+    layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Style},
     text::{Span, Spans},
-    widgets::{Block, Borders, Clear, List, ListItem, ListState},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
     Frame,
 };
 
-use crate::app::AppMessage;
+use crate::app::{AppMessage, MessageSender};
 use crate::components::command::CommandInfo;
 
-use crate::components::Drawable;
-use crate::config::{Connection, KeyConfig};
+use crate::components::{ConfirmComponent, ConnectionFormComponent, Drawable, DrawableComponent};
+use crate::config::{save_connections, Connection, KeyConfig};
+use crate::database::connect;
+use crate::handle_message;
 
 use super::{Component, EventState};
 
@@ -29,23 +32,125 @@ impl AppMessage for ConnectionEvent {
     }
 }
 
+/// Posted once a connection started with the test connection key has been tried.
+struct ConnectionTested {
+    connection: String,
+    result: Result<(), String>,
+}
+
+impl AppMessage for ConnectionTested {
+    fn as_any(&self) -> &(dyn Any + Send + Sync) {
+        self
+    }
+}
+
 pub struct ConnectionsComponent {
     connections: Vec<Connection>,
     state: ListState,
+    config_path: Option<PathBuf>,
+    form: ConnectionFormComponent,
+    /// The index of the connection being edited in the form, or None if a new one is added.
+    editing: Option<usize>,
+    confirm: ConfirmComponent,
+    status: Option<String>,
     key_config: KeyConfig,
 }
 
 impl ConnectionsComponent {
-    pub fn new(key_config: KeyConfig, connections: Vec<Connection>) -> Self {
+    pub fn new(
+        key_config: KeyConfig,
+        connections: Vec<Connection>,
+        config_path: Option<PathBuf>,
+    ) -> Self {
         let mut state = ListState::default();
         if !connections.is_empty() {
             state.select(Some(0));
         }
         Self {
             connections,
-            key_config,
             state,
+            config_path,
+            form: ConnectionFormComponent::new(key_config.clone()),
+            editing: None,
+            confirm: ConfirmComponent::new(key_config.clone()),
+            status: None,
+            key_config,
+        }
+    }
+
+    /// Writes the connections back to the config file.
+    fn save(&mut self) -> Result<()> {
+        if let Some(path) = &self.config_path {
+            save_connections(path, &self.connections)?;
+            self.status = Some(format!("Saved connections to {}", path.display()));
+        }
+        Ok(())
+    }
+
+    /// Opens the form filled with `connection`. Once submitted, the connection at `editing` is
+    /// replaced, or a new one is added if there is no such index.
+    fn open_form(
+        &mut self,
+        title: &str,
+        editing: Option<usize>,
+        connection: Option<Connection>,
+    ) -> Result<()> {
+        self.editing = editing;
+        self.form.set(title, connection.as_ref())
+    }
+
+    fn apply_form(&mut self, connection: Connection) -> Result<()> {
+        match self
+            .editing
+            .and_then(|index| self.connections.get_mut(index))
+        {
+            Some(edited) => *edited = connection,
+            None => {
+                self.connections.push(connection);
+                self.state.select(Some(self.connections.len() - 1));
+            }
+        }
+        self.save()
+    }
+
+    fn delete_selected(&mut self) -> Result<()> {
+        if let Some(index) = self.state.selected() {
+            if index < self.connections.len() {
+                self.connections.remove(index);
+                self.state.select(if self.connections.is_empty() {
+                    None
+                } else {
+                    Some(index.min(self.connections.len() - 1))
+                });
+                self.save()?;
+            }
         }
+        Ok(())
+    }
+
+    /// Tries to connect to the selected connection on a background task. The outcome is shown in
+    /// the status line.
+    fn test_connection(&mut self, sender: MessageSender) -> Result<()> {
+        let connection = match self.selected_connection() {
+            Some(connection) => connection.clone(),
+            None => return Ok(()),
+        };
+        let name = connection.database_url_with_name()?;
+        self.status = Some(format!("Connecting to {}\u{2026}", name));
+        tokio::spawn(async move {
+            let result = match connect(&connection).await {
+                Ok(pool) => {
+                    pool.close().await;
+                    Ok(())
+                }
+                Err(err) => Err(err.to_string()),
+            };
+            sender.send(Box::new(ConnectionTested {
+                connection: name,
+                result,
+            }));
+        });
+        Ok(())
     }
 
     fn next_connection(&mut self, lines: usize) {
@@ -122,8 +227,29 @@ impl<B: Backend> Drawable<B> for ConnectionsComponent {
             height.min(f.size().height),
         );
 
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(1), Constraint::Length(1)])
+            .split(area);
+        let status = self.status.clone().unwrap_or_else(|| {
+            format!(
+                "{}: add, {}: edit, {}: duplicate, {}: delete, {}: test connection",
+                self.key_config.add_connection,
+                self.key_config.edit_connection,
+                self.key_config.duplicate_connection,
+                self.key_config.delete_connection,
+                self.key_config.test_connection
+            )
+        });
+
         f.render_widget(Clear, area);
-        f.render_stateful_widget(connections, area, &mut self.state);
+        f.render_stateful_widget(connections, chunks[0], &mut self.state);
+        f.render_widget(
+            Paragraph::new(status).style(Style::default().fg(Color::DarkGray)),
+            chunks[1],
+        );
+        self.form.draw(f, Rect::default(), false)?;
+        self.confirm.draw(f, Rect::default(), false)?;
         Ok(())
     }
 }
@@ -137,6 +263,47 @@ impl Component for ConnectionsComponent {
         key: crate::event::Key,
         message_queue: &mut crate::app::GlobalMessageQueue,
     ) -> Result<EventState> {
+        if self.form.is_visible() {
+            self.form.event(key, message_queue).await?;
+            if let Some(connection) = self.form.take_submitted() {
+                self.apply_form(connection)?;
+            }
+            return Ok(EventState::Consumed);
+        }
+        if self.confirm.event(key, message_queue).await?.is_consumed() {
+            if self.confirm.take_confirmed().is_some() {
+                self.delete_selected()?;
+            }
+            return Ok(EventState::Consumed);
+        }
+
+        if key == self.key_config.add_connection {
+            self.open_form("Add connection", None, None)?;
+            return Ok(EventState::Consumed);
+        } else if key == self.key_config.edit_connection {
+            if let Some(connection) = self.selected_connection().cloned() {
+                self.open_form("Edit connection", self.state.selected(), Some(connection))?;
+            }
+            return Ok(EventState::Consumed);
+        } else if key == self.key_config.duplicate_connection {
+            if let Some(connection) = self.selected_connection().cloned() {
+                self.open_form("Duplicate connection", None, Some(connection))?;
+            }
+            return Ok(EventState::Consumed);
+        } else if key == self.key_config.delete_connection {
+            if let Some(connection) = self.selected_connection() {
+                let statement = format!(
+                    "Delete connection {} from the config file?",
+                    connection.database_url_with_name()?
+                );
+                self.confirm.set(statement)?;
+            }
+            return Ok(EventState::Consumed);
+        } else if key == self.key_config.test_connection {
+            self.test_connection(message_queue.sender())?;
+            return Ok(EventState::Consumed);
+        }
+
         if key == self.key_config.scroll_down {
             self.next_connection(1);
             return Ok(EventState::Consumed);
@@ -167,4 +334,18 @@ impl Component for ConnectionsComponent {
         }
         Ok(EventState::NotConsumed)
     }
+
+    async fn handle_messages(&mut self, messages: &Vec<Box<dyn AppMessage>>) -> Result<()> {
+        for m in messages.iter() {
+            handle_message!(m, ConnectionTested,
+                tested => {
+                    self.status = Some(match &tested.result {
+                        Ok(()) => format!("Connected to {}", tested.connection),
+                        Err(err) => format!("Failed to connect to {}: {}", tested.connection, err),
+                    });
+                }
+            );
+        }
+        Ok(())
+    }
 }
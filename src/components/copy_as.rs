@@ -0,0 +1,255 @@
+use std::any::Any;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use database_tree::{Database, Table as DTable};
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Margin, Rect},
+    style::{Color, Style},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use crate::app::{AppMessage, AppStateRef, GlobalMessageQueue};
+use crate::clipboard::copy_to_clipboard;
+use crate::components::command::CommandInfo;
+use crate::config::KeyConfig;
+use crate::database::Value;
+use crate::event::Key;
+use crate::export::{export, in_list, ExportFormat, InsertTarget};
+use crate::handle_message;
+
+use super::{Component, DrawableComponent, EventState};
+
+/// Posted by a `TableComponent` to copy the selected cells in a format picked from a menu.
+pub struct CopyRequest {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<Value>>,
+    pub table: Option<(Database, DTable)>,
+}
+
+impl AppMessage for CopyRequest {
+    fn as_any(&self) -> &(dyn Any + Send + Sync) {
+        self
+    }
+}
+
+/// The formats the selection can be copied in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum CopyFormat {
+    Tsv,
+    Csv,
+    Json,
+    Markdown,
+    InList,
+    Insert,
+}
+
+const FORMATS: [CopyFormat; 6] = [
+    CopyFormat::Tsv,
+    CopyFormat::Csv,
+    CopyFormat::Json,
+    CopyFormat::Markdown,
+    CopyFormat::InList,
+    CopyFormat::Insert,
+];
+
+impl CopyFormat {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Tsv => "TSV, to paste into spreadsheets",
+            Self::Csv => "CSV",
+            Self::Json => "JSON array of objects",
+            Self::Markdown => "Markdown table",
+            Self::InList => "SQL IN (...) list of a single column",
+            Self::Insert => "INSERT statements",
+        }
+    }
+
+    fn export_format(self) -> Option<ExportFormat> {
+        match self {
+            Self::Tsv => Some(ExportFormat::Tsv),
+            Self::Csv => Some(ExportFormat::Csv),
+            Self::Json => Some(ExportFormat::Json),
+            Self::Markdown => Some(ExportFormat::Markdown),
+            Self::Insert => Some(ExportFormat::Insert),
+            Self::InList => None,
+        }
+    }
+}
+
+/// Popup listing the formats the selected cells can be copied to the clipboard in.
+pub struct CopyAsComponent {
+    request: Option<CopyRequest>,
+    selected: usize,
+    status: Option<String>,
+    visible: bool,
+    key_config: KeyConfig,
+    app_state: AppStateRef,
+}
+
+impl CopyAsComponent {
+    pub fn new(key_config: KeyConfig, app_state: AppStateRef) -> Self {
+        Self {
+            request: None,
+            selected: 0,
+            status: None,
+            visible: false,
+            key_config,
+            app_state,
+        }
+    }
+
+    async fn copy(&mut self) -> Result<()> {
+        let request = match &self.request {
+            Some(request) => request,
+            None => return Ok(()),
+        };
+        let format = FORMATS[self.selected];
+        let dialect = self
+            .app_state
+            .read()
+            .await
+            .shared_pool
+            .as_ref()
+            .map(|pool| pool.dialect());
+        let text = match format.export_format() {
+            Some(export_format) => {
+                let target = match (dialect, &request.table) {
+                    (Some(dialect), Some((database, table))) if table.name != "-" => {
+                        Some(InsertTarget {
+                            dialect,
+                            database,
+                            table,
+                        })
+                    }
+                    _ => None,
+                };
+                export(export_format, &request.headers, &request.rows, target)?
+            }
+            None => in_list(
+                dialect.ok_or_else(|| anyhow!("not connected to a database"))?,
+                &request.headers,
+                &request.rows,
+            )?,
+        };
+        // Only the line break ending the last row is dropped, as trailing blanks may be data.
+        copy_to_clipboard(text.strip_suffix('\n').unwrap_or(&text))?;
+        self.status = Some(format!(
+            "Copied {} rows as {}",
+            request.rows.len(),
+            format.name()
+        ));
+        Ok(())
+    }
+}
+
+impl DrawableComponent for CopyAsComponent {
+    fn draw<B: Backend>(&self, f: &mut Frame<B>, _area: Rect, _focused: bool) -> Result<()> {
+        if self.visible {
+            let width = 60;
+            let height = FORMATS.len() as u16 + 4;
+            let area = Rect::new(
+                (f.size().width.saturating_sub(width)) / 2,
+                (f.size().height.saturating_sub(height)) / 2,
+                width.min(f.size().width),
+                height.min(f.size().height),
+            );
+            f.render_widget(Clear, area);
+            f.render_widget(
+                Block::default().title("Copy as").borders(Borders::ALL),
+                area,
+            );
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(1), Constraint::Length(1)])
+                .split(area.inner(&Margin {
+                    vertical: 1,
+                    horizontal: 1,
+                }));
+            let formats = List::new(
+                FORMATS
+                    .iter()
+                    .map(|format| ListItem::new(format.name()))
+                    .collect::<Vec<ListItem>>(),
+            )
+            .highlight_style(Style::default().bg(Color::Rgb(0xea, 0x59, 0x0b)));
+            let mut state = ListState::default();
+            state.select(Some(self.selected));
+            f.render_stateful_widget(formats, chunks[0], &mut state);
+            let status = self.status.clone().unwrap_or_else(|| {
+                format!(
+                    "{}: copy {} rows, {}: cancel",
+                    self.key_config.enter,
+                    self.request
+                        .as_ref()
+                        .map_or(0, |request| request.rows.len()),
+                    self.key_config.exit_popup
+                )
+            });
+            f.render_widget(
+                Paragraph::new(status).style(Style::default().fg(Color::DarkGray)),
+                chunks[1],
+            );
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Component for CopyAsComponent {
+    fn commands(&self, _out: &mut Vec<CommandInfo>) {}
+
+    async fn event(
+        &mut self,
+        key: Key,
+        _message_queue: &mut GlobalMessageQueue,
+    ) -> Result<EventState> {
+        if !self.visible {
+            return Ok(EventState::NotConsumed);
+        }
+        if key == self.key_config.exit_popup || self.status.is_some() {
+            self.hide();
+        } else if key == self.key_config.enter {
+            self.copy().await?;
+        } else if key == self.key_config.scroll_down {
+            self.selected = (self.selected + 1).min(FORMATS.len() - 1);
+        } else if key == self.key_config.scroll_up {
+            self.selected = self.selected.saturating_sub(1);
+        }
+        Ok(EventState::Consumed)
+    }
+
+    async fn handle_messages(&mut self, messages: &Vec<Box<dyn AppMessage>>) -> Result<()> {
+        for m in messages.iter() {
+            handle_message!(m, CopyRequest,
+                request => {
+                    self.request = Some(CopyRequest {
+                        headers: request.headers.clone(),
+                        rows: request.rows.clone(),
+                        table: request.table.clone(),
+                    });
+                    self.status = None;
+                    self.show()?;
+                }
+            );
+        }
+        Ok(())
+    }
+
+    fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    fn hide(&mut self) {
+        self.visible = false;
+        self.request = None;
+    }
+
+    fn show(&mut self) -> Result<()> {
+        self.visible = true;
+
+        Ok(())
+    }
+}
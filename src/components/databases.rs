@@ -18,6 +18,7 @@ use database_tree::{Database, DatabaseTree, DatabaseTreeItem, Table};
 use crate::app::{AppMessage, AppStateRef};
 use crate::components::command::{self, CommandInfo};
 use crate::components::connections::ConnectionEvent;
+use crate::components::import::ImportRequest;
 use crate::config::{Connection, KeyConfig};
 use crate::event::Key;
 use crate::ui::common_nav;
@@ -239,7 +240,8 @@ impl DrawableComponent for DatabasesComponent {
 #[async_trait]
 impl Component for DatabasesComponent {
     fn commands(&self, out: &mut Vec<CommandInfo>) {
-        out.push(CommandInfo::new(command::expand_collapse(&self.key_config)))
+        out.push(CommandInfo::new(command::expand_collapse(&self.key_config)));
+        out.push(CommandInfo::new(command::import(&self.key_config)));
     }
 
     async fn event(
@@ -289,6 +291,13 @@ impl Component for DatabasesComponent {
             }
         }
 
+        if key == self.key_config.import && matches!(self.focus, Focus::Tree) {
+            if let Some((database, table)) = self.tree().selected_table() {
+                message_queue.push(Box::new(ImportRequest { database, table }));
+                return Ok(EventState::Consumed);
+            }
+        }
+
         Ok(EventState::NotConsumed)
     }
 
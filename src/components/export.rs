@@ -0,0 +1,192 @@
+use std::any::Any;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use database_tree::{Database, Table as DTable};
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Margin, Rect},
+    style::{Color, Style},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use crate::app::{AppMessage, AppStateRef, GlobalMessageQueue};
+use crate::components::command::CommandInfo;
+use crate::config::{expand_path, KeyConfig};
+use crate::database::Value;
+use crate::event::Key;
+use crate::export::{export, ExportFormat, InsertTarget};
+use crate::handle_message;
+use crate::ui::textbox::TextBox;
+
+use super::{Component, DrawableComponent, EventState};
+
+/// Posted by a `TableComponent` to export the rows it has loaded.
+pub struct ExportRequest {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<Value>>,
+    pub table: Option<(Database, DTable)>,
+}
+
+impl AppMessage for ExportRequest {
+    fn as_any(&self) -> &(dyn Any + Send + Sync) {
+        self
+    }
+}
+
+/// Popup asking for the file a table is exported to. The format is picked from the extension.
+pub struct ExportComponent {
+    path: TextBox,
+    request: Option<ExportRequest>,
+    status: Option<String>,
+    visible: bool,
+    key_config: KeyConfig,
+    app_state: AppStateRef,
+}
+
+impl ExportComponent {
+    pub fn new(key_config: KeyConfig, app_state: AppStateRef) -> Self {
+        Self {
+            path: TextBox::default()
+                .with_label("Path")
+                .with_placeholder("e.g. ~/rows.csv (.csv, .tsv, .json, .md or .sql)"),
+            request: None,
+            status: None,
+            visible: false,
+            key_config,
+            app_state,
+        }
+    }
+
+    async fn export(&mut self) -> Result<()> {
+        let request = match &self.request {
+            Some(request) => request,
+            None => return Ok(()),
+        };
+        let path = PathBuf::from(self.path.get_text());
+        let format = ExportFormat::from_path(&path).ok_or_else(|| {
+            anyhow::anyhow!("unknown export format, use a .csv, .tsv, .json, .md or .sql file")
+        })?;
+        let path = expand_path(&path).unwrap_or(path);
+        let dialect = self
+            .app_state
+            .read()
+            .await
+            .shared_pool
+            .as_ref()
+            .map(|pool| pool.dialect());
+        let target = match (dialect, &request.table) {
+            (Some(dialect), Some((database, table))) if table.name != "-" => Some(InsertTarget {
+                dialect,
+                database,
+                table,
+            }),
+            _ => None,
+        };
+        let contents = export(format, &request.headers, &request.rows, target)?;
+        std::fs::write(&path, contents)?;
+        self.status = Some(format!(
+            "Exported {} rows to {}",
+            request.rows.len(),
+            path.display()
+        ));
+        Ok(())
+    }
+}
+
+impl DrawableComponent for ExportComponent {
+    fn draw<B: Backend>(&self, f: &mut Frame<B>, _area: Rect, _focused: bool) -> Result<()> {
+        if self.visible {
+            let width = 80;
+            let height = 7;
+            let area = Rect::new(
+                (f.size().width.saturating_sub(width)) / 2,
+                (f.size().height.saturating_sub(height)) / 2,
+                width.min(f.size().width),
+                height.min(f.size().height),
+            );
+            f.render_widget(Clear, area);
+            f.render_widget(Block::default().title("Export").borders(Borders::ALL), area);
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(3), Constraint::Length(1)])
+                .split(area.inner(&Margin {
+                    vertical: 1,
+                    horizontal: 1,
+                }));
+            self.path.draw(f, chunks[0], self.status.is_none())?;
+            let status = self.status.clone().unwrap_or_else(|| {
+                format!(
+                    "{}: export {} rows, {}: cancel",
+                    self.key_config.enter,
+                    self.request
+                        .as_ref()
+                        .map_or(0, |request| request.rows.len()),
+                    self.key_config.exit_popup
+                )
+            });
+            f.render_widget(
+                Paragraph::new(status).style(Style::default().fg(Color::DarkGray)),
+                chunks[1],
+            );
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Component for ExportComponent {
+    fn commands(&self, _out: &mut Vec<CommandInfo>) {}
+
+    async fn event(
+        &mut self,
+        key: Key,
+        message_queue: &mut GlobalMessageQueue,
+    ) -> Result<EventState> {
+        if !self.visible {
+            return Ok(EventState::NotConsumed);
+        }
+        if key == self.key_config.exit_popup || self.status.is_some() {
+            self.hide();
+        } else if key == self.key_config.enter {
+            self.export().await?;
+        } else {
+            self.path.event(key, message_queue).await?;
+        }
+        Ok(EventState::Consumed)
+    }
+
+    async fn handle_messages(&mut self, messages: &Vec<Box<dyn AppMessage>>) -> Result<()> {
+        for m in messages.iter() {
+            handle_message!(m, ExportRequest,
+                request => {
+                    self.request = Some(ExportRequest {
+                        headers: request.headers.clone(),
+                        rows: request.rows.clone(),
+                        table: request.table.clone(),
+                    });
+                    self.status = None;
+                    self.show()?;
+                }
+            );
+        }
+        Ok(())
+    }
+
+    fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    fn hide(&mut self) {
+        self.visible = false;
+        self.request = None;
+    }
+
+    fn show(&mut self) -> Result<()> {
+        self.visible = true;
+
+        Ok(())
+    }
+}
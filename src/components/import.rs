@@ -0,0 +1,499 @@
+use std::any::Any;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use database_tree::{Database, Table as DTable};
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Margin, Rect},
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use crate::app::{AppMessage, AppStateRef, GlobalMessageQueue};
+use crate::components::command::CommandInfo;
+use crate::components::utils::query_task::{QueryTask, QueryTaskFinished};
+use crate::config::{expand_path, KeyConfig};
+use crate::database::Dialect;
+use crate::event::Key;
+use crate::handle_message;
+use crate::import::{ColumnMapping, ColumnType, CsvData};
+use crate::ui::textbox::TextBox;
+
+use super::{Component, DrawableComponent, EventState};
+
+/// Posted by the `DatabasesComponent` to import a CSV file into the selected table.
+pub struct ImportRequest {
+    pub database: Database,
+    pub table: DTable,
+}
+
+impl AppMessage for ImportRequest {
+    fn as_any(&self) -> &(dyn Any + Send + Sync) {
+        self
+    }
+}
+
+enum Stage {
+    /// Asking for the file and, optionally, the name of a new table.
+    Path,
+    /// Previewing the file and mapping its columns.
+    Mapping,
+    Running(QueryTask),
+    Done(String),
+}
+
+/// Popup importing the rows of a CSV file into an existing table, or into a new table created
+/// with the types inferred from the file. Rows are inserted in batches within a transaction.
+pub struct ImportComponent {
+    path: TextBox,
+    new_table: TextBox,
+    new_table_focused: bool,
+    /// The table selected when the import was opened.
+    table: Option<(Database, DTable)>,
+    /// The table the rows are imported into, either `table` or the new table.
+    target: Option<(Database, DTable)>,
+    /// Whether a failed import leaves the new table behind, as DDL statements commit implicitly
+    /// in MySQL.
+    keeps_new_table: bool,
+    data: Option<CsvData>,
+    mapping: Vec<ColumnMapping>,
+    options: Vec<ColumnMapping>,
+    selected: usize,
+    stage: Stage,
+    visible: bool,
+    key_config: KeyConfig,
+    app_state: AppStateRef,
+}
+
+impl ImportComponent {
+    /// The number of rows shown in the preview.
+    const PREVIEW_ROWS: usize = 3;
+    /// The number of rejected lines listed once the import is done.
+    const MAX_REJECTED_LINES: usize = 10;
+
+    pub fn new(key_config: KeyConfig, app_state: AppStateRef) -> Self {
+        Self {
+            path: TextBox::default()
+                .with_label("Path")
+                .with_placeholder("e.g. ~/rows.csv, the first line holds the column names"),
+            new_table: TextBox::default().with_label("New table"),
+            new_table_focused: false,
+            table: None,
+            target: None,
+            keeps_new_table: false,
+            data: None,
+            mapping: vec![],
+            options: vec![],
+            selected: 0,
+            stage: Stage::Path,
+            visible: false,
+            key_config,
+            app_state,
+        }
+    }
+
+    fn set(&mut self, database: Database, table: DTable) -> Result<()> {
+        self.new_table.reset();
+        self.new_table
+            .set_placeholder(format!("Leave empty to import into `{}`", table.name));
+        self.new_table_focused = false;
+        self.table = Some((database, table));
+        self.target = self.table.clone();
+        self.data = None;
+        self.stage = Stage::Path;
+        self.show()
+    }
+
+    /// Reads the file and maps its columns to the columns of the target table, or to new columns
+    /// if a new table name was entered.
+    async fn load(&mut self) -> Result<()> {
+        let (database, table) = match &self.table {
+            Some(table) => table.clone(),
+            None => return Ok(()),
+        };
+        let path = PathBuf::from(self.path.get_text());
+        let path = expand_path(&path).unwrap_or(path);
+        let data = CsvData::parse(&std::fs::read_to_string(&path)?)?;
+
+        let new_table = self.new_table.get_text().trim().to_string();
+        if new_table.is_empty() {
+            let columns = match self.app_state.read().await.shared_pool.as_ref() {
+                Some(pool) => pool.get_columns(&table).await?,
+                None => return Ok(()),
+            }
+            .into_iter()
+            .filter_map(|column| column.name)
+            .collect::<Vec<String>>();
+            self.mapping = data.default_mapping(&columns);
+            self.options = std::iter::once(ColumnMapping::Skip)
+                .chain(columns.into_iter().map(ColumnMapping::Column))
+                .collect();
+            self.target = Some((database, table));
+        } else {
+            self.mapping = (0..data.headers.len())
+                .map(|column| ColumnMapping::New(data.column_type(column)))
+                .collect();
+            self.options = std::iter::once(ColumnMapping::Skip)
+                .chain(
+                    ColumnType::ALL
+                        .iter()
+                        .map(|r#type| ColumnMapping::New(*r#type)),
+                )
+                .collect();
+            self.target = Some((
+                database,
+                DTable {
+                    name: new_table,
+                    create_time: None,
+                    update_time: None,
+                    engine: None,
+                    schema: table.schema,
+                    database: table.database,
+                },
+            ));
+        }
+        self.data = Some(data);
+        self.selected = 0;
+        self.stage = Stage::Mapping;
+        Ok(())
+    }
+
+    /// Moves the mapping of the selected CSV column to the next or previous option.
+    fn cycle_mapping(&mut self, forward: bool) {
+        if let Some(mapping) = self.mapping.get_mut(self.selected) {
+            let len = self.options.len();
+            let position = self
+                .options
+                .iter()
+                .position(|option| option == mapping)
+                .unwrap_or(0);
+            let next = if forward {
+                (position + 1) % len
+            } else {
+                (position + len - 1) % len
+            };
+            *mapping = self.options[next].clone();
+        }
+    }
+
+    async fn start(&mut self) -> Result<()> {
+        let ((database, table), data) = match (&self.target, &self.data) {
+            (Some(target), Some(data)) => (target, data),
+            _ => return Ok(()),
+        };
+        let pool = match self.app_state.read().await.shared_pool.clone() {
+            Some(pool) => pool,
+            None => return Ok(()),
+        };
+        let statements = data.import_statements(pool.dialect(), database, table, &self.mapping)?;
+        self.keeps_new_table = pool.dialect() == Dialect::MySql && self.target != self.table;
+        let task = QueryTask::spawn(
+            pool,
+            self.app_state.message_sender(),
+            move |pool, handle| async move {
+                let mut transaction = pool.begin(&handle).await?;
+                let mut inserted = 0;
+                for statement in &statements {
+                    match transaction.execute(statement).await {
+                        Ok(rows) => inserted += rows,
+                        Err(err) => {
+                            transaction.rollback().await?;
+                            return Err(err);
+                        }
+                    }
+                }
+                transaction.commit().await?;
+                Ok(inserted)
+            },
+        );
+        self.stage = Stage::Running(task);
+        Ok(())
+    }
+
+    fn finish(&mut self, result: Result<u64>) {
+        let table = self
+            .target
+            .as_ref()
+            .map_or(String::new(), |(_, table)| table.name.clone());
+        let status = match result {
+            Ok(inserted) => {
+                let mut status = format!("Inserted {} rows into `{}`", inserted, table);
+                let rejected = self
+                    .data
+                    .as_ref()
+                    .map_or(&[][..], |data| data.rejected_lines.as_slice());
+                if !rejected.is_empty() {
+                    status.push_str(&format!(
+                        ", rejected {} lines: {}",
+                        rejected.len(),
+                        rejected
+                            .iter()
+                            .take(Self::MAX_REJECTED_LINES)
+                            .map(|line| line.to_string())
+                            .collect::<Vec<String>>()
+                            .join(", ")
+                    ));
+                    if rejected.len() > Self::MAX_REJECTED_LINES {
+                        status.push_str(", \u{2026}");
+                    }
+                }
+                status
+            }
+            Err(err) if self.keeps_new_table => format!(
+                "Import failed, no rows were inserted but `{}` was created: {}",
+                table, err
+            ),
+            Err(err) => format!("Import failed, no rows were inserted: {}", err),
+        };
+        self.stage = Stage::Done(status);
+    }
+
+    fn mapping_lines(&self, data: &CsvData) -> Vec<Spans<'static>> {
+        data.headers
+            .iter()
+            .zip(self.mapping.iter())
+            .enumerate()
+            .map(|(index, (header, mapping))| {
+                let style = if index == self.selected {
+                    Style::default().bg(Color::Rgb(0xea, 0x59, 0x0b))
+                } else {
+                    Style::default()
+                };
+                let target = match mapping {
+                    ColumnMapping::Skip => "(skip)".to_string(),
+                    ColumnMapping::Column(column) => column.clone(),
+                    ColumnMapping::New(r#type) => format!("new {:?} column", r#type),
+                };
+                Spans::from(vec![
+                    Span::styled(header.clone(), style.add_modifier(Modifier::BOLD)),
+                    Span::raw(format!(" \u{2192} {}", target)),
+                ])
+            })
+            .collect()
+    }
+
+    fn preview_lines(data: &CsvData) -> Vec<Spans<'static>> {
+        std::iter::once(Spans::from(Span::styled(
+            data.headers.join(" | "),
+            Style::default().add_modifier(Modifier::BOLD),
+        )))
+        .chain(data.rows.iter().take(Self::PREVIEW_ROWS).map(|row| {
+            Spans::from(
+                row.iter()
+                    .map(|value| value.as_deref().unwrap_or("NULL"))
+                    .collect::<Vec<&str>>()
+                    .join(" | "),
+            )
+        }))
+        .collect()
+    }
+}
+
+impl DrawableComponent for ImportComponent {
+    fn draw<B: Backend>(&self, f: &mut Frame<B>, _area: Rect, _focused: bool) -> Result<()> {
+        if !self.visible {
+            return Ok(());
+        }
+        let width = 80;
+        let height = match self.stage {
+            Stage::Path => 10,
+            Stage::Mapping => 20,
+            Stage::Running(_) | Stage::Done(_) => 4,
+        };
+        let area = Rect::new(
+            (f.size().width.saturating_sub(width)) / 2,
+            (f.size().height.saturating_sub(height)) / 2,
+            width.min(f.size().width),
+            height.min(f.size().height),
+        );
+        let title = match &self.target {
+            Some((_, table)) => format!("Import into `{}`", table.name),
+            None => "Import".to_string(),
+        };
+        f.render_widget(Clear, area);
+        f.render_widget(Block::default().title(title).borders(Borders::ALL), area);
+        let inner = area.inner(&Margin {
+            vertical: 1,
+            horizontal: 1,
+        });
+        let hint = Style::default().fg(Color::DarkGray);
+
+        match (&self.stage, &self.data) {
+            (Stage::Path, _) => {
+                let chunks = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([
+                        Constraint::Length(3),
+                        Constraint::Length(3),
+                        Constraint::Length(1),
+                    ])
+                    .split(inner);
+                self.path.draw(f, chunks[0], !self.new_table_focused)?;
+                self.new_table.draw(f, chunks[1], self.new_table_focused)?;
+                f.render_widget(
+                    Paragraph::new(format!(
+                        "Tab: switch field, {}: preview, {}: cancel",
+                        self.key_config.enter, self.key_config.exit_popup
+                    ))
+                    .style(hint),
+                    chunks[2],
+                );
+            }
+            (Stage::Mapping, Some(data)) => {
+                let chunks = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([
+                        Constraint::Length(Self::PREVIEW_ROWS as u16 + 3),
+                        Constraint::Min(1),
+                        Constraint::Length(1),
+                        Constraint::Length(1),
+                    ])
+                    .split(inner);
+                f.render_widget(
+                    Paragraph::new(Self::preview_lines(data))
+                        .block(Block::default().title("Preview").borders(Borders::ALL)),
+                    chunks[0],
+                );
+                let offset = self
+                    .selected
+                    .saturating_sub(chunks[1].height.saturating_sub(1) as usize);
+                f.render_widget(
+                    Paragraph::new(self.mapping_lines(data)).scroll((offset as u16, 0)),
+                    chunks[1],
+                );
+                f.render_widget(
+                    Paragraph::new(format!(
+                        "{} rows, {} rejected lines",
+                        data.rows.len(),
+                        data.rejected_lines.len()
+                    )),
+                    chunks[2],
+                );
+                f.render_widget(
+                    Paragraph::new(format!(
+                        "{}/{}: select column, {}/{}: change mapping, {}: import, {}: back",
+                        self.key_config.move_up,
+                        self.key_config.move_down,
+                        self.key_config.scroll_left,
+                        self.key_config.scroll_right,
+                        self.key_config.enter,
+                        self.key_config.exit_popup
+                    ))
+                    .style(hint),
+                    chunks[3],
+                );
+            }
+            (Stage::Running(task), _) => {
+                f.render_widget(
+                    Paragraph::new(
+                        task.progress_message("Importing", self.key_config.cancel_query),
+                    )
+                    .style(hint),
+                    inner,
+                );
+            }
+            (Stage::Done(status), _) => {
+                f.render_widget(Paragraph::new(status.as_str()), inner);
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Component for ImportComponent {
+    fn commands(&self, _out: &mut Vec<CommandInfo>) {}
+
+    async fn event(
+        &mut self,
+        key: Key,
+        message_queue: &mut GlobalMessageQueue,
+    ) -> Result<EventState> {
+        if !self.visible {
+            return Ok(EventState::NotConsumed);
+        }
+        match self.stage {
+            Stage::Path => {
+                if key == self.key_config.exit_popup {
+                    self.hide();
+                } else if key == self.key_config.enter {
+                    self.load().await?;
+                } else if key == Key::Tab {
+                    self.new_table_focused = !self.new_table_focused;
+                } else if self.new_table_focused {
+                    self.new_table.event(key, message_queue).await?;
+                } else {
+                    self.path.event(key, message_queue).await?;
+                }
+            }
+            Stage::Mapping => {
+                if key == self.key_config.exit_popup {
+                    self.target = self.table.clone();
+                    self.stage = Stage::Path;
+                } else if key == self.key_config.enter {
+                    self.start().await?;
+                } else if key == self.key_config.move_up || key == self.key_config.scroll_up {
+                    self.selected = self.selected.saturating_sub(1);
+                } else if key == self.key_config.move_down || key == self.key_config.scroll_down {
+                    self.selected = (self.selected + 1).min(self.mapping.len().saturating_sub(1));
+                } else if key == self.key_config.scroll_left || key == self.key_config.focus_left {
+                    self.cycle_mapping(false);
+                } else if key == self.key_config.scroll_right || key == self.key_config.focus_right
+                {
+                    self.cycle_mapping(true);
+                }
+            }
+            Stage::Running(_) => {
+                if key == self.key_config.cancel_query {
+                    if let Stage::Running(task) = std::mem::replace(&mut self.stage, Stage::Path) {
+                        task.cancel();
+                    }
+                    self.finish(Err(anyhow!("cancelled")));
+                }
+            }
+            Stage::Done(_) => self.hide(),
+        }
+        Ok(EventState::Consumed)
+    }
+
+    async fn handle_messages(&mut self, messages: &Vec<Box<dyn AppMessage>>) -> Result<()> {
+        for m in messages.iter() {
+            handle_message!(m, ImportRequest,
+                request => {
+                    self.set(request.database.clone(), request.table.clone())?;
+                }
+            );
+            handle_message!(m, QueryTaskFinished<u64>,
+                finished => {
+                    if matches!(&self.stage, Stage::Running(task) if task.id() == finished.task_id) {
+                        if let Some(result) = finished.take_result() {
+                            self.finish(result);
+                        }
+                    }
+                }
+            );
+        }
+        Ok(())
+    }
+
+    fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    fn hide(&mut self) {
+        self.visible = false;
+        self.data = None;
+        self.stage = Stage::Path;
+    }
+
+    fn show(&mut self) -> Result<()> {
+        self.visible = true;
+
+        Ok(())
+    }
+}
@@ -5,17 +5,25 @@ use unicode_width::UnicodeWidthChar;
 
 pub use command::{CommandInfo, CommandText};
 pub use completion::CompletionComponent;
+pub use confirm::ConfirmComponent;
+pub use connection_form::ConnectionFormComponent;
 pub use connections::ConnectionsComponent;
+pub use copy_as::CopyAsComponent;
 pub use databases::DatabasesComponent;
 #[cfg(debug_assertions)]
 pub use debug::DebugComponent;
 pub use error::ErrorComponent;
+pub use export::ExportComponent;
 pub use help::HelpComponent;
+pub use import::ImportComponent;
+pub use pending_changes::PendingChangesComponent;
 pub use properties::PropertiesComponent;
 pub use record_table::RecordTableComponent;
+pub use references::ReferencesComponent;
+pub use row_form::RowFormComponent;
 pub use sql_editor::SqlEditorComponent;
 pub use tab::TabToolbar;
-pub use table::TableComponent;
+pub use table::{Sort, TableComponent};
 pub use table_status::TableStatusComponent;
 pub use table_value::TableValueComponent;
 
@@ -23,12 +31,20 @@ use crate::app::AppMessage;
 
 pub mod command;
 pub mod completion;
+pub mod confirm;
+pub mod connection_form;
 pub mod connections;
+pub mod copy_as;
 pub mod databases;
 pub mod error;
+pub mod export;
 pub mod help;
+pub mod import;
+pub mod pending_changes;
 pub mod properties;
 pub mod record_table;
+pub mod references;
+pub mod row_form;
 pub mod sql_editor;
 pub mod tab;
 pub mod table;
@@ -0,0 +1,139 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Style},
+    text::Spans,
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+use crate::app::GlobalMessageQueue;
+use crate::components::command::CommandInfo;
+use crate::config::KeyConfig;
+use crate::event::Key;
+
+use super::{Component, DrawableComponent, EventState};
+
+/// A statement staged from the records tab, together with the number of rows it has to affect.
+#[derive(Clone)]
+pub struct PendingChange {
+    pub statement: String,
+    pub rows: u64,
+}
+
+/// Lists the INSERT/UPDATE/DELETE statements staged from the records tab. They are executed in a
+/// single transaction once committed.
+pub struct PendingChangesComponent {
+    changes: Vec<PendingChange>,
+    visible: bool,
+    key_config: KeyConfig,
+}
+
+impl PendingChangesComponent {
+    /// The maximum number of statements shown at once.
+    const MAX_LINES: usize = 5;
+
+    pub fn new(key_config: KeyConfig) -> Self {
+        Self {
+            changes: vec![],
+            visible: true,
+            key_config,
+        }
+    }
+
+    /// Stages `statement`, which has to affect exactly `rows` rows.
+    pub fn push(&mut self, statement: String, rows: u64) {
+        self.changes.push(PendingChange { statement, rows });
+    }
+
+    pub fn clear(&mut self) {
+        self.changes.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub fn changes(&self) -> &[PendingChange] {
+        &self.changes
+    }
+
+    pub fn statements(&self) -> Vec<&str> {
+        self.changes
+            .iter()
+            .map(|change| change.statement.as_str())
+            .collect()
+    }
+
+    /// The height of the panel, or 0 if it is hidden or there is nothing to show.
+    pub fn height(&self) -> u16 {
+        if !self.visible || self.changes.is_empty() {
+            return 0;
+        }
+        self.changes.len().min(Self::MAX_LINES) as u16 + 2
+    }
+}
+
+impl DrawableComponent for PendingChangesComponent {
+    fn draw<B: Backend>(&self, f: &mut Frame<B>, area: Rect, focused: bool) -> Result<()> {
+        if self.height() == 0 {
+            return Ok(());
+        }
+        let lines = self
+            .changes
+            .iter()
+            .skip(self.changes.len().saturating_sub(Self::MAX_LINES))
+            .map(|change| Spans::from(change.statement.as_str()))
+            .collect::<Vec<Spans>>();
+        let title = format!(
+            "Pending changes ({}) [{}: commit, {}: discard]",
+            self.changes.len(),
+            self.key_config.commit_changes,
+            self.key_config.discard_changes
+        );
+        f.render_widget(
+            Paragraph::new(lines)
+                .block(Block::default().title(title).borders(Borders::ALL))
+                .style(if focused {
+                    Style::default().fg(Color::Yellow)
+                } else {
+                    Style::default().fg(Color::DarkGray)
+                }),
+            area,
+        );
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Component for PendingChangesComponent {
+    fn commands(&self, _out: &mut Vec<CommandInfo>) {}
+
+    async fn event(
+        &mut self,
+        key: Key,
+        _message_queue: &mut GlobalMessageQueue,
+    ) -> Result<EventState> {
+        if key == self.key_config.toggle_pending_changes {
+            self.toggle_visible()?;
+            return Ok(EventState::Consumed);
+        }
+        Ok(EventState::NotConsumed)
+    }
+
+    fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    fn hide(&mut self) {
+        self.visible = false;
+    }
+
+    fn show(&mut self) -> Result<()> {
+        self.visible = true;
+
+        Ok(())
+    }
+}
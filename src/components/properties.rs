@@ -17,7 +17,7 @@ use crate::components::databases::DatabaseEvent;
 use crate::components::tab::{Tab, TabType};
 use crate::components::{Drawable, TableComponent};
 use crate::config::KeyConfig;
-use crate::database::{Column, TableRow};
+use crate::database::{Column, TableRow, Value};
 use crate::handle_message;
 
 use super::{Component, EventState};
@@ -96,8 +96,8 @@ impl PropertiesComponent {
             self.column_table.update(
                 columns
                     .iter()
-                    .map(|c| c.columns())
-                    .collect::<Vec<Vec<String>>>(),
+                    .map(|c| c.columns().into_iter().map(Value::Text).collect())
+                    .collect::<Vec<Vec<Value>>>(),
                 columns.get(0).unwrap().fields(),
                 database.clone(),
                 table.clone(),
@@ -108,8 +108,8 @@ impl PropertiesComponent {
             self.constraint_table.update(
                 constraints
                     .iter()
-                    .map(|c| c.columns())
-                    .collect::<Vec<Vec<String>>>(),
+                    .map(|c| c.columns().into_iter().map(Value::Text).collect())
+                    .collect::<Vec<Vec<Value>>>(),
                 constraints.get(0).unwrap().fields(),
                 database.clone(),
                 table.clone(),
@@ -120,8 +120,8 @@ impl PropertiesComponent {
             self.foreign_key_table.update(
                 foreign_keys
                     .iter()
-                    .map(|c| c.columns())
-                    .collect::<Vec<Vec<String>>>(),
+                    .map(|c| c.columns().into_iter().map(Value::Text).collect())
+                    .collect::<Vec<Vec<Value>>>(),
                 foreign_keys.get(0).unwrap().fields(),
                 database.clone(),
                 table.clone(),
@@ -132,8 +132,8 @@ impl PropertiesComponent {
             self.index_table.update(
                 indexes
                     .iter()
-                    .map(|c| c.columns())
-                    .collect::<Vec<Vec<String>>>(),
+                    .map(|c| c.columns().into_iter().map(Value::Text).collect())
+                    .collect::<Vec<Vec<Value>>>(),
                 indexes.get(0).unwrap().fields(),
                 database.clone(),
                 table.clone(),
@@ -1,21 +1,34 @@
+use std::sync::Arc;
+
 use anyhow::Result;
 use async_trait::async_trait;
 use tui::{
     backend::Backend,
     layout::{Constraint, Direction, Layout, Rect},
+    widgets::Paragraph,
     Frame,
 };
 
-use database_tree::{Database, Table as DTable};
+use database_tree::{Child, Database, Table as DTable};
 
 use crate::app::{AppMessage, AppStateRef, GlobalMessageQueue};
-use crate::components::command::CommandInfo;
+use crate::components::command::{self, CommandInfo};
 use crate::components::databases::DatabaseEvent;
 use crate::components::databases::DatabaseEvent::TableSelected;
+use crate::components::pending_changes::PendingChange;
+use crate::components::references::ReferencingRows;
 use crate::components::tab::{Tab, TabType};
+use crate::components::utils::query_task::{QueryTask, QueryTaskCancelled, QueryTaskFinished};
 use crate::components::EventState::{Consumed, NotConsumed};
-use crate::components::{Drawable, DrawableComponent, TableComponent};
+use crate::components::{
+    ConfirmComponent, Drawable, DrawableComponent, PendingChangesComponent, ReferencesComponent,
+    RowFormComponent, Sort, TableComponent,
+};
 use crate::config::KeyConfig;
+use crate::database::{
+    Dialect, OrderBy, Pool, QueryHandle, RecordsRequest, Reference, TableRow, Value,
+    RECORDS_LIMIT_PER_PAGE,
+};
 use crate::ui::textbox::TextBox;
 use crate::{handle_message, Key};
 
@@ -24,30 +37,129 @@ use super::{Component, EventState};
 pub enum Focus {
     Table,
     Filter,
+    OrderBy,
+    Editor,
+}
+
+type RecordPage = (Vec<String>, Vec<Vec<Value>>);
+
+/// A cell being edited, together with the primary key of its row.
+struct CellEdit {
+    row: usize,
+    column: usize,
+    keys: Vec<(String, Value)>,
+    /// Whether the cell is set to NULL rather than to the entered text.
+    null: bool,
+}
+
+/// A running records query, together with whether it replaces or extends the loaded rows.
+struct RecordsLoad {
+    task: QueryTask,
+    next_page: bool,
+}
+
+/// A running count of the rows referencing the selected row, together with the title of the
+/// popup listing them.
+struct ReferencesLoad {
+    task: QueryTask,
+    title: String,
+}
+
+/// A table shown in the Records tab, together with its filter, order and selected cell. Visits
+/// are stacked when following foreign keys, so that going back restores them.
+struct Visit {
+    database: Database,
+    table: DTable,
+    filter: String,
+    order_by: String,
+    selection: Option<(usize, usize)>,
 }
 
 pub struct RecordTableComponent {
     pub filter: TextBox,
+    pub order_by: TextBox,
     pub table: TableComponent,
     pub focus: Focus,
     key_config: KeyConfig,
     database: Option<Database>,
     dtable: Option<DTable>,
+    page: u16,
+    /// The order of the loaded records, as parsed from `order_by` on the last reload.
+    sorted_by: Vec<OrderBy>,
+    loading: Option<RecordsLoad>,
+    /// The running commit of the pending changes.
+    committing: Option<QueryTask>,
+    /// The id of the cancelled commit, until it has ended and the records are reloaded.
+    cancelled_commit: Option<usize>,
+    finding_references: Option<ReferencesLoad>,
+    editor: TextBox,
+    edit: Option<CellEdit>,
+    pending_changes: PendingChangesComponent,
+    row_form: RowFormComponent,
+    confirm: ConfirmComponent,
+    references: ReferencesComponent,
+    primary_keys: Option<Vec<String>>,
+    /// The tables visited before following foreign keys, the last one visited on top.
+    history: Vec<Visit>,
+    /// The visit restored once the table it shows has been selected.
+    pending_visit: Option<Visit>,
+    /// The cell selected once the first page of records has been loaded.
+    pending_selection: Option<(usize, usize)>,
     app_state: AppStateRef,
 }
 
 impl<B: Backend> Drawable<B> for RecordTableComponent {
     fn draw(&mut self, f: &mut Frame<B>, area: Rect, focused: bool) -> Result<()> {
+        let cancel_key = self.key_config.cancel_query;
+        let progress = if let Some(task) = &self.committing {
+            Some(task.progress_message("Committing changes", cancel_key))
+        } else if let Some(load) = &self.finding_references {
+            Some(
+                load.task
+                    .progress_message("Counting referencing rows", cancel_key),
+            )
+        } else {
+            self.loading
+                .as_ref()
+                .map(|load| load.task.progress_message("Loading records", cancel_key))
+        };
         let layout = Layout::default()
             .direction(Direction::Vertical)
-            .constraints(vec![Constraint::Length(3), Constraint::Length(5)])
+            .constraints(vec![
+                Constraint::Length(3),
+                Constraint::Min(1),
+                Constraint::Length(self.pending_changes.height()),
+                Constraint::Length(if progress.is_some() { 1 } else { 0 }),
+            ])
             .split(area);
 
         self.table
             .draw(f, layout[1], focused && matches!(self.focus, Focus::Table))?;
 
-        self.filter
-            .draw(f, layout[0], focused && matches!(self.focus, Focus::Filter))?;
+        if let Some(progress) = progress {
+            f.render_widget(Paragraph::new(progress), layout[3]);
+        }
+
+        self.pending_changes.draw(f, layout[2], focused)?;
+
+        if matches!(self.focus, Focus::Editor) {
+            self.editor.draw(f, layout[0], focused)?;
+        } else {
+            let inputs = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(70), Constraint::Percentage(30)])
+                .split(layout[0]);
+            self.filter
+                .draw(f, inputs[0], focused && matches!(self.focus, Focus::Filter))?;
+            self.order_by.draw(
+                f,
+                inputs[1],
+                focused && matches!(self.focus, Focus::OrderBy),
+            )?;
+        }
+        self.row_form.draw(f, area, focused)?;
+        self.confirm.draw(f, area, focused)?;
+        self.references.draw(f, area, focused)?;
         Ok(())
     }
 }
@@ -68,62 +180,727 @@ impl RecordTableComponent {
             filter: TextBox::default()
                 .with_placeholder("Enter SQL expression to filter records")
                 .with_completion(key_config.clone()),
+            order_by: TextBox::default()
+                .with_label("ORDER BY")
+                .with_placeholder("column [DESC], ..."),
             table: TableComponent::new(key_config.clone()),
+            editor: TextBox::default(),
+            edit: None,
+            pending_changes: PendingChangesComponent::new(key_config.clone()),
+            row_form: RowFormComponent::new(key_config.clone()),
+            confirm: ConfirmComponent::new(key_config.clone()),
+            references: ReferencesComponent::new(key_config.clone()),
+            primary_keys: None,
+            history: vec![],
+            pending_visit: None,
+            pending_selection: None,
             focus: Focus::Table,
             key_config,
             app_state,
             database: None,
             dtable: None,
+            page: 0,
+            sorted_by: vec![],
+            loading: None,
+            committing: None,
+            cancelled_commit: None,
+            finding_references: None,
         }
     }
 
     async fn update_table(&mut self, database: Database, table: DTable) -> Result<()> {
         self.database = Some(database);
         self.dtable = Some(table);
+        self.primary_keys = None;
         self.reload_results_table().await
     }
 
+    fn filter_expression(&self) -> Option<String> {
+        let filter = self.filter.get_text();
+        if filter.is_empty() {
+            None
+        } else {
+            Some(filter)
+        }
+    }
+
     async fn reload_results_table(&mut self) -> Result<()> {
-        if let Some(database) = &self.database {
-            if let Some(table) = &self.dtable {
-                let mut headers: Vec<String> = vec![];
-                let mut rows: Vec<Vec<String>> = vec![];
-                if let Some(pool) = self.app_state.read().await.shared_pool.as_ref() {
-                    let filter = self.filter.get_text();
-                    let res = pool
-                        .get_records(
-                            database,
-                            table,
-                            0,
-                            if filter.is_empty() {
-                                None
-                            } else {
-                                Some(filter)
-                            },
-                        )
-                        .await?;
-                    headers = res.0;
-                    rows = res.1;
-                }
-                self.table
-                    .update(rows, headers, database.clone(), table.clone());
-                self.filter.set_label(table.clone().name);
-                // self.filter.set_table(table.clone());
+        self.sorted_by = OrderBy::parse_list(&self.order_by.get_text())?;
+        self.page = 0;
+        if let Some(table) = &self.dtable {
+            self.filter.set_label(table.clone().name);
+        }
+        self.spawn_load(false).await;
+        Ok(())
+    }
+
+    /// Fails if changes are pending. Reloading the records drops the marks of the staged changes,
+    /// which would then be committed against rows no longer shown.
+    fn ensure_no_pending_changes(&self) -> Result<()> {
+        if !self.pending_changes.is_empty() || self.committing.is_some() {
+            anyhow::bail!("Commit or discard the pending changes before loading other records");
+        }
+        Ok(())
+    }
+
+    /// Fetches the page following the last loaded one in the background. The rows are appended
+    /// to the table once they arrive.
+    async fn load_next_page(&mut self) {
+        if self.table.eod || self.loading.is_some() {
+            return;
+        }
+        self.spawn_load(true).await;
+    }
+
+    /// Starts loading either the first page or the page after `self.page`, cancelling any load
+    /// which is still running.
+    async fn spawn_load(&mut self, next_page: bool) {
+        if let Some(load) = self.loading.take() {
+            load.task.cancel();
+        }
+        let (database, table) = match (&self.database, &self.dtable) {
+            (Some(database), Some(table)) => (database.clone(), table.clone()),
+            _ => return,
+        };
+        let page = if next_page { self.page + 1 } else { 0 };
+        let request = RecordsRequest {
+            filter: self.filter_expression(),
+            order_by: self.sorted_by.clone(),
+            ..RecordsRequest::page(page)
+        };
+        let app_state = self.app_state.read().await;
+        if let Some(pool) = app_state.shared_pool.clone() {
+            let task = QueryTask::spawn(
+                pool,
+                self.app_state.message_sender(),
+                move |pool, _| async move { pool.get_records(&database, &table, &request).await },
+            );
+            self.loading = Some(RecordsLoad { task, next_page });
+        }
+    }
+
+    /// Applies a loaded page. The table is marked as complete once a short page is returned.
+    fn apply_page(&mut self, next_page: bool, (headers, rows): RecordPage) {
+        let is_last_page = rows.len() < RECORDS_LIMIT_PER_PAGE as usize;
+        if next_page {
+            self.page += 1;
+            self.table.append_rows(rows);
+        } else if let (Some(database), Some(table)) = (&self.database, &self.dtable) {
+            let sort = self.sorted_by.first().and_then(|order_by| {
+                Some(Sort {
+                    column: headers
+                        .iter()
+                        .position(|header| *header == order_by.column)?,
+                    descending: order_by.descending,
+                })
+            });
+            self.table
+                .update(rows, headers, database.clone(), table.clone());
+            if sort.is_some() {
+                self.table.set_database_sort(sort);
+            }
+            if let Some((row, column)) = self.pending_selection.take() {
+                self.table.select_position(row, column);
+            }
+        }
+        if is_last_page {
+            self.table.end();
+        }
+    }
+
+    /// Reloads the records sorted by the selected column in the database, so that the order
+    /// applies to the whole table rather than to the loaded rows only. Repeating it switches
+    /// from ascending to descending order, and then back to the order of the table.
+    async fn sort_in_database(&mut self) -> Result<()> {
+        self.ensure_no_pending_changes()?;
+        let order_by = self.table.next_sort().and_then(|sort| {
+            Some(OrderBy {
+                column: self.table.headers.get(sort.column)?.clone(),
+                descending: sort.descending,
+            })
+        });
+        match order_by {
+            Some(order_by) => self.order_by.set_str(&order_by.to_string()),
+            None => self.order_by.reset(),
+        }
+        self.reload_results_table().await
+    }
+
+    /// Cancels the running load and count of referencing rows if `key` is the cancel key.
+    fn cancel_load(&mut self, key: Key) -> bool {
+        if key != self.key_config.cancel_query {
+            return false;
+        }
+        let mut cancelled = false;
+        if let Some(load) = self.finding_references.take() {
+            load.task.cancel();
+            cancelled = true;
+        }
+        if let Some(load) = self.loading.take() {
+            load.task.cancel();
+            cancelled = true;
+        }
+        cancelled
+    }
+
+    /// Opens the editor on the selected cell. Fails if the table has no primary key, since the
+    /// edited row could not be identified in the generated UPDATE.
+    async fn start_edit(&mut self) -> Result<()> {
+        let (row, column) = match self.table.selected_cell_position() {
+            Some(position) => position,
+            None => return Ok(()),
+        };
+        let keys = self.row_keys(&[row], "edited").await?.remove(0);
+        let value = &self.table.rows[row][column];
+        let null = value.is_null();
+        self.editor.set_label(self.table.headers[column].clone());
+        self.editor.set_str(&if null {
+            String::new()
+        } else {
+            value.to_string()
+        });
+        self.edit = Some(CellEdit {
+            row,
+            column,
+            keys,
+            null,
+        });
+        self.set_edit_null(null);
+        self.focus = Focus::Editor;
+        Ok(())
+    }
+
+    /// Sets the edited cell to NULL, clearing the entered text, or back to the entered text.
+    fn set_edit_null(&mut self, null: bool) {
+        if let Some(edit) = self.edit.as_mut() {
+            edit.null = null;
+        }
+        if null {
+            self.editor.set_str(&String::new());
+            self.editor.set_placeholder("NULL");
+        } else {
+            self.editor.set_placeholder(format!(
+                "Press {} to set the value to NULL",
+                self.key_config.set_null
+            ));
+        }
+    }
+
+    /// Opens the form used to enter the values of a new row.
+    async fn start_insert(&mut self) -> Result<()> {
+        if let Some(table) = &self.dtable {
+            if let Some(pool) = self.app_state.read().await.shared_pool.as_ref() {
+                let columns = pool.get_columns(table).await?;
+                self.row_form.set(columns)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Stages an INSERT of the values entered in the row form.
+    async fn stage_insert(&mut self, values: Vec<(String, Option<String>)>) {
+        if let (Some(database), Some(table)) = (&self.database, &self.dtable) {
+            if let Some(pool) = self.app_state.read().await.shared_pool.as_ref() {
+                let statement = pool.dialect().insert(database, table, &values);
+                self.pending_changes.push(statement, 1);
             }
         }
+    }
 
+    /// Stages a DELETE of the selected rows.
+    async fn stage_delete(&mut self) -> Result<()> {
+        let rows = match self.table.selected_rows() {
+            Some(rows) => rows,
+            None => return Ok(()),
+        };
+        let keys = self
+            .row_keys(&rows.clone().collect::<Vec<usize>>(), "deleted")
+            .await?;
+        if let (Some(database), Some(table)) = (&self.database, &self.dtable) {
+            if let Some(pool) = self.app_state.read().await.shared_pool.as_ref() {
+                let statement = pool.dialect().delete(database, table, &keys);
+                self.table.mark_deleted_rows(rows);
+                self.pending_changes.push(statement, keys.len() as u64);
+            }
+        }
         Ok(())
     }
 
+    /// Returns the primary key `(column, value)` pairs identifying each of `rows`. Fails if the
+    /// table has no primary key, since the rows could not be identified in a statement.
+    async fn row_keys(
+        &mut self,
+        rows: &[usize],
+        action: &str,
+    ) -> Result<Vec<Vec<(String, Value)>>> {
+        let primary_keys = self.primary_keys().await?;
+        if primary_keys.is_empty() {
+            anyhow::bail!(
+                "Table `{}` has no primary key, so its rows cannot be {}",
+                self.dtable.as_ref().map_or("", |table| table.name.as_str()),
+                action
+            );
+        }
+        let mut indexes = vec![];
+        for key in primary_keys.iter() {
+            indexes.push(
+                self.table
+                    .headers
+                    .iter()
+                    .position(|header| header == key)
+                    .ok_or_else(|| anyhow::anyhow!("primary key column `{}` is not loaded", key))?,
+            );
+        }
+        Ok(rows
+            .iter()
+            .map(|row| {
+                primary_keys
+                    .iter()
+                    .zip(indexes.iter())
+                    .map(|(key, index)| (key.clone(), self.table.rows[*row][*index].clone()))
+                    .collect()
+            })
+            .collect())
+    }
+
+    /// Returns the primary key columns of the current table, fetching them on first use.
+    async fn primary_keys(&mut self) -> Result<Vec<String>> {
+        if let Some(keys) = &self.primary_keys {
+            return Ok(keys.clone());
+        }
+        let (database, table) = match (&self.database, &self.dtable) {
+            (Some(database), Some(table)) => (database, table),
+            _ => return Ok(vec![]),
+        };
+        let keys = match self.app_state.read().await.shared_pool.as_ref() {
+            Some(pool) => pool.get_primary_keys(database, table).await?,
+            None => vec![],
+        };
+        self.primary_keys = Some(keys.clone());
+        Ok(keys)
+    }
+
+    /// Pins the primary key columns to the left of the table.
+    async fn freeze_primary_key(&mut self) -> Result<()> {
+        let primary_keys = self.primary_keys().await?;
+        if primary_keys.is_empty() {
+            anyhow::bail!(
+                "Table `{}` has no primary key",
+                self.dtable.as_ref().map_or("", |table| table.name.as_str())
+            );
+        }
+        self.table.freeze_columns(&primary_keys);
+        Ok(())
+    }
+
+    /// Opens the table referenced by the foreign key of the selected cell, filtered to the rows
+    /// holding its value. The current table is stacked so that it can be returned to.
+    async fn follow_foreign_key(&mut self, message_queue: &mut GlobalMessageQueue) -> Result<()> {
+        self.ensure_no_pending_changes()?;
+        let (database, table) = match (&self.database, &self.dtable) {
+            (Some(database), Some(table)) => (database.clone(), table.clone()),
+            _ => return Ok(()),
+        };
+        let (row, column) = match self.table.selected_cell_position() {
+            Some(position) => position,
+            None => return Ok(()),
+        };
+        let value = &self.table.rows[row][column];
+        if value.is_null() {
+            anyhow::bail!("The value is NULL, so it references no row");
+        }
+        let header = &self.table.headers[column];
+        let (foreign_keys, dialect) = match self.app_state.read().await.shared_pool.as_ref() {
+            Some(pool) => (
+                pool.get_foreign_keys(&database, &table).await?,
+                pool.dialect(),
+            ),
+            None => return Ok(()),
+        };
+        let (ref_table, ref_column) = referenced_column(&foreign_keys, header)
+            .ok_or_else(|| anyhow::anyhow!("Column `{}` is not a foreign key", header))?;
+        let target = find_table(&database, table.schema.as_deref(), &ref_table)
+            .unwrap_or_else(|| table_named(&table, &ref_table, table.schema.clone()));
+        let filter = format!(
+            "{} = {}",
+            dialect.quote_identifier(&ref_column),
+            dialect.literal(value)
+        );
+        self.visit(database, target, filter, message_queue);
+        Ok(())
+    }
+
+    /// Opens `table` filtered with `filter`, stacking the current table so that going back
+    /// returns to it.
+    fn visit(
+        &mut self,
+        database: Database,
+        table: DTable,
+        filter: String,
+        message_queue: &mut GlobalMessageQueue,
+    ) {
+        if let Some(current) = self.dtable.clone() {
+            self.history.push(Visit {
+                database: database.clone(),
+                table: current,
+                filter: self.filter.get_text(),
+                order_by: self.order_by.get_text(),
+                selection: self.table.selected_cell_position(),
+            });
+        }
+        self.pending_visit = Some(Visit {
+            database: database.clone(),
+            table: table.clone(),
+            filter,
+            order_by: String::new(),
+            selection: None,
+        });
+        message_queue.push(Box::new(TableSelected(database, table)));
+    }
+
+    /// Lists the foreign keys referencing the current table, with the number of rows referencing
+    /// the selected row through each of them. The rows are counted in the background, and the
+    /// list is shown once they have been.
+    async fn show_references(&mut self) {
+        if self.finding_references.is_some() {
+            return;
+        }
+        let (database, table) = match (&self.database, &self.dtable) {
+            (Some(database), Some(table)) => (database.clone(), table.clone()),
+            _ => return,
+        };
+        let row = match self.table.selected_row.selected() {
+            Some(row) if row < self.table.rows.len() => row,
+            _ => return,
+        };
+        let title = format!("Rows referencing row {} of {}", row + 1, table.name);
+        let values = self
+            .table
+            .headers
+            .iter()
+            .cloned()
+            .zip(self.table.rows[row].iter().cloned())
+            .collect::<Vec<(String, Value)>>();
+        if let Some(pool) = self.app_state.read().await.shared_pool.clone() {
+            let task = QueryTask::spawn(pool, self.app_state.message_sender(), move |pool, _| {
+                referencing_rows(pool, database, table, values)
+            });
+            self.finding_references = Some(ReferencesLoad { task, title });
+        }
+    }
+
+    /// Opens the rows referencing the selected row through the picked foreign key.
+    fn open_references(
+        &mut self,
+        rows: ReferencingRows,
+        message_queue: &mut GlobalMessageQueue,
+    ) -> Result<()> {
+        self.ensure_no_pending_changes()?;
+        let (database, table) = match (&self.database, &self.dtable) {
+            (Some(database), Some(table)) => (database.clone(), table.clone()),
+            _ => return Ok(()),
+        };
+        let target = find_table(
+            &database,
+            rows.reference.schema.as_deref(),
+            &rows.reference.table,
+        )
+        .unwrap_or_else(|| referencing_table(&table, &rows.reference));
+        self.visit(
+            database,
+            target,
+            rows.filter.unwrap_or_default(),
+            message_queue,
+        );
+        Ok(())
+    }
+
+    /// Returns to the table visited before following the last foreign key.
+    fn go_back(&mut self, message_queue: &mut GlobalMessageQueue) -> Result<bool> {
+        if self.history.is_empty() {
+            return Ok(false);
+        }
+        self.ensure_no_pending_changes()?;
+        let visit = match self.history.pop() {
+            Some(visit) => visit,
+            None => return Ok(false),
+        };
+        message_queue.push(Box::new(TableSelected(
+            visit.database.clone(),
+            visit.table.clone(),
+        )));
+        self.pending_visit = Some(visit);
+        Ok(true)
+    }
+
+    /// Stages an UPDATE of the edited cell. The new value is shown in the table right away.
+    async fn stage_edit(&mut self) {
+        let edit = match self.edit.take() {
+            Some(edit) => edit,
+            None => return,
+        };
+        let (database, table) = match (&self.database, &self.dtable) {
+            (Some(database), Some(table)) => (database, table),
+            _ => return,
+        };
+        let value = if edit.null {
+            Value::Null
+        } else {
+            Value::Text(self.editor.get_text())
+        };
+        if let Some(pool) = self.app_state.read().await.shared_pool.as_ref() {
+            let statement = pool.dialect().update(
+                database,
+                table,
+                &self.table.headers[edit.column],
+                value.to_sql_text().as_deref(),
+                &edit.keys,
+            );
+            self.table.set_cell(edit.row, edit.column, value);
+            self.table.mark_changed_cell(edit.row, edit.column);
+            self.pending_changes.push(statement, 1);
+        }
+    }
+
+    /// Shows the pending statements for confirmation before they are committed.
+    fn confirm_commit(&mut self) -> Result<()> {
+        if self.pending_changes.is_empty() {
+            return Ok(());
+        }
+        self.confirm
+            .set(self.pending_changes.statements().join(";\n"))
+    }
+
+    /// Commits all pending statements in the background. The changes are cleared and the records
+    /// reloaded once the commit has finished.
+    async fn commit_changes(&mut self) {
+        if self.committing.is_some() {
+            return;
+        }
+        let changes = self.pending_changes.changes().to_vec();
+        if let Some(pool) = self.app_state.read().await.shared_pool.clone() {
+            self.committing = Some(QueryTask::spawn(
+                pool,
+                self.app_state.message_sender(),
+                move |pool, handle| commit(pool, handle, changes),
+            ));
+        }
+    }
+
+    /// Cancels the running commit if `key` is the cancel key. The commit may have been applied
+    /// before the server was reached, so the changes are dropped rather than kept pending, and
+    /// the records are reloaded once the commit has ended to show what the table holds.
+    fn cancel_commit(&mut self, key: Key) -> bool {
+        if key != self.key_config.cancel_query {
+            return false;
+        }
+        match self.committing.take() {
+            Some(task) => {
+                self.cancelled_commit = Some(task.id());
+                self.pending_changes.clear();
+                task.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Drops all pending statements and reloads the records to undo the staged values.
+    async fn discard_changes(&mut self) -> Result<()> {
+        if self.pending_changes.is_empty() {
+            return Ok(());
+        }
+        self.pending_changes.clear();
+        self.reload_results_table().await
+    }
+
+    fn scrolled_past_last_row(&self, key: Key) -> bool {
+        self.table.moves_to_next_rows(key) && !self.table.eod && self.table.is_last_row_selected()
+    }
+
     fn reset(&mut self) {
+        if let Some(load) = self.loading.take() {
+            load.task.cancel();
+        }
+        self.cancelled_commit = None;
+        self.edit = None;
+        self.focus = Focus::Table;
+        self.row_form.hide();
+        self.confirm.hide();
         self.table.reset();
         self.filter.reset();
+        self.order_by.reset();
+        self.sorted_by.clear();
     }
 }
 
+/// Executes `changes` in a single transaction. Nothing is applied if any of them fails or does
+/// not affect the expected number of rows, e.g. because a row has been changed in the meantime,
+/// and the changes stay pending so that they can be fixed or discarded.
+async fn commit(
+    pool: Arc<dyn Pool>,
+    handle: QueryHandle,
+    changes: Vec<PendingChange>,
+) -> Result<()> {
+    let mut transaction = pool.begin(&handle).await?;
+    for change in changes.iter() {
+        let result = transaction
+            .execute(&change.statement)
+            .await
+            .and_then(|rows| {
+                if rows == change.rows {
+                    Ok(())
+                } else {
+                    Err(anyhow::anyhow!(
+                        "`{}` affected {} rows instead of {}, so no change was committed",
+                        change.statement,
+                        rows,
+                        change.rows
+                    ))
+                }
+            });
+        if let Err(err) = result {
+            transaction.rollback().await?;
+            return Err(err);
+        }
+    }
+    transaction.commit().await
+}
+
+/// Counts the rows referencing, through each foreign key referencing `table`, the row holding
+/// `values` in the loaded columns.
+async fn referencing_rows(
+    pool: Arc<dyn Pool>,
+    database: Database,
+    table: DTable,
+    values: Vec<(String, Value)>,
+) -> Result<Vec<ReferencingRows>> {
+    let dialect = pool.dialect();
+    let mut references = vec![];
+    for reference in pool.get_references(&database, &table).await? {
+        let filter = reference_filter(dialect, &reference, &values);
+        let count = match &filter {
+            Some(filter) => Some(
+                pool.count_records(&database, &referencing_table(&table, &reference), filter)
+                    .await?,
+            ),
+            None => None,
+        };
+        references.push(ReferencingRows {
+            reference,
+            filter,
+            count,
+        });
+    }
+    Ok(references)
+}
+
+/// Returns the condition selecting the rows referencing, through `reference`, the row holding
+/// `values`. Returns None if a referenced column is NULL or not loaded, as no row can then be
+/// told to reference it.
+fn reference_filter(
+    dialect: Dialect,
+    reference: &Reference,
+    values: &[(String, Value)],
+) -> Option<String> {
+    reference
+        .columns
+        .iter()
+        .map(|(column, ref_column)| {
+            let value = values
+                .iter()
+                .find(|(header, _)| header == ref_column)
+                .map(|(_, value)| value)
+                .filter(|value| !value.is_null())?;
+            Some(format!(
+                "{} = {}",
+                dialect.quote_identifier(column),
+                dialect.literal(value)
+            ))
+        })
+        .collect::<Option<Vec<String>>>()
+        .map(|conditions| conditions.join(" AND "))
+}
+
+/// Returns the table and column referenced by `column`, as listed by `Pool::get_foreign_keys`.
+fn referenced_column(foreign_keys: &[Box<dyn TableRow>], column: &str) -> Option<(String, String)> {
+    foreign_keys.iter().find_map(|foreign_key| {
+        let fields = foreign_key
+            .fields()
+            .into_iter()
+            .zip(foreign_key.columns())
+            .collect::<Vec<(String, String)>>();
+        let field = |name: &str| {
+            fields
+                .iter()
+                .find(|(field, _)| field == name)
+                .map(|(_, value)| value.clone())
+        };
+        if field("column_name")? != column {
+            return None;
+        }
+        Some((field("ref_table")?, field("ref_column")?))
+    })
+}
+
+/// Returns the table of `reference`, for when it cannot be found in the database tree.
+fn referencing_table(table: &DTable, reference: &Reference) -> DTable {
+    table_named(
+        table,
+        &reference.table,
+        reference.schema.clone().or_else(|| table.schema.clone()),
+    )
+}
+
+/// Returns the table called `name` in the database of `table`, for when it cannot be found in the
+/// database tree. Only the name and schema of a table are used to query it.
+fn table_named(table: &DTable, name: &str, schema: Option<String>) -> DTable {
+    DTable {
+        name: name.to_string(),
+        create_time: None,
+        update_time: None,
+        engine: None,
+        schema,
+        database: table.database.clone(),
+    }
+}
+
+/// Looks up the table called `name` in `database`, in `schema` if the database has schemas.
+fn find_table(database: &Database, schema: Option<&str>, name: &str) -> Option<DTable> {
+    database.children.iter().find_map(|child| match child {
+        Child::Table(table) if table.name == name => Some(table.clone()),
+        Child::Schema(s) if schema.is_none() || schema == Some(s.name.as_str()) => {
+            s.tables.iter().find(|table| table.name == name).cloned()
+        }
+        _ => None,
+    })
+}
+
 #[async_trait]
 impl Component for RecordTableComponent {
     fn commands(&self, out: &mut Vec<CommandInfo>) {
+        out.push(CommandInfo::new(command::edit_cell(&self.key_config)));
+        out.push(CommandInfo::new(command::set_null(&self.key_config)));
+        out.push(CommandInfo::new(command::insert_delete_row(
+            &self.key_config,
+        )));
+        out.push(CommandInfo::new(command::commit_discard_changes(
+            &self.key_config,
+        )));
+        out.push(CommandInfo::new(command::toggle_pending_changes(
+            &self.key_config,
+        )));
+        out.push(CommandInfo::new(command::sort_in_database(
+            &self.key_config,
+        )));
+        out.push(CommandInfo::new(command::order_by(&self.key_config)));
+        out.push(CommandInfo::new(command::follow_foreign_key(
+            &self.key_config,
+        )));
+        out.push(CommandInfo::new(command::show_references(&self.key_config)));
+        out.push(CommandInfo::new(command::freeze_primary_key(
+            &self.key_config,
+        )));
         self.table.commands(out)
     }
 
@@ -132,21 +909,103 @@ impl Component for RecordTableComponent {
         key: Key,
         message_queue: &mut GlobalMessageQueue,
     ) -> Result<EventState> {
+        if self.confirm.event(key, message_queue).await?.is_consumed() {
+            if self.confirm.take_confirmed().is_some() {
+                self.commit_changes().await;
+            }
+            return Ok(Consumed);
+        }
+        if self
+            .references
+            .event(key, message_queue)
+            .await?
+            .is_consumed()
+        {
+            if let Some(rows) = self.references.take_picked() {
+                self.open_references(rows, message_queue)?;
+            }
+            return Ok(Consumed);
+        }
+        if self.row_form.event(key, message_queue).await?.is_consumed() {
+            if let Some(values) = self.row_form.take_submitted() {
+                self.stage_insert(values).await;
+            }
+            return Ok(Consumed);
+        }
         return match self.focus {
             Focus::Table => {
-                if key == self.key_config.filter {
+                if self.committing.is_some() {
+                    // Staged changes cannot be altered until the commit has finished.
+                    if self.cancel_commit(key) {
+                        Ok(EventState::Consumed)
+                    } else {
+                        self.table.event(key, message_queue).await
+                    }
+                } else if self.table.is_searching() {
+                    self.table.event(key, message_queue).await
+                } else if key == self.key_config.filter {
                     self.focus = Focus::Filter;
                     Ok(EventState::Consumed)
+                } else if key == self.key_config.order_by {
+                    self.focus = Focus::OrderBy;
+                    Ok(EventState::Consumed)
+                } else if key == self.key_config.edit_cell {
+                    self.start_edit().await?;
+                    Ok(EventState::Consumed)
+                } else if key == self.key_config.insert_row {
+                    self.start_insert().await?;
+                    Ok(EventState::Consumed)
+                } else if key == self.key_config.delete_row {
+                    self.stage_delete().await?;
+                    Ok(EventState::Consumed)
+                } else if key == self.key_config.commit_changes {
+                    self.confirm_commit()?;
+                    Ok(EventState::Consumed)
+                } else if key == self.key_config.discard_changes {
+                    self.discard_changes().await?;
+                    Ok(EventState::Consumed)
+                } else if key == self.key_config.sort_in_database {
+                    self.sort_in_database().await?;
+                    Ok(EventState::Consumed)
+                } else if key == self.key_config.follow_foreign_key {
+                    self.follow_foreign_key(message_queue).await?;
+                    Ok(EventState::Consumed)
+                } else if key == self.key_config.show_references {
+                    self.show_references().await;
+                    Ok(EventState::Consumed)
+                } else if key == self.key_config.go_back && self.go_back(message_queue)? {
+                    Ok(EventState::Consumed)
+                } else if key == self.key_config.freeze_primary_key {
+                    self.freeze_primary_key().await?;
+                    Ok(EventState::Consumed)
+                } else if self.cancel_load(key)
+                    || self
+                        .pending_changes
+                        .event(key, message_queue)
+                        .await?
+                        .is_consumed()
+                {
+                    Ok(EventState::Consumed)
                 } else {
-                    self.table.event(key, message_queue).await
+                    let state = self.table.event(key, message_queue).await?;
+                    if self.scrolled_past_last_row(key) {
+                        self.load_next_page().await;
+                    }
+                    Ok(state)
                 }
             }
-            Focus::Filter => {
-                if self.filter.event(key, message_queue).await?.is_consumed() {
+            Focus::Filter | Focus::OrderBy => {
+                let input = if matches!(self.focus, Focus::Filter) {
+                    &mut self.filter
+                } else {
+                    &mut self.order_by
+                };
+                if input.event(key, message_queue).await?.is_consumed() {
                     Ok(Consumed)
                 } else {
                     if key == Key::Enter {
-                        // run filter
+                        // run filter and order
+                        self.ensure_no_pending_changes()?;
                         self.reload_results_table().await?;
                         self.focus = Focus::Table;
                         Ok(Consumed)
@@ -158,6 +1017,29 @@ impl Component for RecordTableComponent {
                     }
                 }
             }
+            Focus::Editor => {
+                if key == self.key_config.set_null {
+                    let null = self.edit.as_ref().map_or(false, |edit| edit.null);
+                    self.set_edit_null(!null);
+                    Ok(Consumed)
+                } else if self.editor.event(key, message_queue).await?.is_consumed() {
+                    // Typing a value replaces NULL.
+                    if !self.editor.get_text().is_empty() {
+                        self.set_edit_null(false);
+                    }
+                    Ok(Consumed)
+                } else if key == self.key_config.enter {
+                    self.focus = Focus::Table;
+                    self.stage_edit().await;
+                    Ok(Consumed)
+                } else if key == self.key_config.exit_popup {
+                    self.focus = Focus::Table;
+                    self.edit = None;
+                    Ok(Consumed)
+                } else {
+                    Ok(NotConsumed)
+                }
+            }
         };
     }
 
@@ -165,7 +1047,17 @@ impl Component for RecordTableComponent {
         for m in messages.iter() {
             handle_message!(m, DatabaseEvent,
                 TableSelected(database,table) => {
+                    self.ensure_no_pending_changes()?;
                     self.reset();
+                    // Tables picked from the list start a new history.
+                    match self.pending_visit.take() {
+                        Some(visit) if visit.table == *table => {
+                            self.filter.set_str(&visit.filter);
+                            self.order_by.set_str(&visit.order_by);
+                            self.pending_selection = visit.selection;
+                        }
+                        _ => self.history.clear(),
+                    }
                     self.update_table(database.clone(), table.clone()).await?;
                     let app_rhandle = self.app_state.read().await;
                     if let Some(src) = (*app_rhandle).pool_completion_src().await {
@@ -173,7 +1065,237 @@ impl Component for RecordTableComponent {
                     }
                 }
             );
+            handle_message!(m, QueryTaskFinished<RecordPage>,
+                finished => {
+                    if matches!(&self.loading, Some(load) if load.task.id() == finished.task_id) {
+                        let next_page = self.loading.take().map_or(false, |load| load.next_page);
+                        if let Some(result) = finished.take_result() {
+                            self.apply_page(next_page, result?);
+                        }
+                    }
+                }
+            );
+            handle_message!(m, QueryTaskFinished<Vec<ReferencingRows>>,
+                finished => {
+                    if matches!(&self.finding_references, Some(load) if load.task.id() == finished.task_id) {
+                        let title = self.finding_references.take().map(|load| load.title).unwrap_or_default();
+                        if let Some(result) = finished.take_result() {
+                            self.references.set(title, result?)?;
+                        }
+                    }
+                }
+            );
+            handle_message!(m, QueryTaskFinished<()>,
+                finished => {
+                    if matches!(&self.committing, Some(task) if task.id() == finished.task_id) {
+                        self.committing = None;
+                        if let Some(result) = finished.take_result() {
+                            result?;
+                            self.pending_changes.clear();
+                            self.reload_results_table().await?;
+                        }
+                    }
+                }
+            );
+            handle_message!(m, QueryTaskCancelled,
+                cancelled => {
+                    if self.cancelled_commit == Some(cancelled.task_id) {
+                        self.cancelled_commit = None;
+                        self.reload_results_table().await?;
+                    }
+                }
+            );
         }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use database_tree::{Database, Schema};
+
+    use std::sync::Arc;
+
+    use super::{commit, find_table, reference_filter, referenced_column, RecordTableComponent};
+    use crate::app::{AppMessage, AppStateRef};
+    use crate::components::databases::DatabaseEvent;
+    use crate::components::pending_changes::PendingChange;
+    use crate::components::Component;
+    use crate::config::KeyConfig;
+    use crate::database::{
+        test_table, Dialect, ExecuteResult, Pool, QueryHandle, Reference, SqlitePool, TableRow,
+        Value,
+    };
+
+    struct ForeignKey(&'static str, &'static str, &'static str);
+
+    impl TableRow for ForeignKey {
+        fn fields(&self) -> Vec<String> {
+            vec!["name", "column_name", "ref_table", "ref_column"]
+                .into_iter()
+                .map(String::from)
+                .collect()
+        }
+
+        fn columns(&self) -> Vec<String> {
+            vec!["fk", self.0, self.1, self.2]
+                .into_iter()
+                .map(String::from)
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_referenced_column() {
+        let foreign_keys: Vec<Box<dyn TableRow>> = vec![
+            Box::new(ForeignKey("user_id", "users", "id")),
+            Box::new(ForeignKey("item_id", "items", "code")),
+        ];
+        assert_eq!(
+            referenced_column(&foreign_keys, "item_id"),
+            Some(("items".to_string(), "code".to_string()))
+        );
+        assert_eq!(referenced_column(&foreign_keys, "id"), None);
+    }
+
+    #[test]
+    fn test_find_table() {
+        let database = Database::new(
+            "shop".to_string(),
+            vec![
+                Schema {
+                    name: "public".to_string(),
+                    tables: vec![test_table("users").1],
+                }
+                .into(),
+                Schema {
+                    name: "audit".to_string(),
+                    tables: vec![test_table("events").1],
+                }
+                .into(),
+            ],
+        );
+        assert_eq!(
+            find_table(&database, Some("public"), "users"),
+            Some(test_table("users").1)
+        );
+        assert_eq!(find_table(&database, Some("public"), "events"), None);
+        assert_eq!(
+            find_table(
+                &Database::new("shop".to_string(), vec![test_table("users").1.into()]),
+                None,
+                "users"
+            ),
+            Some(test_table("users").1)
+        );
+    }
+
+    #[test]
+    fn test_reference_filter() {
+        let reference = Reference {
+            name: "fk".to_string(),
+            schema: None,
+            table: "stocks".to_string(),
+            columns: vec![
+                ("shop_id".to_string(), "id".to_string()),
+                ("shop_region".to_string(), "region".to_string()),
+            ],
+        };
+        let mut values = vec![
+            ("id".to_string(), Value::Int(1)),
+            ("region".to_string(), Value::Text("eu".to_string())),
+        ];
+        assert_eq!(
+            reference_filter(Dialect::Postgres, &reference, &values),
+            Some("\"shop_id\" = 1 AND \"shop_region\" = 'eu'".to_string())
+        );
+        values[1].1 = Value::Null;
+        assert_eq!(
+            reference_filter(Dialect::Postgres, &reference, &values),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pending_changes_on_reload() {
+        let mut component =
+            RecordTableComponent::new(KeyConfig::default(), AppStateRef::detached());
+        let database = Database::new("shop".to_string(), vec![test_table("users").1.into()]);
+        let select = |table| -> Vec<Box<dyn AppMessage>> {
+            vec![Box::new(DatabaseEvent::TableSelected(
+                database.clone(),
+                table,
+            ))]
+        };
+        component
+            .handle_messages(&select(test_table("users").1))
+            .await
+            .unwrap();
+        component
+            .pending_changes
+            .push("DELETE FROM users WHERE id = 1".to_string(), 1);
+
+        assert!(component.sort_in_database().await.is_err());
+        assert!(!component.pending_changes.is_empty());
+
+        assert!(component
+            .handle_messages(&select(test_table("items").1))
+            .await
+            .is_err());
+        assert!(!component.pending_changes.is_empty());
+        assert_eq!(component.dtable, Some(test_table("users").1));
+
+        component.discard_changes().await.unwrap();
+        component
+            .handle_messages(&select(test_table("items").1))
+            .await
+            .unwrap();
+        assert_eq!(component.dtable, Some(test_table("items").1));
+        assert!(component.sort_in_database().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_commit_checks_affected_rows() {
+        let pool: Arc<dyn Pool> = Arc::new(SqlitePool::new("sqlite::memory:").await.unwrap());
+        pool.execute(
+            &"CREATE TABLE t (id INTEGER PRIMARY KEY, note TEXT); INSERT INTO t VALUES (1, 'a')"
+                .to_string(),
+        )
+        .await
+        .unwrap();
+        let change = |statement: &str| PendingChange {
+            statement: statement.to_string(),
+            rows: 1,
+        };
+        let err = commit(
+            pool.clone(),
+            QueryHandle::default(),
+            vec![
+                change("UPDATE t SET note = 'b' WHERE id = 1"),
+                change("DELETE FROM t WHERE id = 2"),
+            ],
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`DELETE FROM t WHERE id = 2` affected 0 rows instead of 1, so no change was committed"
+        );
+        match pool
+            .execute(&"SELECT note FROM t".to_string())
+            .await
+            .unwrap()
+            .remove(0)
+        {
+            ExecuteResult::Read { rows, .. } => assert_eq!(rows, vec![vec![Value::from("a")]]),
+            ExecuteResult::Write { .. } => panic!("expected rows"),
+        }
+        commit(
+            pool,
+            QueryHandle::default(),
+            vec![change("UPDATE t SET note = 'b' WHERE id = 1")],
+        )
+        .await
+        .unwrap();
+    }
+}
@@ -0,0 +1,229 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Margin, Rect},
+    style::{Color, Style},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use crate::app::GlobalMessageQueue;
+use crate::components::command::CommandInfo;
+use crate::config::KeyConfig;
+use crate::database::Reference;
+use crate::event::Key;
+
+use super::{Component, DrawableComponent, EventState};
+
+/// A foreign key referencing the selected row, with the filter selecting the referencing rows
+/// and their number, if the referenced value is known.
+pub struct ReferencingRows {
+    pub reference: Reference,
+    pub filter: Option<String>,
+    pub count: Option<i64>,
+}
+
+impl ReferencingRows {
+    fn describe(&self) -> String {
+        let table = match &self.reference.schema {
+            Some(schema) => format!("{}.{}", schema, self.reference.table),
+            None => self.reference.table.clone(),
+        };
+        let (columns, ref_columns): (Vec<&str>, Vec<&str>) = self
+            .reference
+            .columns
+            .iter()
+            .map(|(column, ref_column)| (column.as_str(), ref_column.as_str()))
+            .unzip();
+        format!(
+            "{}.{} -> {}: {}",
+            table,
+            column_list(&columns),
+            column_list(&ref_columns),
+            match self.count {
+                Some(1) => "1 row".to_string(),
+                Some(count) => format!("{} rows", count),
+                None => "-".to_string(),
+            }
+        )
+    }
+}
+
+/// Returns the name of the only column of a key, or the names of its columns in parentheses.
+fn column_list(columns: &[&str]) -> String {
+    match columns {
+        [column] => column.to_string(),
+        columns => format!("({})", columns.join(", ")),
+    }
+}
+
+/// Popup listing the tables whose foreign keys reference the selected row. The owner opens the
+/// referencing rows once `event` reports that one of the tables has been picked.
+pub struct ReferencesComponent {
+    title: String,
+    references: Vec<ReferencingRows>,
+    selected: usize,
+    picked: Option<usize>,
+    visible: bool,
+    key_config: KeyConfig,
+}
+
+impl ReferencesComponent {
+    pub fn new(key_config: KeyConfig) -> Self {
+        Self {
+            title: String::new(),
+            references: vec![],
+            selected: 0,
+            picked: None,
+            visible: false,
+            key_config,
+        }
+    }
+
+    pub fn set(&mut self, title: String, references: Vec<ReferencingRows>) -> Result<()> {
+        self.title = title;
+        self.references = references;
+        self.selected = 0;
+        self.picked = None;
+        self.show()
+    }
+
+    /// Returns the references picked to be opened, resetting the choice.
+    pub fn take_picked(&mut self) -> Option<ReferencingRows> {
+        let index = self.picked.take()?;
+        if index < self.references.len() {
+            Some(self.references.remove(index))
+        } else {
+            None
+        }
+    }
+}
+
+impl DrawableComponent for ReferencesComponent {
+    fn draw<B: Backend>(&self, f: &mut Frame<B>, _area: Rect, _focused: bool) -> Result<()> {
+        if self.visible {
+            let width = 80;
+            let height = (self.references.len() as u16).clamp(1, 15) + 3;
+            let area = Rect::new(
+                (f.size().width.saturating_sub(width)) / 2,
+                (f.size().height.saturating_sub(height)) / 2,
+                width.min(f.size().width),
+                height.min(f.size().height),
+            );
+            f.render_widget(Clear, area);
+            f.render_widget(
+                Block::default()
+                    .title(self.title.as_str())
+                    .borders(Borders::ALL),
+                area,
+            );
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(1), Constraint::Length(1)])
+                .split(area.inner(&Margin {
+                    vertical: 1,
+                    horizontal: 1,
+                }));
+            if self.references.is_empty() {
+                f.render_widget(
+                    Paragraph::new("No foreign key references this table"),
+                    chunks[0],
+                );
+            } else {
+                let references = List::new(
+                    self.references
+                        .iter()
+                        .map(|reference| ListItem::new(reference.describe()))
+                        .collect::<Vec<ListItem>>(),
+                )
+                .highlight_style(Style::default().bg(Color::Rgb(0xea, 0x59, 0x0b)));
+                let mut state = ListState::default();
+                state.select(Some(self.selected));
+                f.render_stateful_widget(references, chunks[0], &mut state);
+            }
+            f.render_widget(
+                Paragraph::new(format!(
+                    "{}: open the referencing rows, {}: close",
+                    self.key_config.enter, self.key_config.exit_popup
+                ))
+                .style(Style::default().fg(Color::DarkGray)),
+                chunks[1],
+            );
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Component for ReferencesComponent {
+    fn commands(&self, _out: &mut Vec<CommandInfo>) {}
+
+    async fn event(
+        &mut self,
+        key: Key,
+        _message_queue: &mut GlobalMessageQueue,
+    ) -> Result<EventState> {
+        if !self.visible {
+            return Ok(EventState::NotConsumed);
+        }
+        if key == self.key_config.exit_popup || key == self.key_config.show_references {
+            self.hide();
+        } else if key == self.key_config.enter {
+            if !self.references.is_empty() {
+                self.picked = Some(self.selected);
+            }
+            self.hide();
+        } else if key == self.key_config.scroll_down {
+            self.selected = (self.selected + 1).min(self.references.len().saturating_sub(1));
+        } else if key == self.key_config.scroll_up {
+            self.selected = self.selected.saturating_sub(1);
+        }
+        Ok(EventState::Consumed)
+    }
+
+    fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    fn hide(&mut self) {
+        self.visible = false;
+    }
+
+    fn show(&mut self) -> Result<()> {
+        self.visible = true;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ReferencingRows;
+    use crate::database::Reference;
+
+    #[test]
+    fn test_describe() {
+        let mut rows = ReferencingRows {
+            reference: Reference {
+                name: "orders_user_id_fkey".to_string(),
+                schema: Some("public".to_string()),
+                table: "orders".to_string(),
+                columns: vec![("user_id".to_string(), "id".to_string())],
+            },
+            filter: Some("\"user_id\" = 1".to_string()),
+            count: Some(3),
+        };
+        assert_eq!(rows.describe(), "public.orders.user_id -> id: 3 rows");
+        rows.count = None;
+        assert_eq!(rows.describe(), "public.orders.user_id -> id: -");
+        rows.reference.columns = vec![
+            ("shop_id".to_string(), "id".to_string()),
+            ("shop_region".to_string(), "region".to_string()),
+        ];
+        assert_eq!(
+            rows.describe(),
+            "public.orders.(shop_id, shop_region) -> (id, region): -"
+        );
+    }
+}
@@ -0,0 +1,318 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Margin, Rect},
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use crate::app::GlobalMessageQueue;
+use crate::components::command::CommandInfo;
+use crate::config::KeyConfig;
+use crate::database::Column;
+use crate::event::Key;
+use crate::ui::textbox::TextBox;
+
+use super::{Component, DrawableComponent, EventState};
+
+struct FormField {
+    column: Column,
+    value: String,
+    /// Whether the column is set to NULL rather than to `value`.
+    null: bool,
+}
+
+impl FormField {
+    fn name(&self) -> String {
+        self.column.name.clone().unwrap_or_default()
+    }
+
+    fn description(&self) -> String {
+        let mut description = vec![];
+        if let Some(r#type) = &self.column.r#type {
+            description.push(r#type.clone());
+        }
+        if let Some(null) = self.column.null.as_ref().filter(|null| !null.is_empty()) {
+            description.push(format!("null: {}", null));
+        }
+        if let Some(default) = &self.column.default {
+            description.push(format!("default: {}", default));
+        }
+        description.join(", ")
+    }
+}
+
+/// Popup form with one input per column, used to enter the values of a new row. Columns left
+/// empty are omitted from the INSERT so that the server applies their default.
+pub struct RowFormComponent {
+    fields: Vec<FormField>,
+    selected: usize,
+    input: TextBox,
+    submitted: bool,
+    visible: bool,
+    key_config: KeyConfig,
+}
+
+impl RowFormComponent {
+    pub fn new(key_config: KeyConfig) -> Self {
+        Self {
+            fields: vec![],
+            selected: 0,
+            input: TextBox::default().with_placeholder("Leave empty for the default value"),
+            submitted: false,
+            visible: false,
+            key_config,
+        }
+    }
+
+    pub fn set(&mut self, columns: Vec<Column>) -> Result<()> {
+        self.fields = columns
+            .into_iter()
+            .map(|column| FormField {
+                column,
+                value: String::new(),
+                null: false,
+            })
+            .collect();
+        self.submitted = false;
+        self.selected = 0;
+        self.input.reset();
+        self.select(0);
+        self.show()
+    }
+
+    /// Returns the entered `(column, value)` pairs if the form was submitted. Empty fields are
+    /// skipped, and fields set to NULL have no value.
+    pub fn take_submitted(&mut self) -> Option<Vec<(String, Option<String>)>> {
+        if !self.submitted {
+            return None;
+        }
+        self.submitted = false;
+        Some(
+            self.fields
+                .iter()
+                .filter(|field| field.null || !field.value.is_empty())
+                .map(|field| {
+                    let value = if field.null {
+                        None
+                    } else {
+                        Some(field.value.clone())
+                    };
+                    (field.name(), value)
+                })
+                .collect(),
+        )
+    }
+
+    fn select(&mut self, index: usize) {
+        if let Some(field) = self.fields.get_mut(self.selected) {
+            field.value = self.input.get_text();
+        }
+        self.selected = index.min(self.fields.len().saturating_sub(1));
+        match self.fields.get(self.selected) {
+            Some(field) => {
+                self.input.set_label(field.name());
+                self.input.set_str(&field.value);
+            }
+            None => self.input.reset(),
+        }
+        self.update_placeholder();
+    }
+
+    /// Sets the selected column to NULL, clearing the entered text, or back to the entered text.
+    fn set_null(&mut self, null: bool) {
+        if let Some(field) = self.fields.get_mut(self.selected) {
+            field.null = null;
+            if null {
+                field.value.clear();
+                self.input.reset();
+            }
+        }
+        self.update_placeholder();
+    }
+
+    fn update_placeholder(&mut self) {
+        if matches!(self.fields.get(self.selected), Some(field) if field.null) {
+            self.input.set_placeholder("NULL");
+        } else {
+            self.input
+                .set_placeholder("Leave empty for the default value");
+        }
+    }
+}
+
+impl DrawableComponent for RowFormComponent {
+    fn draw<B: Backend>(&self, f: &mut Frame<B>, _area: Rect, _focused: bool) -> Result<()> {
+        if self.visible {
+            let width = 80;
+            let height = self.fields.len() as u16 + 7;
+            let area = Rect::new(
+                (f.size().width.saturating_sub(width)) / 2,
+                (f.size().height.saturating_sub(height)) / 2,
+                width.min(f.size().width),
+                height.min(f.size().height),
+            );
+            f.render_widget(Clear, area);
+            f.render_widget(
+                Block::default().title("Insert row").borders(Borders::ALL),
+                area,
+            );
+
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Min(1),
+                    Constraint::Length(3),
+                    Constraint::Length(1),
+                ])
+                .split(area.inner(&Margin {
+                    vertical: 1,
+                    horizontal: 1,
+                }));
+
+            let lines = self
+                .fields
+                .iter()
+                .enumerate()
+                .map(|(index, field)| {
+                    let style = if index == self.selected {
+                        Style::default().bg(Color::Rgb(0xea, 0x59, 0x0b))
+                    } else {
+                        Style::default()
+                    };
+                    let value = if field.null {
+                        "NULL".to_string()
+                    } else if index == self.selected {
+                        self.input.get_text()
+                    } else {
+                        field.value.clone()
+                    };
+                    Spans::from(vec![
+                        Span::styled(field.name(), style.add_modifier(Modifier::BOLD)),
+                        Span::styled(
+                            format!(" ({})", field.description()),
+                            Style::default().fg(Color::DarkGray),
+                        ),
+                        Span::raw(format!(" = {}", value)),
+                    ])
+                })
+                .collect::<Vec<Spans>>();
+            let offset = self
+                .selected
+                .saturating_sub(chunks[0].height.saturating_sub(1) as usize);
+            f.render_widget(Paragraph::new(lines).scroll((offset as u16, 0)), chunks[0]);
+
+            self.input.draw(f, chunks[1], true)?;
+
+            f.render_widget(
+                Paragraph::new(format!(
+                    "{}/{}: select column, {}: insert, {}: cancel, {}: NULL",
+                    self.key_config.move_up,
+                    self.key_config.move_down,
+                    self.key_config.enter,
+                    self.key_config.exit_popup,
+                    self.key_config.set_null
+                ))
+                .style(Style::default().fg(Color::DarkGray)),
+                chunks[2],
+            );
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Component for RowFormComponent {
+    fn commands(&self, _out: &mut Vec<CommandInfo>) {}
+
+    async fn event(
+        &mut self,
+        key: Key,
+        message_queue: &mut GlobalMessageQueue,
+    ) -> Result<EventState> {
+        if !self.visible {
+            return Ok(EventState::NotConsumed);
+        }
+        if key == self.key_config.exit_popup {
+            self.hide();
+        } else if key == self.key_config.enter {
+            self.select(self.selected);
+            self.submitted = true;
+            self.hide();
+        } else if key == self.key_config.move_up {
+            self.select(self.selected.saturating_sub(1));
+        } else if key == self.key_config.move_down || key == Key::Tab {
+            self.select(self.selected + 1);
+        } else if key == self.key_config.set_null {
+            let null = matches!(self.fields.get(self.selected), Some(field) if field.null);
+            self.set_null(!null);
+        } else {
+            self.input.event(key, message_queue).await?;
+            // Typing a value replaces NULL.
+            if !self.input.get_text().is_empty() {
+                self.set_null(false);
+            }
+        }
+        Ok(EventState::Consumed)
+    }
+
+    fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    fn hide(&mut self) {
+        self.visible = false;
+    }
+
+    fn show(&mut self) -> Result<()> {
+        self.visible = true;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Column, KeyConfig, RowFormComponent};
+
+    fn column(name: &str) -> Column {
+        Column {
+            name: Some(name.to_string()),
+            r#type: None,
+            null: None,
+            default: None,
+            comment: None,
+        }
+    }
+
+    #[test]
+    fn test_take_submitted_skips_empty_fields() {
+        let mut form = RowFormComponent::new(KeyConfig::default());
+        form.set(vec![
+            column("id"),
+            column("name"),
+            column("email"),
+            column("note"),
+        ])
+        .unwrap();
+        form.input.set_str(&"1".to_string());
+        form.select(1);
+        form.input.set_str(&"NULL".to_string());
+        form.select(3);
+        form.set_null(true);
+        form.select(3);
+        form.submitted = true;
+        assert_eq!(
+            form.take_submitted(),
+            Some(vec![
+                ("id".to_string(), Some("1".to_string())),
+                ("name".to_string(), Some("NULL".to_string())),
+                ("note".to_string(), None)
+            ])
+        );
+        assert_eq!(form.take_submitted(), None);
+    }
+}
@@ -1,26 +1,33 @@
-use anyhow::Result;
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
 use async_trait::async_trait;
-use log::info;
+use log::error;
 use tui::{
     backend::Backend,
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Style},
-    widgets::{Block, Borders, Paragraph, Wrap},
+    style::{Color, Modifier, Style},
+    text::Spans,
+    widgets::{Block, Borders, Paragraph, Tabs, Wrap},
     Frame,
 };
 use unicode_width::UnicodeWidthStr;
 
 use crate::app::{AppMessage, AppStateRef, GlobalMessageQueue};
-use crate::components::command::CommandInfo;
+use crate::components::command::{self, CommandInfo};
+use crate::components::connections::ConnectionEvent;
 use crate::components::databases::DatabaseEvent;
 use crate::components::tab::{Tab, TabType};
+use crate::components::utils::query_task::{QueryTask, QueryTaskFinished};
 use crate::components::EventState::{Consumed, NotConsumed};
 use crate::components::{Drawable, DrawableComponent};
 use crate::config::KeyConfig;
-use crate::database::ExecuteResult;
+use crate::database::{ExecuteResult, Pool, PoolSession, QueryHandle};
 use crate::event::Key;
 use crate::handle_message;
-use crate::sql_utils::find_last_separator;
+use crate::sql_utils::{split_statements, statement_at};
 use crate::ui::stateful_paragraph::{ParagraphState, StatefulParagraph};
 use crate::ui::textarea::TextArea;
 
@@ -29,16 +36,56 @@ use super::{
     TableComponent,
 };
 
-struct QueryResult {
-    updated_rows: u64,
+/// The result sets of a statement executed from the editor.
+struct ExecutedStatement {
+    /// The number of the statement among the executed ones, counted from 1.
+    number: usize,
+    line: usize,
+    elapsed: Duration,
+    results: Vec<ExecuteResult>,
 }
 
-impl QueryResult {
-    fn result_str(&self) -> String {
-        format!("Query OK, {} row affected", self.updated_rows)
+impl ExecutedStatement {
+    /// Describes the result sets, e.g. "Statement 2, line 4: 3 rows affected (0.012s)".
+    fn summary(&self) -> String {
+        let results = self
+            .results
+            .iter()
+            .map(|result| match result {
+                ExecuteResult::Read { rows, .. } => format!("{} rows returned", rows.len()),
+                ExecuteResult::Write { updated_rows } => {
+                    format!("{} rows affected", updated_rows)
+                }
+            })
+            .collect::<Vec<String>>();
+        format!(
+            "Statement {}, line {}: {} ({:.3}s)",
+            self.number,
+            self.line,
+            if results.is_empty() {
+                "no result".to_string()
+            } else {
+                results.join(", ")
+            },
+            self.elapsed.as_secs_f64()
+        )
     }
 }
 
+/// The session the statements of an editor run in. It is kept open from one execution to the
+/// next, so that transactions and session state carry over.
+struct EditorSession {
+    session: Box<dyn PoolSession>,
+    connection_id: Option<u64>,
+}
+
+/// The statements executed before the first one failing, if any, and the session they ran in.
+struct ScriptResult {
+    statements: Vec<ExecutedStatement>,
+    error: Option<anyhow::Error>,
+    session: EditorSession,
+}
+
 pub enum Focus {
     Editor,
     Table,
@@ -46,8 +93,15 @@ pub enum Focus {
 
 pub struct SqlEditorComponent {
     text_area: TextArea,
-    table: TableComponent,
-    query_result: Option<QueryResult>,
+    /// A table for each result set returned by the last execution.
+    result_tables: Vec<TableComponent>,
+    /// A line for each statement of the last execution.
+    summary: Vec<String>,
+    /// The index of the selected result set, or of the summary after the last one.
+    selected_result: usize,
+    running_query: Option<QueryTask>,
+    /// The session of the previous executions. It is handed to the running query, if any.
+    session: Option<EditorSession>,
     key_config: KeyConfig,
     paragraph_state: ParagraphState,
     focus: Focus,
@@ -74,13 +128,17 @@ impl SqlEditorComponent {
         key_config: KeyConfig,
         app_state: AppStateRef,
         editor_name: Option<String>,
+        theme: &str,
     ) -> Self {
         Self {
-            text_area: TextArea::new(key_config.clone(), app_state.clone()).await,
-            table: TableComponent::new(key_config.clone()),
+            text_area: TextArea::new(key_config.clone(), app_state.clone(), theme).await,
+            result_tables: vec![],
+            summary: vec![],
+            selected_result: 0,
             focus: Focus::Editor,
             paragraph_state: ParagraphState::default(),
-            query_result: None,
+            running_query: None,
+            session: None,
             key_config,
             app_state,
             editor_name: editor_name.unwrap_or("Sql Editor".to_string()),
@@ -92,47 +150,227 @@ impl SqlEditorComponent {
         key: Key,
         msg_queue: &mut GlobalMessageQueue,
     ) -> Result<EventState> {
+        if self.cancel_query(key) {
+            return Ok(Consumed);
+        }
         if self.text_area.event(key, msg_queue).await?.is_consumed() {
             return Ok(Consumed);
         }
-        match key {
-            Key::Esc => {
-                self.focus = Focus::Table;
-                return Ok(EventState::Consumed);
-            }
-            Key::F5 => {
-                let query: String = self.text_area.get_text();
-                self.execute_query(query).await?;
-                return Ok(EventState::Consumed);
-            }
-            _ => (),
+        if key == Key::Esc {
+            self.focus = Focus::Table;
+            return Ok(EventState::Consumed);
+        }
+        if key == self.key_config.execute_statement {
+            let dialect = match self.app_state.read().await.dialect() {
+                Some(dialect) => dialect,
+                None => return Ok(EventState::Consumed),
+            };
+            let script = self.text_area.get_text();
+            let statements = match self.text_area.selection() {
+                Some(selection) => split_statements(dialect, &script[selection.clone()])
+                    .into_iter()
+                    .map(|statement| {
+                        statement.start + selection.start..statement.end + selection.start
+                    })
+                    .collect(),
+                None => statement_at(
+                    &split_statements(dialect, &script),
+                    self.text_area.cursor_offset(),
+                )
+                .into_iter()
+                .collect(),
+            };
+            self.execute_statements(&script, statements).await;
+            return Ok(EventState::Consumed);
+        }
+        if key == self.key_config.execute_all {
+            let dialect = match self.app_state.read().await.dialect() {
+                Some(dialect) => dialect,
+                None => return Ok(EventState::Consumed),
+            };
+            let script = self.text_area.get_text();
+            let statements = split_statements(dialect, &script);
+            self.execute_statements(&script, statements).await;
+            return Ok(EventState::Consumed);
         }
         Ok(NotConsumed)
     }
 
-    async fn execute_query(&mut self, query: String) -> Result<()> {
-        if let Some(pool) = self.app_state.read().await.shared_pool.as_ref() {
-            let result = pool.execute(&query).await?;
-            match result {
-                ExecuteResult::Read {
+    /// Spawns the statements at `ranges` of `script` in the background, running them one after
+    /// the other until one fails. Their results are delivered through the global message queue
+    /// and applied in `handle_messages`.
+    async fn execute_statements(&mut self, script: &str, ranges: Vec<Range<usize>>) {
+        if self.running_query.is_some() || ranges.is_empty() {
+            return;
+        }
+        let statements: Vec<(usize, String)> = ranges
+            .into_iter()
+            .map(|range| {
+                (
+                    script[..range.start].matches('\n').count() + 1,
+                    script[range].to_string(),
+                )
+            })
+            .collect();
+        if let Some(pool) = self.app_state.read().await.shared_pool.clone() {
+            let session = self.session.take();
+            self.running_query = Some(QueryTask::spawn(
+                pool,
+                self.app_state.message_sender(),
+                |pool, handle| execute_script(pool, handle, session, statements),
+            ));
+        }
+    }
+
+    /// Closes the session of the previous executions, if any, in the background. Returns true if
+    /// there was one.
+    fn close_session(&mut self) -> bool {
+        let session = match self.session.take() {
+            Some(session) => session,
+            None => return false,
+        };
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            runtime.spawn(async move {
+                if let Err(err) = session.session.close().await {
+                    error!("failed to close the session: {}", err);
+                }
+            });
+        }
+        true
+    }
+
+    /// Cancels the running query if `key` is the cancel key. Returns true if a query was cancelled.
+    fn cancel_query(&mut self, key: Key) -> bool {
+        if key != self.key_config.cancel_query {
+            return false;
+        }
+        self.cancel_running_query("Query cancelled")
+    }
+
+    /// Cancels the running query, if any, showing `reason` in the summary. The session the query
+    /// runs in is closed with it. Returns true if a query was cancelled.
+    fn cancel_running_query(&mut self, reason: &str) -> bool {
+        if let Some(task) = self.running_query.take() {
+            task.cancel();
+            self.result_tables.clear();
+            self.summary = vec![reason.to_string(), SESSION_CLOSED.to_string()];
+            self.selected_result = 0;
+            return true;
+        }
+        false
+    }
+
+    /// Shows a tab for each result set and the summary of the statements, returning the error
+    /// of the statement that failed, if any.
+    fn apply_result(&mut self, result: ScriptResult) -> Result<()> {
+        self.session = Some(result.session);
+        self.result_tables.clear();
+        self.summary = result
+            .statements
+            .iter()
+            .map(ExecutedStatement::summary)
+            .collect();
+        if let Some(error) = &result.error {
+            self.summary.push(
+                error
+                    .to_string()
+                    .lines()
+                    .next()
+                    .unwrap_or_default()
+                    .to_string(),
+            );
+        }
+        self.selected_result = 0;
+        for statement in result.statements {
+            for result in statement.results {
+                if let ExecuteResult::Read {
                     headers,
                     rows,
                     database,
                     table,
-                } => {
-                    self.table.update(rows, headers, database, table);
-                    self.focus = Focus::Table;
-                    self.query_result = None;
-                }
-                ExecuteResult::Write { updated_rows } => {
-                    self.query_result = Some(QueryResult { updated_rows })
+                } = result
+                {
+                    let mut result_table = TableComponent::new(self.key_config.clone());
+                    result_table.update(rows, headers, database, table);
+                    self.result_tables.push(result_table);
                 }
             }
         }
-        Ok(())
+        if !self.result_tables.is_empty() {
+            self.focus = Focus::Table;
+        }
+        match result.error {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    fn result_titles(&self) -> Vec<Spans<'_>> {
+        (1..=self.result_tables.len())
+            .map(|number| format!("Result {}", number))
+            .chain(std::iter::once("Summary".to_string()))
+            .map(Spans::from)
+            .collect()
     }
 }
 
+/// Tells that closing a session rolls back its open transaction.
+const SESSION_CLOSED: &str =
+    "The session was closed, rolling back the transaction left open in it, if any";
+
+/// Executes `statements`, given with the line they start on, one after the other until one
+/// fails. They run in `session`, or in a new session if there is none yet, which is handed back
+/// with the results so that the next execution continues in it.
+async fn execute_script(
+    pool: Arc<dyn Pool>,
+    handle: QueryHandle,
+    session: Option<EditorSession>,
+    statements: Vec<(usize, String)>,
+) -> Result<ScriptResult> {
+    let mut session = match session {
+        Some(session) => {
+            if let Some(id) = session.connection_id {
+                handle.set_connection_id(id);
+            }
+            session
+        }
+        None => EditorSession {
+            session: pool.session(&handle).await?,
+            connection_id: handle.connection_id(),
+        },
+    };
+    let count = statements.len();
+    let mut executed = vec![];
+    let mut error = None;
+    for (index, (line, statement)) in statements.into_iter().enumerate() {
+        let started = Instant::now();
+        match session.session.execute(&statement).await {
+            Ok(results) => executed.push(ExecutedStatement {
+                number: index + 1,
+                line,
+                elapsed: started.elapsed(),
+                results,
+            }),
+            Err(err) => {
+                error = Some(anyhow!(
+                    "Statement {} of {}, on line {}, failed: {}\n\n{}",
+                    index + 1,
+                    count,
+                    line,
+                    err,
+                    statement
+                ));
+                break;
+            }
+        }
+    }
+    Ok(ScriptResult {
+        statements: executed,
+        error,
+        session,
+    })
+}
+
 impl<B: Backend> Drawable<B> for SqlEditorComponent {
     fn draw(&mut self, f: &mut Frame<B>, area: Rect, focused: bool) -> Result<()> {
         let layout = Layout::default()
@@ -146,20 +384,54 @@ impl<B: Backend> Drawable<B> for SqlEditorComponent {
         self.text_area
             .draw(f, layout[0], focused && matches!(self.focus, Focus::Editor))?;
 
-        if let Some(result) = self.query_result.as_ref() {
-            let result = Paragraph::new(result.result_str())
-                .block(Block::default().borders(Borders::ALL).style(
-                    if focused && matches!(self.focus, Focus::Editor) {
-                        Style::default()
-                    } else {
-                        Style::default().fg(Color::DarkGray)
-                    },
-                ))
-                .wrap(Wrap { trim: true });
-            f.render_widget(result, layout[1]);
+        let table_focused = focused && matches!(self.focus, Focus::Table);
+        let block_style = if table_focused {
+            Style::default()
+        } else {
+            Style::default().fg(Color::DarkGray)
+        };
+        if let Some(task) = self.running_query.as_ref() {
+            let status = Paragraph::new(
+                task.progress_message("Running query", self.key_config.cancel_query),
+            )
+            .block(Block::default().borders(Borders::ALL).style(block_style))
+            .wrap(Wrap { trim: true });
+            f.render_widget(status, layout[1]);
+            return Ok(());
+        }
+        if self.summary.is_empty() {
+            f.render_widget(
+                Block::default().borders(Borders::ALL).style(block_style),
+                layout[1],
+            );
+            return Ok(());
+        }
+
+        let result_layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(1)])
+            .split(layout[1]);
+        let tabs = Tabs::new(self.result_titles())
+            .select(self.selected_result)
+            .style(block_style)
+            .highlight_style(
+                Style::default()
+                    .fg(Color::Reset)
+                    .add_modifier(Modifier::UNDERLINED),
+            );
+        f.render_widget(tabs, result_layout[0]);
+        if let Some(table) = self.result_tables.get_mut(self.selected_result) {
+            table.draw(f, result_layout[1], table_focused)?;
         } else {
-            self.table
-                .draw(f, layout[1], focused && matches!(self.focus, Focus::Table))?;
+            let summary = Paragraph::new(
+                self.summary
+                    .iter()
+                    .map(|line| Spans::from(line.as_str()))
+                    .collect::<Vec<Spans>>(),
+            )
+            .block(Block::default().borders(Borders::ALL).style(block_style))
+            .wrap(Wrap { trim: true });
+            f.render_widget(summary, result_layout[1]);
         }
         Ok(())
     }
@@ -167,7 +439,13 @@ impl<B: Backend> Drawable<B> for SqlEditorComponent {
 
 #[async_trait]
 impl Component for SqlEditorComponent {
-    fn commands(&self, _out: &mut Vec<CommandInfo>) {}
+    fn commands(&self, out: &mut Vec<CommandInfo>) {
+        out.push(CommandInfo::new(command::execute_statement(
+            &self.key_config,
+        )));
+        out.push(CommandInfo::new(command::execute_all(&self.key_config)));
+        out.push(CommandInfo::new(command::result_sets(&self.key_config)));
+    }
 
     async fn event(
         &mut self,
@@ -177,17 +455,127 @@ impl Component for SqlEditorComponent {
         return match self.focus {
             Focus::Editor => self.editor_key_event(key, message_queue).await,
             Focus::Table => {
+                if let Some(table) = self.result_tables.get_mut(self.selected_result) {
+                    if table.is_searching() {
+                        return table.event(key, message_queue).await;
+                    }
+                }
+                if self.cancel_query(key) {
+                    return Ok(EventState::Consumed);
+                }
                 if key == self.key_config.focus_above {
                     self.focus = Focus::Editor;
                     return Ok(EventState::Consumed);
                 }
-                self.table.event(key, message_queue).await
+                if key == self.key_config.next_result_set {
+                    self.selected_result = (self.selected_result + 1).min(self.result_tables.len());
+                    return Ok(EventState::Consumed);
+                }
+                if key == self.key_config.previous_result_set {
+                    self.selected_result = self.selected_result.saturating_sub(1);
+                    return Ok(EventState::Consumed);
+                }
+                match self.result_tables.get_mut(self.selected_result) {
+                    Some(table) => table.event(key, message_queue).await,
+                    None => Ok(EventState::NotConsumed),
+                }
             }
         };
     }
 
     async fn handle_messages(&mut self, messages: &Vec<Box<dyn AppMessage>>) -> Result<()> {
         self.text_area.handle_messages(messages).await?;
+        for m in messages.iter() {
+            handle_message!(m, QueryTaskFinished<ScriptResult>,
+                finished => {
+                    if matches!(&self.running_query, Some(task) if task.id() == finished.task_id) {
+                        self.running_query = None;
+                        if let Some(result) = finished.take_result() {
+                            self.apply_result(result?)?;
+                        }
+                    }
+                }
+            );
+            handle_message!(m, ConnectionEvent,
+                ConnectionEvent::ConnectionChanged(_) => {
+                    if !self.cancel_running_query("Query cancelled as the connection changed")
+                        && self.close_session()
+                    {
+                        self.summary.push(SESSION_CLOSED.to_string());
+                    }
+                }
+            );
+        }
         Ok(())
     }
 }
+
+impl Drop for SqlEditorComponent {
+    fn drop(&mut self) {
+        self.close_session();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use super::{execute_script, ExecutedStatement};
+    use crate::database::{ExecuteResult, Pool, QueryHandle, SqlitePool, Value};
+
+    #[test]
+    fn test_summary() {
+        let mut statement = ExecutedStatement {
+            number: 2,
+            line: 4,
+            elapsed: Duration::from_millis(12),
+            results: vec![
+                ExecuteResult::read(vec!["a".to_string()], vec![vec![Value::Int(1)]; 3]),
+                ExecuteResult::Write { updated_rows: 0 },
+            ],
+        };
+        assert_eq!(
+            statement.summary(),
+            "Statement 2, line 4: 3 rows returned, 0 rows affected (0.012s)"
+        );
+        statement.results.clear();
+        assert_eq!(
+            statement.summary(),
+            "Statement 2, line 4: no result (0.012s)"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_session_kept_across_executions() {
+        let pool: Arc<dyn Pool> = Arc::new(SqlitePool::new("sqlite::memory:").await.unwrap());
+        let statements = |statements: &[&str]| {
+            statements
+                .iter()
+                .map(|statement| (1, statement.to_string()))
+                .collect()
+        };
+        let result = execute_script(
+            pool.clone(),
+            QueryHandle::default(),
+            None,
+            statements(&["CREATE TEMP TABLE t (a INTEGER)", "BEGIN"]),
+        )
+        .await
+        .unwrap();
+        assert!(result.error.is_none());
+        let result = execute_script(
+            pool.clone(),
+            QueryHandle::default(),
+            Some(result.session),
+            statements(&["INSERT INTO t VALUES (1)", "COMMIT", "SELECT a FROM t"]),
+        )
+        .await
+        .unwrap();
+        assert!(result.error.is_none());
+        match &result.statements[2].results[..] {
+            [ExecuteResult::Read { rows, .. }] => assert_eq!(rows, &vec![vec![Value::Int(1)]]),
+            _ => panic!("expected the rows of t"),
+        }
+    }
+}
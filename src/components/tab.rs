@@ -326,7 +326,7 @@ impl<B: Backend> Component for TabPanel<B> {
                 TabMessage::NewEditor => {
                     let num = self.tab_components.len() - 1;
                     let tab_name = format!("Sql Editor {}", num);
-                    let new_editor = SqlEditorComponent::new(self.config.key_config.clone(), self.app_state.clone(), Some(tab_name.clone())).await;
+                    let new_editor = SqlEditorComponent::new(self.config.key_config.clone(), self.app_state.clone(), Some(tab_name.clone()), &self.config.editor.theme).await;
                     self.tab_components.push(Box::new(new_editor));
                     self.toolbar.add_tab(tab_name);
                 },TabMessage::CloseCurrentEditor => {
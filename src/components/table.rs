@@ -1,9 +1,16 @@
+use std::collections::{HashMap, HashSet};
 use std::convert::From;
+use std::ops::Range;
 
 use crate::clipboard::copy_to_clipboard;
 use crate::components::command::{self, CommandInfo};
+use crate::components::copy_as::CopyRequest;
+use crate::components::export::ExportRequest;
+use crate::components::table_value::InspectValueRequest;
 use crate::components::Drawable;
 use crate::config::KeyConfig;
+use crate::database::Value;
+use crate::event::Key;
 use anyhow::Result;
 use async_trait::async_trait;
 use database_tree::{Database, Table as DTable};
@@ -11,19 +18,44 @@ use tui::{
     backend::Backend,
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
-    widgets::{Block, Borders, Cell, Row, Table, TableState},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState},
     Frame,
 };
 use unicode_width::UnicodeWidthStr;
 
 use super::{
-    utils::scroll_vertical::VerticalScroll, Component, DrawableComponent, EventState,
-    TableStatusComponent, TableValueComponent,
+    utils::scroll_vertical::VerticalScroll, utils::selection_stats::SelectionStats,
+    utils::table_search::TableSearch, Component, DrawableComponent, EventState,
+    TableStatusComponent,
 };
 
+/// The narrowest a column can be made.
+const MIN_COLUMN_WIDTH: usize = 3;
+/// The widest a column is made to fit its values. Wider columns can be set by hand.
+const MAX_AUTO_COLUMN_WIDTH: usize = 20;
+/// The widest a column can be made.
+const MAX_COLUMN_WIDTH: usize = 200;
+/// The number of characters a column is widened or narrowed by at a time.
+const COLUMN_WIDTH_STEP: usize = 2;
+
+/// Orders the loaded rows by one of the columns.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sort {
+    pub column: usize,
+    pub descending: bool,
+}
+
+/// The columns pinned to the left of a table and the widths set by hand, both by header.
+#[derive(Clone, Default)]
+struct ColumnLayout {
+    frozen: Vec<String>,
+    widths: HashMap<String, usize>,
+}
+
 pub struct TableComponent {
     pub headers: Vec<String>,
-    pub rows: Vec<Vec<String>>,
+    pub rows: Vec<Vec<Value>>,
     pub eod: bool,
     pub selected_row: TableState,
     table: Option<(Database, DTable)>,
@@ -31,6 +63,21 @@ pub struct TableComponent {
     selection_area_corner: Option<(usize, usize)>,
     column_page_start: std::cell::Cell<usize>,
     scroll: VerticalScroll,
+    changed_cells: HashSet<(usize, usize)>,
+    deleted_rows: HashSet<usize>,
+    /// Shows the selected row as a list of fields instead of the rows.
+    record_view: bool,
+    /// The columns shown, in display order, or `None` to show all of them as loaded.
+    /// `selected_column` and `selection_area_corner` index into this list.
+    columns: Option<Vec<usize>>,
+    sort: Option<Sort>,
+    /// Set when the rows arrived sorted from the database, so that they are not sorted again.
+    sorted_by_database: bool,
+    /// The position each row was loaded at, used to restore the order when the sort is removed.
+    load_order: Vec<usize>,
+    search: TableSearch,
+    /// The column layouts by table, kept for the session.
+    layouts: HashMap<String, ColumnLayout>,
     key_config: KeyConfig,
 }
 
@@ -46,6 +93,15 @@ impl TableComponent {
             column_page_start: std::cell::Cell::new(0),
             scroll: VerticalScroll::new(false, false),
             eod: false,
+            changed_cells: HashSet::new(),
+            deleted_rows: HashSet::new(),
+            record_view: false,
+            columns: None,
+            sort: None,
+            sorted_by_database: false,
+            load_order: vec![],
+            search: TableSearch::default(),
+            layouts: HashMap::new(),
             key_config,
         }
     }
@@ -58,7 +114,7 @@ impl TableComponent {
 
     pub fn update(
         &mut self,
-        rows: Vec<Vec<String>>,
+        rows: Vec<Vec<Value>>,
         headers: Vec<String>,
         database: Database,
         table: DTable,
@@ -67,25 +123,52 @@ impl TableComponent {
         if !rows.is_empty() {
             self.selected_row.select(Some(0))
         }
+        // The columns and the sort are kept across reloads of the same columns.
+        let headers_changed = headers != self.headers;
+        if headers_changed {
+            self.columns = None;
+            self.sort = None;
+        }
+        if self.sorted_by_database {
+            self.sort = None;
+            self.sorted_by_database = false;
+        }
         self.headers = headers;
         self.rows = rows;
+        self.load_order.clear();
         self.selected_column = 0;
         self.selection_area_corner = None;
         self.column_page_start = std::cell::Cell::new(0);
         self.scroll = VerticalScroll::new(false, false);
         self.eod = false;
+        self.clear_marks();
         self.table = Some((database, table));
+        if headers_changed {
+            self.arrange_frozen_columns();
+        }
+        if self.sort.is_some() {
+            self.sort_rows();
+            self.selected_row
+                .select(Some(0).filter(|_| !self.rows.is_empty()));
+        }
+        self.refresh_search();
     }
 
     pub fn reset(&mut self) {
         self.selected_row.select(None);
         self.headers = Vec::new();
         self.rows = Vec::new();
+        self.columns = None;
+        self.sort = None;
+        self.sorted_by_database = false;
+        self.load_order.clear();
         self.selected_column = 0;
         self.selection_area_corner = None;
         self.column_page_start = std::cell::Cell::new(0);
         self.scroll = VerticalScroll::new(false, false);
         self.eod = false;
+        self.clear_marks();
+        self.search.clear();
         self.table = None;
     }
 
@@ -97,6 +180,478 @@ impl TableComponent {
         self.eod = true;
     }
 
+    /// Appends the next page of rows, keeping the current selection and scroll position.
+    pub fn append_rows(&mut self, rows: Vec<Vec<Value>>) {
+        if self.rows.is_empty() && !rows.is_empty() {
+            self.selected_row.select(Some(0));
+        }
+        self.rows.extend(rows);
+        if self.sort.is_some() && !self.sorted_by_database {
+            self.sort_rows();
+        }
+        self.refresh_search();
+    }
+
+    /// Returns the index of the column shown at `position`.
+    fn column(&self, position: usize) -> Option<usize> {
+        match &self.columns {
+            Some(columns) => columns.get(position).copied(),
+            None => Some(position),
+        }
+    }
+
+    /// Returns the number of columns shown.
+    fn column_count(&self) -> usize {
+        self.columns.as_ref().map_or(self.headers.len(), Vec::len)
+    }
+
+    /// Returns the indexes of the columns shown, in display order.
+    fn visible_columns(&self) -> Vec<usize> {
+        self.columns
+            .clone()
+            .unwrap_or_else(|| (0..self.headers.len()).collect())
+    }
+
+    /// Returns the value of `row` in the column shown at `position`.
+    fn cell<'a>(&self, row: &'a [Value], position: usize) -> Option<&'a Value> {
+        row.get(self.column(position)?)
+    }
+
+    /// Returns the header of the column shown at `position`, as loaded.
+    fn header_name(&self, position: usize) -> Option<&String> {
+        self.headers.get(self.column(position)?)
+    }
+
+    /// Returns the header of the column shown at `position`, with an arrow if the rows are
+    /// sorted by it.
+    fn header(&self, position: usize) -> Option<String> {
+        let column = self.column(position)?;
+        let header = self.headers.get(column)?;
+        Some(match self.sort {
+            Some(Sort {
+                column: sorted,
+                descending,
+            }) if sorted == column => {
+                format!("{} {}", header, if descending { "▼" } else { "▲" })
+            }
+            _ => header.clone(),
+        })
+    }
+
+    fn hide_column(&mut self) {
+        let mut columns = self.visible_columns();
+        if columns.len() <= 1 || self.selected_column >= columns.len() {
+            return;
+        }
+        columns.remove(self.selected_column);
+        self.selected_column = self.selected_column.min(columns.len() - 1);
+        self.columns = Some(columns);
+        self.reset_selection();
+        self.refresh_search();
+    }
+
+    fn show_all_columns(&mut self) {
+        let selected = self.column(self.selected_column).unwrap_or(0);
+        self.columns = None;
+        self.arrange_frozen_columns();
+        self.selected_column = self
+            .visible_columns()
+            .iter()
+            .position(|column| *column == selected)
+            .unwrap_or(0);
+        self.reset_selection();
+        self.refresh_search();
+    }
+
+    fn layout(&self) -> Option<&ColumnLayout> {
+        self.layouts.get(&self.title())
+    }
+
+    fn layout_mut(&mut self) -> &mut ColumnLayout {
+        self.layouts.entry(self.title()).or_default()
+    }
+
+    /// Returns the number of columns pinned to the left, which stay visible while scrolling.
+    fn frozen_count(&self) -> usize {
+        let layout = match self.layout() {
+            Some(layout) => layout,
+            None => return 0,
+        };
+        (0..self.column_count())
+            .take_while(|position| {
+                matches!(self.header_name(*position), Some(header) if layout.frozen.contains(header))
+            })
+            .count()
+    }
+
+    /// Moves the pinned columns in front of the others, in the order they were pinned.
+    fn arrange_frozen_columns(&mut self) {
+        let frozen = self
+            .layout()
+            .map(|layout| layout.frozen.clone())
+            .unwrap_or_default();
+        if frozen.is_empty() {
+            return;
+        }
+        let mut columns = self.visible_columns();
+        columns.sort_by_key(|column| {
+            frozen
+                .iter()
+                .position(|header| Some(header) == self.headers.get(*column))
+                .unwrap_or(frozen.len())
+        });
+        self.columns = Some(columns);
+    }
+
+    /// Pins the columns with the given headers to the left, e.g. the primary key.
+    pub fn freeze_columns(&mut self, headers: &[String]) {
+        let frozen = headers
+            .iter()
+            .filter(|header| self.headers.contains(header))
+            .cloned()
+            .collect();
+        self.layout_mut().frozen = frozen;
+        self.arrange_frozen_columns();
+        self.selected_column = 0;
+        self.reset_selection();
+        self.refresh_search();
+    }
+
+    /// Pins the columns up to the selected one, or unpins the selected column and the ones after
+    /// it if it is already pinned.
+    fn toggle_frozen_columns(&mut self) {
+        let end = if self.selected_column < self.frozen_count() {
+            self.selected_column
+        } else {
+            self.selected_column + 1
+        };
+        let frozen = (0..end)
+            .filter_map(|position| self.header_name(position).cloned())
+            .collect();
+        self.layout_mut().frozen = frozen;
+    }
+
+    /// Returns the width of the column shown at `position`: the width set by hand if any,
+    /// otherwise the width of its longest value.
+    fn column_width(&self, position: usize) -> usize {
+        if let Some(width) = self
+            .header_name(position)
+            .and_then(|header| self.layout()?.widths.get(header))
+        {
+            return *width;
+        }
+        self.rows
+            .iter()
+            .map(|row| {
+                self.cell(row, position)
+                    .map_or(String::new(), |cell| cell.to_string())
+                    .width()
+            })
+            .max()
+            .map_or(MIN_COLUMN_WIDTH, |v| {
+                v.max(self.header(position).map_or(3, |header| header.width()))
+                    .clamp(MIN_COLUMN_WIDTH, MAX_AUTO_COLUMN_WIDTH)
+            })
+    }
+
+    /// Widens the selected column, or narrows it, remembering its width for the table.
+    fn resize_column(&mut self, widen: bool) {
+        let header = match self.header_name(self.selected_column) {
+            Some(header) => header.clone(),
+            None => return,
+        };
+        let width = self.column_width(self.selected_column);
+        let width = if widen {
+            width + COLUMN_WIDTH_STEP
+        } else {
+            width.saturating_sub(COLUMN_WIDTH_STEP)
+        };
+        self.layout_mut()
+            .widths
+            .insert(header, width.clamp(MIN_COLUMN_WIDTH, MAX_COLUMN_WIDTH));
+    }
+
+    /// Swaps the selected column with its neighbour on the right, or on the left.
+    fn move_column(&mut self, right: bool) {
+        let mut columns = self.visible_columns();
+        let target = if right {
+            self.selected_column + 1
+        } else if let Some(target) = self.selected_column.checked_sub(1) {
+            target
+        } else {
+            return;
+        };
+        if target >= columns.len() {
+            return;
+        }
+        columns.swap(self.selected_column, target);
+        self.selected_column = target;
+        self.columns = Some(columns);
+        self.reset_selection();
+        self.refresh_search();
+    }
+
+    /// Returns the sort following the current one when sorting by the selected column:
+    /// ascending, then descending, then none.
+    pub fn next_sort(&self) -> Option<Sort> {
+        let column = self.column(self.selected_column)?;
+        match self.sort {
+            Some(sort) if sort.column == column && !sort.descending => Some(Sort {
+                column,
+                descending: true,
+            }),
+            Some(sort) if sort.column == column => None,
+            _ => Some(Sort {
+                column,
+                descending: false,
+            }),
+        }
+    }
+
+    /// Sorts the loaded rows, or restores the order they were loaded in if `sort` is `None`.
+    fn sort_by(&mut self, sort: Option<Sort>) {
+        self.sort = sort;
+        self.sorted_by_database = false;
+        self.sort_rows();
+    }
+
+    /// Records that the rows were sorted by the database, so that only the header shows it.
+    pub fn set_database_sort(&mut self, sort: Option<Sort>) {
+        self.sort = sort;
+        self.sorted_by_database = sort.is_some();
+    }
+
+    fn sort_rows(&mut self) {
+        // Rows appended since the last sort are in load order.
+        self.load_order
+            .extend(self.load_order.len()..self.rows.len());
+        let mut order = (0..self.rows.len()).collect::<Vec<usize>>();
+        match self.sort {
+            Some(Sort { column, descending }) => order.sort_by(|a, b| {
+                let ordering = match (self.rows[*a].get(column), self.rows[*b].get(column)) {
+                    (Some(a), Some(b)) => a.compare(b),
+                    (a, b) => a.is_some().cmp(&b.is_some()),
+                };
+                if descending {
+                    ordering.reverse()
+                } else {
+                    ordering
+                }
+            }),
+            None => order.sort_by_key(|index| self.load_order[*index]),
+        }
+        self.permute_rows(&order);
+    }
+
+    /// Moves the row at `order[i]` to `i`, together with its marks and the selection.
+    fn permute_rows(&mut self, order: &[usize]) {
+        let mut positions = vec![0; order.len()];
+        for (position, index) in order.iter().enumerate() {
+            positions[*index] = position;
+        }
+        let mut rows = std::mem::take(&mut self.rows)
+            .into_iter()
+            .map(Some)
+            .collect::<Vec<Option<Vec<Value>>>>();
+        self.rows = order
+            .iter()
+            .map(|index| rows[*index].take().unwrap_or_default())
+            .collect();
+        self.load_order = order.iter().map(|index| self.load_order[*index]).collect();
+        self.changed_cells = self
+            .changed_cells
+            .iter()
+            .map(|(row, column)| (positions[*row], *column))
+            .collect();
+        self.deleted_rows = self
+            .deleted_rows
+            .iter()
+            .map(|row| positions[*row])
+            .collect();
+        if let Some(selected) = self.selected_row.selected() {
+            self.selected_row.select(positions.get(selected).copied());
+        }
+        self.reset_selection();
+        self.refresh_search();
+    }
+
+    /// Looks for the matches of the search again, after the rows or the columns changed.
+    fn refresh_search(&mut self) {
+        let columns = self.visible_columns();
+        self.search.find(&self.rows, &columns);
+    }
+
+    /// Returns true while the search query is being typed, in which case all keys go to it.
+    pub fn is_searching(&self) -> bool {
+        self.search.is_editing()
+    }
+
+    /// Selects the cell in `row` and in the column shown at `position`.
+    fn select_cell(&mut self, (row, position): (usize, usize)) {
+        self.reset_selection();
+        self.selected_row.select(Some(row));
+        self.selected_column = position;
+    }
+
+    /// Selects the cell in `row` and in the column `column` of the loaded rows, if it is shown.
+    pub fn select_position(&mut self, row: usize, column: usize) {
+        if row >= self.rows.len() {
+            return;
+        }
+        if let Some(position) = self
+            .visible_columns()
+            .iter()
+            .position(|shown| *shown == column)
+        {
+            self.select_cell((row, position));
+        }
+    }
+
+    /// Handles the keys typed into the search query. Each change moves the selection to the
+    /// first match from where the search was started.
+    fn search_event(&mut self, key: Key) -> EventState {
+        if key == self.key_config.exit_popup {
+            self.search.clear();
+            return EventState::Consumed;
+        } else if key == self.key_config.enter {
+            self.search.finish();
+            return EventState::Consumed;
+        } else if key == self.key_config.toggle_search_regex {
+            self.search.toggle_regex();
+        } else if key == self.key_config.toggle_search_case {
+            self.search.toggle_ignore_case();
+        } else if key == Key::Backspace {
+            self.search.pop();
+        } else if let Key::Char(c) = key {
+            self.search.push(c);
+        } else {
+            return EventState::Consumed;
+        }
+        self.refresh_search();
+        if let Some(cell) = self.search.first_match() {
+            self.select_cell(cell);
+        }
+        EventState::Consumed
+    }
+
+    fn select_next_match(&mut self, forward: bool) {
+        if let Some(row) = self.selected_row.selected() {
+            if let Some(cell) = self.search.next_match((row, self.selected_column), forward) {
+                self.select_cell(cell);
+            }
+        }
+    }
+
+    fn is_search_match(&self, row_index: usize, column_index: usize) -> bool {
+        matches!(self.value_at(row_index, column_index), Some(value) if self.search.is_match(value))
+    }
+
+    /// Returns true if the last loaded row is selected.
+    pub fn is_last_row_selected(&self) -> bool {
+        matches!(self.selected_row.selected(), Some(index) if index + 1 >= self.rows.len())
+    }
+
+    /// Returns true if `key` moves the selection towards the following rows.
+    pub fn moves_to_next_rows(&self, key: Key) -> bool {
+        if self.record_view {
+            key == self.key_config.scroll_right
+        } else {
+            key == self.key_config.scroll_down
+                || key == self.key_config.scroll_down_multiple_lines
+                || key == self.key_config.scroll_to_bottom
+        }
+    }
+
+    /// Returns the (row, column) index of the cell under the cursor.
+    pub fn selected_cell_position(&self) -> Option<(usize, usize)> {
+        let row = self.selected_row.selected()?;
+        let column = self.column(self.selected_column)?;
+        self.rows.get(row)?.get(column)?;
+        Some((row, column))
+    }
+
+    /// Replaces the value of a single cell, e.g. after it has been updated in the database.
+    pub fn set_cell(&mut self, row: usize, column: usize, value: Value) {
+        if let Some(cell) = self.rows.get_mut(row).and_then(|row| row.get_mut(column)) {
+            *cell = value;
+        }
+        self.refresh_search();
+    }
+
+    /// Returns the range of rows covered by the selection.
+    pub fn selected_rows(&self) -> Option<Range<usize>> {
+        let selected = self.selected_row.selected()?;
+        let (start, end) = match self.selection_area_corner {
+            Some((_, y)) => (y.min(selected), y.max(selected)),
+            None => (selected, selected),
+        };
+        Some(start..end + 1)
+    }
+
+    /// Highlights a cell holding a change which has not been committed yet.
+    pub fn mark_changed_cell(&mut self, row: usize, column: usize) {
+        self.changed_cells.insert((row, column));
+    }
+
+    /// Highlights rows which are going to be deleted once the pending changes are committed.
+    pub fn mark_deleted_rows(&mut self, rows: Range<usize>) {
+        self.deleted_rows.extend(rows);
+    }
+
+    pub fn clear_marks(&mut self) {
+        self.changed_cells.clear();
+        self.deleted_rows.clear();
+    }
+
+    /// Returns the style of a cell holding a pending change, if any.
+    fn mark_style(&self, row: usize, column: usize) -> Option<Style> {
+        if self.deleted_rows.contains(&row) {
+            return Some(
+                Style::default()
+                    .fg(Color::Red)
+                    .add_modifier(Modifier::CROSSED_OUT),
+            );
+        }
+        if self.changed_cells.contains(&(row, column)) {
+            return Some(Style::default().fg(Color::Yellow));
+        }
+        None
+    }
+
+    fn cell_mark_style(&self, row_index: usize, column_index: usize) -> Option<Style> {
+        if self.deleted_rows.contains(&row_index) {
+            return self.mark_style(row_index, 0);
+        }
+        let column = self.column(self.drawn_position(column_index)?)?;
+        self.mark_style(row_index, column)
+    }
+
+    /// Returns the position of the column drawn at `column_index`: the pinned columns come
+    /// first, followed by the page of columns starting at `column_page_start`.
+    fn drawn_position(&self, column_index: usize) -> Option<usize> {
+        // Column 0 holds the row numbers.
+        let index = column_index.checked_sub(1)?;
+        let frozen = self.frozen_count();
+        Some(if index < frozen {
+            index
+        } else {
+            self.column_page_start.get() + index - frozen
+        })
+    }
+
+    /// Returns the value shown in the given cell of the drawn page of columns.
+    fn value_at(&self, row_index: usize, column_index: usize) -> Option<&Value> {
+        self.cell(
+            self.rows.get(row_index)?,
+            self.drawn_position(column_index)?,
+        )
+    }
+
+    fn value_style(&self, row_index: usize, column_index: usize) -> Style {
+        self.value_at(row_index, column_index)
+            .map_or_else(Style::default, value_style)
+    }
+
     fn next_row(&mut self, lines: usize) {
         let i = match self.selected_row.selected() {
             Some(i) => {
@@ -144,12 +699,45 @@ impl TableComponent {
             .select(Some(self.rows.len().saturating_sub(1)));
     }
 
+    fn select_column(&mut self, column: usize) {
+        if self.rows.is_empty() {
+            return;
+        }
+        self.reset_selection();
+        self.selected_column = column.min(self.column_count().saturating_sub(1));
+    }
+
+    /// Handles the keys of the record view: up and down move between the fields, left and right
+    /// between the records.
+    fn record_view_event(&mut self, key: Key) -> EventState {
+        if key == self.key_config.scroll_down {
+            self.select_column(self.selected_column + 1);
+        } else if key == self.key_config.scroll_up {
+            self.select_column(self.selected_column.saturating_sub(1));
+        } else if key == self.key_config.scroll_down_multiple_lines {
+            self.select_column(self.selected_column + 10);
+        } else if key == self.key_config.scroll_up_multiple_lines {
+            self.select_column(self.selected_column.saturating_sub(10));
+        } else if key == self.key_config.scroll_to_top {
+            self.select_column(0);
+        } else if key == self.key_config.scroll_to_bottom {
+            self.select_column(self.column_count());
+        } else if key == self.key_config.scroll_right {
+            self.next_row(1);
+        } else if key == self.key_config.scroll_left {
+            self.previous_row(1);
+        } else {
+            return EventState::NotConsumed;
+        }
+        EventState::Consumed
+    }
+
     fn next_column(&mut self) {
         if self.rows.is_empty() {
             return;
         }
         self.reset_selection();
-        if self.selected_column >= self.headers.len().saturating_sub(1) {
+        if self.selected_column >= self.column_count().saturating_sub(1) {
             return;
         }
         self.selected_column += 1;
@@ -176,7 +764,7 @@ impl TableComponent {
         if let Some((x, y)) = self.selection_area_corner {
             self.selection_area_corner = Some((
                 if positive {
-                    (x + 1).min(self.headers.len().saturating_sub(1))
+                    (x + 1).min(self.column_count().saturating_sub(1))
                 } else {
                     x.saturating_sub(1)
                 },
@@ -211,16 +799,59 @@ impl TableComponent {
                 self.rows[y.min(selected_row_index)..y.max(selected_row_index) + 1]
                     .iter()
                     .map(|row| {
-                        row[x.min(self.selected_column)..x.max(self.selected_column) + 1].join(",")
+                        (x.min(self.selected_column)..x.max(self.selected_column) + 1)
+                            .filter_map(|position| self.cell(row, position))
+                            .map(Value::to_string)
+                            .collect::<Vec<String>>()
+                            .join(",")
                     })
                     .collect::<Vec<String>>()
                     .join("\n"),
             );
         }
-        self.rows
-            .get(self.selected_row.selected()?)?
-            .get(self.selected_column)
-            .map(|cell| cell.to_string())
+        self.cell(
+            self.rows.get(self.selected_row.selected()?)?,
+            self.selected_column,
+        )
+        .map(|cell| cell.to_string())
+    }
+
+    /// Returns the aggregates of the selected cells, if more than one cell is selected.
+    fn selection_stats(&self) -> Option<SelectionStats> {
+        let (x, _) = self.selection_area_corner?;
+        let positions = x.min(self.selected_column)..=x.max(self.selected_column);
+        let rows = &self.rows[self.selected_rows()?];
+        Some(SelectionStats::new(rows.iter().flat_map(|row| {
+            positions
+                .clone()
+                .filter_map(move |position| self.cell(row, position))
+        })))
+    }
+
+    /// Returns the headers and the values of `rows` in the columns shown at `positions`.
+    fn columns_of(
+        &self,
+        rows: &[Vec<Value>],
+        positions: Range<usize>,
+    ) -> (Vec<String>, Vec<Vec<Value>>) {
+        let columns = positions
+            .filter_map(|position| self.column(position))
+            .filter(|column| *column < self.headers.len())
+            .collect::<Vec<usize>>();
+        (
+            columns
+                .iter()
+                .map(|column| self.headers[*column].clone())
+                .collect(),
+            rows.iter()
+                .map(|row| {
+                    columns
+                        .iter()
+                        .map(|column| row.get(*column).cloned().unwrap_or(Value::Null))
+                        .collect()
+                })
+                .collect(),
+        )
     }
 
     fn selected_column_index(&self) -> usize {
@@ -230,29 +861,19 @@ impl TableComponent {
         self.selected_column
     }
 
-    fn is_selected_cell(
-        &self,
-        row_index: usize,
-        column_index: usize,
-        selected_column_index: usize,
-    ) -> bool {
-        if let Some((x, y)) = self.selection_area_corner {
-            let x_in_page = x
-                .saturating_add(1)
-                .saturating_sub(self.column_page_start.get());
-            return matches!(
-                self.selected_row.selected(),
-                Some(selected_row_index)
-                if (x_in_page.min(selected_column_index).max(1)..x_in_page.max(selected_column_index) + 1)
-                    .contains(&column_index)
-                    && (y.min(selected_row_index)..y.max(selected_row_index) + 1)
-                        .contains(&row_index)
-            );
-        }
-        matches!(
+    fn is_selected_cell(&self, row_index: usize, column_index: usize) -> bool {
+        let (position, selected_row_index) = match (
+            self.drawn_position(column_index),
             self.selected_row.selected(),
-            Some(selected_row_index) if row_index == selected_row_index &&  column_index == selected_column_index
-        )
+        ) {
+            (Some(position), Some(selected_row_index)) => (position, selected_row_index),
+            _ => return false,
+        };
+        let (x, y) = self
+            .selection_area_corner
+            .unwrap_or((self.selected_column, selected_row_index));
+        (x.min(self.selected_column)..=x.max(self.selected_column)).contains(&position)
+            && (y.min(selected_row_index)..=y.max(selected_row_index)).contains(&row_index)
     }
 
     fn is_number_column(&self, row_index: usize, column_index: usize) -> bool {
@@ -262,26 +883,41 @@ impl TableComponent {
         )
     }
 
+    /// Returns the headers of the pinned columns and of the columns from `left` to `right`.
     fn headers(&self, left: usize, right: usize) -> Vec<String> {
-        let mut headers = self.headers.clone()[left..right].to_vec();
+        let mut headers = (0..self.frozen_count())
+            .chain(left..right)
+            .filter_map(|position| self.header(position))
+            .collect::<Vec<String>>();
         headers.insert(0, "".to_string());
         headers
     }
 
+    /// Returns the values of the pinned columns and of the columns from `left` to `right`.
     fn rows(&self, left: usize, right: usize) -> Vec<Vec<String>> {
-        let rows = self
+        let frozen = self.frozen_count();
+        let mut new_rows: Vec<Vec<String>> = self
             .rows
             .iter()
-            .map(|row| row.to_vec())
-            .collect::<Vec<Vec<String>>>();
-        let mut new_rows: Vec<Vec<String>> =
-            rows.iter().map(|row| row[left..right].to_vec()).collect();
+            .map(|row| {
+                (0..frozen)
+                    .chain(left..right)
+                    .map(|position| {
+                        self.cell(row, position)
+                            .map_or_else(String::new, Value::to_string)
+                    })
+                    .collect()
+            })
+            .collect();
         for (index, row) in new_rows.iter_mut().enumerate() {
             row.insert(0, (index + 1).to_string())
         }
         new_rows
     }
 
+    /// Lays out the pinned columns, followed by as many of the other columns as fit around the
+    /// selected one. Returns the index of the selected column among the drawn ones, the drawn
+    /// headers and rows, and the widths of the drawn columns.
     fn calculate_cell_widths(
         &self,
         area_width: u16,
@@ -289,43 +925,31 @@ impl TableComponent {
         if self.rows.is_empty() {
             return (0, Vec::new(), Vec::new(), Vec::new());
         }
-        if self.selected_column_index() < self.column_page_start.get() {
-            self.column_page_start.set(self.selected_column_index());
-        }
-
-        let far_right_column_index = self.selected_column_index();
-        let mut column_index = self.selected_column_index();
+        let frozen = self.frozen_count();
         let number_column_width = (self.rows.len() + 1).to_string().width() as u16;
+        let frozen_widths = (0..frozen)
+            .map(|position| self.column_width(position))
+            .collect::<Vec<usize>>();
+        let area_width = (area_width.saturating_sub(number_column_width) as usize)
+            .saturating_sub(frozen_widths.iter().map(|width| width + 1).sum());
+
+        // The pinned columns are always drawn, so only the others are scrolled through.
+        let page_start = self.column_page_start.get().max(frozen);
+        let far_right_column_index = match self.selected_column_index() {
+            selected if selected < frozen => page_start,
+            selected => selected,
+        };
+        let page_start = page_start.min(far_right_column_index);
+        let mut column_index = far_right_column_index;
         let mut widths = Vec::new();
-        loop {
-            let length = self
-                .rows
-                .iter()
-                .map(|row| {
-                    row.get(column_index)
-                        .map_or(String::new(), |cell| cell.to_string())
-                        .width()
-                })
-                .collect::<Vec<usize>>()
-                .iter()
-                .max()
-                .map_or(3, |v| {
-                    *v.max(
-                        &self
-                            .headers
-                            .get(column_index)
-                            .map_or(3, |header| header.to_string().width()),
-                    )
-                    .clamp(&3, &20)
-                });
-            if widths.iter().map(|(_, width)| width).sum::<usize>() + length + widths.len() + 1
-                >= area_width.saturating_sub(number_column_width) as usize
-            {
+        while column_index < self.column_count() {
+            let length = self.column_width(column_index);
+            if widths.iter().sum::<usize>() + length + widths.len() + 1 >= area_width {
                 column_index += 1;
                 break;
             }
-            widths.push((self.headers[column_index].clone(), length));
-            if column_index == self.column_page_start.get() {
+            widths.push(length);
+            if column_index == page_start {
                 break;
             }
             column_index -= 1;
@@ -333,105 +957,45 @@ impl TableComponent {
         widths.reverse();
 
         let far_left_column_index = column_index;
-        let selected_column_index = widths.len().saturating_sub(1);
         let mut column_index = far_right_column_index + 1;
-        while widths.iter().map(|(_, width)| width).sum::<usize>() + widths.len()
-            < area_width.saturating_sub(number_column_width) as usize
+        while widths.iter().sum::<usize>() + widths.len() < area_width
+            && column_index < self.column_count()
         {
-            let length = self
-                .rows
-                .iter()
-                .map(|row| {
-                    row.get(column_index)
-                        .map_or(String::new(), |cell| cell.to_string())
-                        .width()
-                })
-                .collect::<Vec<usize>>()
-                .iter()
-                .max()
-                .map_or(3, |v| {
-                    *v.max(
-                        self.headers
-                            .iter()
-                            .map(|header| header.to_string().width())
-                            .collect::<Vec<usize>>()
-                            .get(column_index)
-                            .unwrap_or(&3),
-                    )
-                    .clamp(&3, &20)
-                });
-            match self.headers.get(column_index) {
-                Some(header) => {
-                    widths.push((header.to_string(), length));
-                }
-                None => break,
-            }
+            widths.push(self.column_width(column_index));
             column_index += 1
         }
-        if self.selected_column_index() != self.headers.len().saturating_sub(1)
-            && column_index.saturating_sub(1) != self.headers.len().saturating_sub(1)
-        {
+        let truncated = column_index < self.column_count();
+        if truncated {
             widths.pop();
         }
-        let far_right_column_index = column_index;
-        let mut constraints = widths
+        let far_right_column_index = column_index.min(self.column_count());
+        let mut constraints = frozen_widths
             .iter()
-            .map(|(_, width)| Constraint::Length(*width as u16))
+            .chain(widths.iter())
+            .map(|width| Constraint::Length(*width as u16))
             .collect::<Vec<Constraint>>();
-        if self.selected_column_index() != self.headers.len().saturating_sub(1)
-            && column_index.saturating_sub(1) != self.headers.len().saturating_sub(1)
-        {
+        if truncated {
             constraints.push(Constraint::Min(10));
         }
         constraints.insert(0, Constraint::Length(number_column_width));
         self.column_page_start.set(far_left_column_index);
 
         (
-            self.selection_area_corner
-                .map_or(selected_column_index + 1, |(x, _)| {
-                    if x > self.selected_column {
-                        (selected_column_index + 1)
-                            .saturating_sub(x.saturating_sub(self.selected_column))
-                    } else {
-                        (selected_column_index + 1)
-                            .saturating_add(self.selected_column.saturating_sub(x))
-                    }
-                }),
+            if self.selected_column < frozen {
+                self.selected_column + 1
+            } else {
+                self.selected_column
+                    .checked_sub(far_left_column_index)
+                    .map_or(0, |offset| frozen + offset + 1)
+            },
             self.headers(far_left_column_index, far_right_column_index),
             self.rows(far_left_column_index, far_right_column_index),
             constraints,
         )
     }
-}
-
-impl<B: Backend> Drawable<B> for TableComponent {
-    fn draw(&mut self, f: &mut Frame<B>, area: Rect, focused: bool) -> Result<()> {
-        let chunks = Layout::default()
-            .vertical_margin(1)
-            .horizontal_margin(1)
-            .direction(Direction::Vertical)
-            .constraints(
-                [
-                    Constraint::Length(2),
-                    Constraint::Min(1),
-                    Constraint::Length(2),
-                ]
-                .as_ref(),
-            )
-            .split(area);
-
-        f.render_widget(
-            Block::default()
-                .title(self.title())
-                .borders(Borders::ALL)
-                .style(if focused {
-                    Style::default()
-                } else {
-                    Style::default().fg(Color::DarkGray)
-                }),
-            area,
-        );
 
+    /// Draws the loaded rows, one per line, paging through the columns that do not fit.
+    fn draw_rows<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect, focused: bool) {
         self.selected_row.selected().map_or_else(
             || {
                 self.scroll.reset();
@@ -440,14 +1004,14 @@ impl<B: Backend> Drawable<B> for TableComponent {
                 self.scroll.update(
                     selection,
                     self.rows.len(),
-                    chunks[1].height.saturating_sub(2) as usize,
+                    area.height.saturating_sub(2) as usize,
                 );
             },
         );
 
         let block = Block::default().borders(Borders::NONE);
         let (selected_column_index, headers, rows, constraints) =
-            self.calculate_cell_widths(block.inner(chunks[0]).width);
+            self.calculate_cell_widths(block.inner(area).width);
         let header_cells = headers.iter().enumerate().map(|(column_index, h)| {
             Cell::from(h.to_string()).style(if selected_column_index == column_index {
                 Style::default().add_modifier(Modifier::BOLD)
@@ -464,42 +1028,198 @@ impl<B: Backend> Drawable<B> for TableComponent {
                 .unwrap_or(0)
                 + 1;
             let cells = item.iter().enumerate().map(|(column_index, c)| {
-                Cell::from(c.to_string()).style(
-                    if self.is_selected_cell(row_index, column_index, selected_column_index) {
-                        Style::default().bg(Color::Rgb(0xea, 0x59, 0x0b))
-                    } else if self.is_number_column(row_index, column_index) {
-                        Style::default().add_modifier(Modifier::BOLD)
-                    } else {
-                        Style::default()
-                    },
-                )
+                // Numbers are right aligned.
+                let text = match (
+                    self.value_at(row_index, column_index),
+                    constraints.get(column_index),
+                ) {
+                    (Some(value), Some(Constraint::Length(width))) if value.is_number() => {
+                        format!("{:>width$}", c, width = *width as usize)
+                    }
+                    _ => c.to_string(),
+                };
+                Cell::from(text).style(if self.is_selected_cell(row_index, column_index) {
+                    Style::default().bg(Color::Rgb(0xea, 0x59, 0x0b))
+                } else if self.is_number_column(row_index, column_index) {
+                    Style::default().add_modifier(Modifier::BOLD)
+                } else if self.is_search_match(row_index, column_index) {
+                    search_match_style()
+                } else {
+                    self.cell_mark_style(row_index, column_index)
+                        .unwrap_or_else(|| self.value_style(row_index, column_index))
+                })
             });
             Row::new(cells).height(height as u16).bottom_margin(1)
         });
 
-        let table = Table::new(rows)
-            .header(header)
-            .block(block)
-            .style(if focused {
-                Style::default()
-            } else {
-                Style::default().fg(Color::DarkGray)
-            })
-            .widths(&constraints);
-        let mut state = self.selected_row.clone();
-        f.render_stateful_widget(
-            table,
-            chunks[1],
-            if let Some((_, y)) = self.selection_area_corner {
-                state.select(Some(y));
-                &mut state
-            } else {
-                &mut self.selected_row
-            },
+        let table = Table::new(rows)
+            .header(header)
+            .block(block)
+            .style(if focused {
+                Style::default()
+            } else {
+                Style::default().fg(Color::DarkGray)
+            })
+            .widths(&constraints);
+        let mut state = self.selected_row.clone();
+        f.render_stateful_widget(
+            table,
+            area,
+            if let Some((_, y)) = self.selection_area_corner {
+                state.select(Some(y));
+                &mut state
+            } else {
+                &mut self.selected_row
+            },
+        );
+    }
+
+    /// Draws the selected row as a list of fields, one per line.
+    fn draw_record<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect, focused: bool) {
+        let row_index = match self.selected_row.selected() {
+            Some(row_index) if row_index < self.rows.len() => row_index,
+            _ => {
+                self.scroll.reset();
+                return;
+            }
+        };
+        self.scroll.update(
+            self.selected_column,
+            self.column_count(),
+            area.height.saturating_sub(2) as usize,
+        );
+        let fields = (0..self.column_count())
+            .filter_map(|position| {
+                let column = self.column(position)?;
+                Some((position, column, self.header(position)?))
+            })
+            .collect::<Vec<(usize, usize, String)>>();
+        let field_width = fields
+            .iter()
+            .map(|(_, _, header)| header.width())
+            .max()
+            .unwrap_or(0)
+            .clamp(5, 30);
+        let rows = fields.into_iter().map(|(position, column, header)| {
+            let value = self.rows[row_index].get(column).unwrap_or(&Value::Null);
+            let text = value.to_string();
+            let height = text.chars().filter(|c| *c == '\n').count() + 1;
+            Row::new(vec![
+                Cell::from(header).style(Style::default().add_modifier(Modifier::BOLD)),
+                Cell::from(text).style(if position == self.selected_column {
+                    Style::default().bg(Color::Rgb(0xea, 0x59, 0x0b))
+                } else if self.search.is_match(value) {
+                    search_match_style()
+                } else {
+                    self.mark_style(row_index, column)
+                        .unwrap_or_else(|| value_style(value))
+                }),
+            ])
+            .height(height as u16)
+        });
+        let header = Row::new(vec![
+            format!(
+                "Field ({} of {})",
+                self.selected_column + 1,
+                self.column_count()
+            ),
+            format!("Value (record {} of {})", row_index + 1, self.rows.len()),
+        ])
+        .style(Style::default().add_modifier(Modifier::BOLD))
+        .bottom_margin(1);
+        let constraints = [Constraint::Length(field_width as u16), Constraint::Min(10)];
+        let table = Table::new(rows)
+            .header(header)
+            .style(if focused {
+                Style::default()
+            } else {
+                Style::default().fg(Color::DarkGray)
+            })
+            .widths(&constraints);
+        let mut state = TableState::default();
+        state.select(Some(self.selected_column));
+        f.render_stateful_widget(table, area, &mut state);
+    }
+}
+
+fn search_match_style() -> Style {
+    Style::default().fg(Color::Black).bg(Color::Yellow)
+}
+
+/// Null values are dimmed, so that they can be told apart from the text `NULL`.
+fn value_style(value: &Value) -> Style {
+    if value.is_null() {
+        Style::default().fg(Color::DarkGray)
+    } else {
+        Style::default()
+    }
+}
+
+impl<B: Backend> Drawable<B> for TableComponent {
+    fn draw(&mut self, f: &mut Frame<B>, area: Rect, focused: bool) -> Result<()> {
+        let chunks = Layout::default()
+            .vertical_margin(1)
+            .horizontal_margin(1)
+            .direction(Direction::Vertical)
+            .constraints(
+                [
+                    Constraint::Length(2),
+                    Constraint::Min(1),
+                    Constraint::Length(2),
+                ]
+                .as_ref(),
+            )
+            .split(area);
+
+        f.render_widget(
+            Block::default()
+                .title(self.title())
+                .borders(Borders::ALL)
+                .style(if focused {
+                    Style::default()
+                } else {
+                    Style::default().fg(Color::DarkGray)
+                }),
+            area,
         );
 
-        TableValueComponent::new(self.selected_cells().unwrap_or_default())
-            .draw(f, chunks[0], focused)?;
+        if self.record_view {
+            self.draw_record(f, chunks[1], focused);
+        } else {
+            self.draw_rows(f, chunks[1], focused);
+        }
+
+        if self.search.is_editing() {
+            let prompt = format!(
+                "Search ({}; {}, {}): ",
+                self.search.modes(),
+                self.key_config.toggle_search_regex,
+                self.key_config.toggle_search_case
+            );
+            f.set_cursor(
+                chunks[0].x + (prompt.width() + self.search.query().width()) as u16,
+                chunks[0].y,
+            );
+            f.render_widget(
+                Paragraph::new(Spans::from(vec![
+                    Span::styled(prompt, Style::default().fg(Color::Rgb(0xea, 0x59, 0x0b))),
+                    Span::raw(self.search.query()),
+                ]))
+                .block(Block::default().borders(Borders::BOTTOM)),
+                chunks[0],
+            );
+        } else {
+            f.render_widget(
+                Paragraph::new(self.selected_cells().unwrap_or_default())
+                    .block(Block::default().borders(Borders::BOTTOM))
+                    .style(if focused {
+                        Style::default()
+                    } else {
+                        Style::default().fg(Color::DarkGray)
+                    }),
+                chunks[0],
+            );
+        }
 
         TableStatusComponent::new(
             if self.rows.is_empty() {
@@ -514,6 +1234,14 @@ impl<B: Backend> Drawable<B> for TableComponent {
             },
             self.table.as_ref().map(|t| t.1.clone()),
         )
+        .with_selection(self.selection_stats())
+        .with_search(
+            self.search.status(
+                self.selected_row
+                    .selected()
+                    .map(|row| (row, self.selected_column)),
+            ),
+        )
         .draw(f, chunks[2], focused)?;
 
         self.scroll.draw(f, chunks[1]);
@@ -527,19 +1255,129 @@ impl Component for TableComponent {
         out.push(CommandInfo::new(command::extend_selection_by_one_cell(
             &self.key_config,
         )));
+        out.push(CommandInfo::new(command::export(&self.key_config)));
+        out.push(CommandInfo::new(command::copy_as(&self.key_config)));
+        out.push(CommandInfo::new(command::inspect_value(&self.key_config)));
+        out.push(CommandInfo::new(command::toggle_record_view(
+            &self.key_config,
+        )));
+        out.push(CommandInfo::new(command::sort_by_column(&self.key_config)));
+        out.push(CommandInfo::new(command::hide_show_columns(
+            &self.key_config,
+        )));
+        out.push(CommandInfo::new(command::move_column(&self.key_config)));
+        out.push(CommandInfo::new(command::search(&self.key_config)));
+        out.push(CommandInfo::new(command::freeze_columns(&self.key_config)));
+        out.push(CommandInfo::new(command::resize_column(&self.key_config)));
     }
 
     async fn event(
         &mut self,
         key: crate::event::Key,
-        _message_queue: &mut crate::app::GlobalMessageQueue,
+        message_queue: &mut crate::app::GlobalMessageQueue,
     ) -> Result<EventState> {
+        if self.search.is_editing() {
+            return Ok(self.search_event(key));
+        }
+        if key == self.key_config.search && !self.rows.is_empty() {
+            self.search.start((
+                self.selected_row.selected().unwrap_or(0),
+                self.selected_column,
+            ));
+            return Ok(EventState::Consumed);
+        }
+        if self.search.is_active() {
+            if key == self.key_config.next_match {
+                self.select_next_match(true);
+                return Ok(EventState::Consumed);
+            } else if key == self.key_config.previous_match {
+                self.select_next_match(false);
+                return Ok(EventState::Consumed);
+            } else if key == self.key_config.exit_popup {
+                self.search.clear();
+                return Ok(EventState::Consumed);
+            }
+        }
+        if key == self.key_config.export && !self.headers.is_empty() {
+            // Hidden columns are left out, and the others exported in the order shown.
+            let (headers, rows) = self.columns_of(&self.rows, 0..self.column_count());
+            message_queue.push(Box::new(ExportRequest {
+                headers,
+                rows,
+                table: self.table.clone(),
+            }));
+            return Ok(EventState::Consumed);
+        }
+        if key == self.key_config.copy_as {
+            if let Some(rows) = self.selected_rows() {
+                let (x, _) = self
+                    .selection_area_corner
+                    .unwrap_or((self.selected_column, 0));
+                let positions = x.min(self.selected_column)..x.max(self.selected_column) + 1;
+                let (headers, rows) = self.columns_of(&self.rows[rows], positions);
+                message_queue.push(Box::new(CopyRequest {
+                    headers,
+                    rows,
+                    table: self.table.clone(),
+                }));
+                return Ok(EventState::Consumed);
+            }
+        }
+        if key == self.key_config.inspect_value {
+            if let Some((row, column)) = self.selected_cell_position() {
+                message_queue.push(Box::new(InspectValueRequest {
+                    header: self.headers[column].clone(),
+                    value: self.rows[row][column].clone(),
+                }));
+                return Ok(EventState::Consumed);
+            }
+        }
         if key == self.key_config.copy {
             if let Some(text) = self.selected_cells() {
                 copy_to_clipboard(text.as_str())?;
                 return Ok(EventState::Consumed);
             }
         }
+        if key == self.key_config.sort_by_column && !self.rows.is_empty() {
+            self.sort_by(self.next_sort());
+            return Ok(EventState::Consumed);
+        }
+        if key == self.key_config.hide_column {
+            self.hide_column();
+            return Ok(EventState::Consumed);
+        }
+        if key == self.key_config.show_all_columns {
+            self.show_all_columns();
+            return Ok(EventState::Consumed);
+        }
+        if key == self.key_config.move_column_left {
+            self.move_column(false);
+            return Ok(EventState::Consumed);
+        }
+        if key == self.key_config.move_column_right {
+            self.move_column(true);
+            return Ok(EventState::Consumed);
+        }
+        if key == self.key_config.freeze_columns {
+            self.toggle_frozen_columns();
+            return Ok(EventState::Consumed);
+        }
+        if key == self.key_config.widen_column {
+            self.resize_column(true);
+            return Ok(EventState::Consumed);
+        }
+        if key == self.key_config.narrow_column {
+            self.resize_column(false);
+            return Ok(EventState::Consumed);
+        }
+        if key == self.key_config.toggle_record_view {
+            self.record_view = !self.record_view;
+            self.reset_selection();
+            return Ok(EventState::Consumed);
+        }
+        if self.record_view {
+            return Ok(self.record_view_event(key));
+        }
         if key == self.key_config.scroll_left {
             self.previous_column();
             return Ok(EventState::Consumed);
@@ -585,7 +1423,7 @@ impl Component for TableComponent {
 mod test {
     use tui::layout::Constraint;
 
-    use super::{KeyConfig, TableComponent};
+    use super::{Key, KeyConfig, Sort, TableComponent, Value};
 
     #[test]
     fn test_headers() {
@@ -598,8 +1436,14 @@ mod test {
     fn test_rows() {
         let mut component = TableComponent::new(KeyConfig::default());
         component.rows = vec![
-            vec!["a", "b", "c"].iter().map(|h| h.to_string()).collect(),
-            vec!["d", "e", "f"].iter().map(|h| h.to_string()).collect(),
+            vec!["a", "b", "c"]
+                .iter()
+                .map(|h| Value::from(*h))
+                .collect(),
+            vec!["d", "e", "f"]
+                .iter()
+                .map(|h| Value::from(*h))
+                .collect(),
         ];
         assert_eq!(component.rows(1, 2), vec![vec!["1", "b"], vec!["2", "e"]],)
     }
@@ -619,8 +1463,14 @@ mod test {
         let mut component = TableComponent::new(KeyConfig::default());
         component.headers = vec!["1", "2", "3"].iter().map(|h| h.to_string()).collect();
         component.rows = vec![
-            vec!["a", "b", "c"].iter().map(|h| h.to_string()).collect(),
-            vec!["d", "e", "f"].iter().map(|h| h.to_string()).collect(),
+            vec!["a", "b", "c"]
+                .iter()
+                .map(|h| Value::from(*h))
+                .collect(),
+            vec!["d", "e", "f"]
+                .iter()
+                .map(|h| Value::from(*h))
+                .collect(),
         ];
         component.selected_row.select(Some(1));
         component.selected_column = 1;
@@ -644,8 +1494,14 @@ mod test {
         let mut component = TableComponent::new(KeyConfig::default());
         component.headers = vec!["1", "2", "3"].iter().map(|h| h.to_string()).collect();
         component.rows = vec![
-            vec!["a", "b", "c"].iter().map(|h| h.to_string()).collect(),
-            vec!["d", "e", "f"].iter().map(|h| h.to_string()).collect(),
+            vec!["a", "b", "c"]
+                .iter()
+                .map(|h| Value::from(*h))
+                .collect(),
+            vec!["d", "e", "f"]
+                .iter()
+                .map(|h| Value::from(*h))
+                .collect(),
         ];
         component.selected_row.select(Some(1));
         component.selected_column = 1;
@@ -668,8 +1524,14 @@ mod test {
 
         let mut component = TableComponent::new(KeyConfig::default());
         component.rows = vec![
-            vec!["a", "b", "c"].iter().map(|h| h.to_string()).collect(),
-            vec!["d", "e", "f"].iter().map(|h| h.to_string()).collect(),
+            vec!["a", "b", "c"]
+                .iter()
+                .map(|h| Value::from(*h))
+                .collect(),
+            vec!["d", "e", "f"]
+                .iter()
+                .map(|h| Value::from(*h))
+                .collect(),
         ];
         component.selected_row.select(Some(1));
         component.selected_column = 1;
@@ -692,8 +1554,14 @@ mod test {
 
         let mut component = TableComponent::new(KeyConfig::default());
         component.rows = vec![
-            vec!["a", "b", "c"].iter().map(|h| h.to_string()).collect(),
-            vec!["d", "e", "f"].iter().map(|h| h.to_string()).collect(),
+            vec!["a", "b", "c"]
+                .iter()
+                .map(|h| Value::from(*h))
+                .collect(),
+            vec!["d", "e", "f"]
+                .iter()
+                .map(|h| Value::from(*h))
+                .collect(),
         ];
         component.selected_row.select(Some(0));
         component.selected_column = 1;
@@ -702,13 +1570,70 @@ mod test {
         assert_eq!(component.selected_cells(), Some("b\ne".to_string()));
     }
 
+    #[test]
+    fn test_append_rows() {
+        let mut component = TableComponent::new(KeyConfig::default());
+        component.headers = vec!["1", "2"].iter().map(|h| h.to_string()).collect();
+        component.rows = vec![vec!["a", "b"].iter().map(|h| Value::from(*h)).collect()];
+        component.selected_row.select(Some(0));
+        assert!(component.is_last_row_selected());
+
+        component.append_rows(vec![vec!["c", "d"]
+            .iter()
+            .map(|h| Value::from(*h))
+            .collect()]);
+        assert_eq!(component.rows.len(), 2);
+        assert_eq!(component.selected_row.selected(), Some(0));
+        assert!(!component.is_last_row_selected());
+    }
+
+    #[test]
+    fn test_selected_rows() {
+        let mut component = TableComponent::new(KeyConfig::default());
+        component.headers = vec!["1", "2"].iter().map(|h| h.to_string()).collect();
+        component.rows = vec![
+            vec!["a", "b"].iter().map(|h| Value::from(*h)).collect(),
+            vec!["c", "d"].iter().map(|h| Value::from(*h)).collect(),
+            vec!["e", "f"].iter().map(|h| Value::from(*h)).collect(),
+        ];
+        component.selected_row.select(Some(2));
+        assert_eq!(component.selected_rows(), Some(2..3));
+        component.expand_selected_area_y(false);
+        assert_eq!(component.selected_rows(), Some(1..3));
+    }
+
+    #[test]
+    fn test_cell_mark_style() {
+        let mut component = TableComponent::new(KeyConfig::default());
+        component.headers = vec!["1", "2"].iter().map(|h| h.to_string()).collect();
+        component.rows = vec![
+            vec!["a", "b"].iter().map(|h| Value::from(*h)).collect(),
+            vec!["c", "d"].iter().map(|h| Value::from(*h)).collect(),
+        ];
+        component.mark_changed_cell(0, 1);
+        component.mark_deleted_rows(1..2);
+        assert_eq!(component.cell_mark_style(0, 1), None);
+        assert!(component.cell_mark_style(0, 2).is_some());
+        assert!(component.cell_mark_style(1, 1).is_some());
+
+        component.clear_marks();
+        assert_eq!(component.cell_mark_style(0, 2), None);
+        assert_eq!(component.cell_mark_style(1, 1), None);
+    }
+
     #[test]
     fn test_is_number_column() {
         let mut component = TableComponent::new(KeyConfig::default());
         component.headers = vec!["1", "2", "3"].iter().map(|h| h.to_string()).collect();
         component.rows = vec![
-            vec!["a", "b", "c"].iter().map(|h| h.to_string()).collect(),
-            vec!["d", "e", "f"].iter().map(|h| h.to_string()).collect(),
+            vec!["a", "b", "c"]
+                .iter()
+                .map(|h| Value::from(*h))
+                .collect(),
+            vec!["d", "e", "f"]
+                .iter()
+                .map(|h| Value::from(*h))
+                .collect(),
         ];
         component.selected_row.select(Some(0));
         assert!(component.is_number_column(0, 0));
@@ -724,8 +1649,14 @@ mod test {
         let mut component = TableComponent::new(KeyConfig::default());
         component.headers = vec!["1", "2", "3"].iter().map(|h| h.to_string()).collect();
         component.rows = vec![
-            vec!["a", "b", "c"].iter().map(|h| h.to_string()).collect(),
-            vec!["d", "e", "f"].iter().map(|h| h.to_string()).collect(),
+            vec!["a", "b", "c"]
+                .iter()
+                .map(|h| Value::from(*h))
+                .collect(),
+            vec!["d", "e", "f"]
+                .iter()
+                .map(|h| Value::from(*h))
+                .collect(),
         ];
         component.selected_row.select(Some(0));
         assert_eq!(component.selected_cells(), Some("a".to_string()));
@@ -740,8 +1671,14 @@ mod test {
         let mut component = TableComponent::new(KeyConfig::default());
         component.headers = vec!["1", "2", "3"].iter().map(|h| h.to_string()).collect();
         component.rows = vec![
-            vec!["a", "b", "c"].iter().map(|h| h.to_string()).collect(),
-            vec!["d", "e", "f"].iter().map(|h| h.to_string()).collect(),
+            vec!["a", "b", "c"]
+                .iter()
+                .map(|h| Value::from(*h))
+                .collect(),
+            vec!["d", "e", "f"]
+                .iter()
+                .map(|h| Value::from(*h))
+                .collect(),
         ];
         component.selected_row.select(Some(0));
         component.selection_area_corner = Some((1, 1));
@@ -757,16 +1694,22 @@ mod test {
         let mut component = TableComponent::new(KeyConfig::default());
         component.headers = vec!["1", "2", "3"].iter().map(|h| h.to_string()).collect();
         component.rows = vec![
-            vec!["a", "b", "c"].iter().map(|h| h.to_string()).collect(),
-            vec!["d", "e", "f"].iter().map(|h| h.to_string()).collect(),
+            vec!["a", "b", "c"]
+                .iter()
+                .map(|h| Value::from(*h))
+                .collect(),
+            vec!["d", "e", "f"]
+                .iter()
+                .map(|h| Value::from(*h))
+                .collect(),
         ];
         component.selected_row.select(Some(0));
         // a
-        assert!(component.is_selected_cell(0, 1, 1));
+        assert!(component.is_selected_cell(0, 1));
         // d
-        assert!(!component.is_selected_cell(1, 1, 1));
+        assert!(!component.is_selected_cell(1, 1));
         // e
-        assert!(!component.is_selected_cell(1, 2, 1));
+        assert!(!component.is_selected_cell(1, 2));
     }
 
     #[test]
@@ -778,21 +1721,27 @@ mod test {
         let mut component = TableComponent::new(KeyConfig::default());
         component.headers = vec!["1", "2", "3"].iter().map(|h| h.to_string()).collect();
         component.rows = vec![
-            vec!["a", "b", "c"].iter().map(|h| h.to_string()).collect(),
-            vec!["d", "e", "f"].iter().map(|h| h.to_string()).collect(),
+            vec!["a", "b", "c"]
+                .iter()
+                .map(|h| Value::from(*h))
+                .collect(),
+            vec!["d", "e", "f"]
+                .iter()
+                .map(|h| Value::from(*h))
+                .collect(),
         ];
         component.selected_row.select(Some(0));
         component.selection_area_corner = Some((1, 1));
         // a
-        assert!(component.is_selected_cell(0, 1, 1));
+        assert!(component.is_selected_cell(0, 1));
         // b
-        assert!(component.is_selected_cell(0, 2, 1));
+        assert!(component.is_selected_cell(0, 2));
         // d
-        assert!(component.is_selected_cell(1, 1, 1));
+        assert!(component.is_selected_cell(1, 1));
         // e
-        assert!(component.is_selected_cell(1, 2, 1));
+        assert!(component.is_selected_cell(1, 2));
         // f
-        assert!(!component.is_selected_cell(1, 3, 1));
+        assert!(!component.is_selected_cell(1, 3));
     }
 
     #[test]
@@ -802,9 +1751,12 @@ mod test {
         component.rows = vec![
             vec!["aaaaa", "bbbbb", "ccccc"]
                 .iter()
-                .map(|h| h.to_string())
+                .map(|h| Value::from(*h))
+                .collect(),
+            vec!["d", "e", "f"]
+                .iter()
+                .map(|h| Value::from(*h))
                 .collect(),
-            vec!["d", "e", "f"].iter().map(|h| h.to_string()).collect(),
         ];
         let (selected_column_index, headers, rows, constraints) =
             component.calculate_cell_widths(10);
@@ -828,9 +1780,12 @@ mod test {
         component.rows = vec![
             vec!["aaaaa", "bbbbb", "ccccc"]
                 .iter()
-                .map(|h| h.to_string())
+                .map(|h| Value::from(*h))
+                .collect(),
+            vec!["d", "e", "f"]
+                .iter()
+                .map(|h| Value::from(*h))
                 .collect(),
-            vec!["d", "e", "f"].iter().map(|h| h.to_string()).collect(),
         ];
 
         let (selected_column_index, headers, rows, constraints) =
@@ -862,11 +1817,11 @@ mod test {
         component.rows = vec![
             vec!["aaaaa", "bbbbb", "ccccc"]
                 .iter()
-                .map(|h| h.to_string())
+                .map(|h| Value::from(*h))
                 .collect(),
             vec!["dddddddddd", "e", "f"]
                 .iter()
-                .map(|h| h.to_string())
+                .map(|h| Value::from(*h))
                 .collect(),
         ];
 
@@ -891,4 +1846,236 @@ mod test {
             ]
         );
     }
+
+    #[test]
+    fn test_record_view_event() {
+        let mut component = TableComponent::new(KeyConfig::default());
+        component.headers = vec!["1", "2", "3"].iter().map(|h| h.to_string()).collect();
+        component.rows = vec![
+            vec!["a", "b", "c"]
+                .iter()
+                .map(|h| Value::from(*h))
+                .collect(),
+            vec!["d", "e", "f"]
+                .iter()
+                .map(|h| Value::from(*h))
+                .collect(),
+        ];
+        component.selected_row.select(Some(0));
+        component.record_view = true;
+        let key_config = KeyConfig::default();
+
+        assert!(component
+            .record_view_event(key_config.scroll_to_bottom)
+            .is_consumed());
+        assert_eq!(component.selected_column, 2);
+        component.record_view_event(key_config.scroll_up);
+        component.record_view_event(key_config.scroll_right);
+        assert_eq!(component.selected_cells(), Some("e".to_string()));
+        component.record_view_event(key_config.scroll_right);
+        assert_eq!(component.selected_row.selected(), Some(1));
+        assert!(component.moves_to_next_rows(key_config.scroll_right));
+        assert!(!component.moves_to_next_rows(key_config.scroll_down));
+    }
+
+    #[test]
+    fn test_sort_by() {
+        let mut component = TableComponent::new(KeyConfig::default());
+        component.headers = vec!["id", "name"].iter().map(|h| h.to_string()).collect();
+        component.rows = vec![
+            vec![Value::Int(10), Value::from("b")],
+            vec![Value::Null, Value::from("c")],
+            vec![Value::Int(9), Value::from("a")],
+        ];
+        component.selected_row.select(Some(0));
+        component.mark_changed_cell(0, 1);
+
+        let sort = component.next_sort();
+        assert_eq!(
+            sort,
+            Some(Sort {
+                column: 0,
+                descending: false
+            })
+        );
+        component.sort_by(sort);
+        assert_eq!(component.headers(0, 2), vec!["", "id ▲", "name"]);
+        assert_eq!(
+            component.rows(1, 2),
+            vec![vec!["1", "c"], vec!["2", "a"], vec!["3", "b"]]
+        );
+        // The selection and the marks follow their row.
+        assert_eq!(component.selected_row.selected(), Some(2));
+        assert!(component.changed_cells.contains(&(2, 1)));
+
+        component.sort_by(component.next_sort());
+        assert_eq!(
+            component.rows(0, 1),
+            vec![vec!["1", "10"], vec!["2", "9"], vec!["3", "NULL"]]
+        );
+
+        component.append_rows(vec![vec![Value::Int(11), Value::from("d")]]);
+        assert_eq!(component.rows[0][0], Value::Int(11));
+
+        assert_eq!(component.next_sort(), None);
+        component.sort_by(None);
+        assert_eq!(
+            component.rows(1, 2),
+            vec![
+                vec!["1", "b"],
+                vec!["2", "c"],
+                vec!["3", "a"],
+                vec!["4", "d"]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hide_and_move_column() {
+        let mut component = TableComponent::new(KeyConfig::default());
+        component.headers = vec!["1", "2", "3"].iter().map(|h| h.to_string()).collect();
+        component.rows = vec![vec!["a", "b", "c"]
+            .iter()
+            .map(|h| Value::from(*h))
+            .collect()];
+        component.selected_row.select(Some(0));
+        component.selected_column = 1;
+
+        component.hide_column();
+        assert_eq!(component.headers(0, 2), vec!["", "1", "3"]);
+        assert_eq!(component.selected_cell_position(), Some((0, 2)));
+
+        component.move_column(false);
+        assert_eq!(component.headers(0, 2), vec!["", "3", "1"]);
+        assert_eq!(component.selected_cells(), Some("c".to_string()));
+        component.expand_selected_area_x(true);
+        assert_eq!(component.selected_cells(), Some("c,a".to_string()));
+
+        component.show_all_columns();
+        assert_eq!(component.headers(0, 3), vec!["", "1", "2", "3"]);
+        assert_eq!(component.selected_cell_position(), Some((0, 2)));
+    }
+
+    #[test]
+    fn test_search_event() {
+        let mut component = TableComponent::new(KeyConfig::default());
+        component.headers = vec!["1", "2"].iter().map(|h| h.to_string()).collect();
+        component.rows = vec![
+            vec!["a", "bar"].iter().map(|h| Value::from(*h)).collect(),
+            vec!["Bob", "c"].iter().map(|h| Value::from(*h)).collect(),
+        ];
+        component.selected_row.select(Some(0));
+        component.selected_column = 1;
+        component.search.start((0, 1));
+
+        component.search_event(Key::Char('b'));
+        assert_eq!(component.selected_cell_position(), Some((0, 1)));
+        component.search_event(component.key_config.toggle_search_case);
+        component.search_event(component.key_config.enter);
+        assert!(!component.is_searching());
+        assert_eq!(
+            component.search.status(Some((0, 1))),
+            Some("match 1 of 2".to_string())
+        );
+
+        component.select_next_match(true);
+        assert_eq!(component.selected_cell_position(), Some((1, 0)));
+        component.select_next_match(true);
+        assert_eq!(component.selected_cell_position(), Some((0, 1)));
+        component.select_next_match(false);
+        assert_eq!(component.selected_cell_position(), Some((1, 0)));
+    }
+
+    #[test]
+    fn test_frozen_columns() {
+        let mut component = TableComponent::new(KeyConfig::default());
+        component.headers = vec!["id", "a", "b", "c"]
+            .iter()
+            .map(|h| h.to_string())
+            .collect();
+        component.rows = vec![vec!["1", "aaaaa", "bbbbb", "ccccc"]
+            .iter()
+            .map(|h| Value::from(*h))
+            .collect()];
+        component.selected_row.select(Some(0));
+        component.freeze_columns(&["c".to_string()]);
+        assert_eq!(component.frozen_count(), 1);
+
+        component.selected_column = 3;
+        let (selected_column_index, headers, rows, constraints) =
+            component.calculate_cell_widths(14);
+        assert_eq!(selected_column_index, 2);
+        assert_eq!(headers, vec!["", "c", "b"]);
+        assert_eq!(rows, vec![vec!["1", "ccccc", "bbbbb"]]);
+        assert_eq!(
+            constraints,
+            vec![
+                Constraint::Length(1),
+                Constraint::Length(5),
+                Constraint::Length(5),
+            ]
+        );
+        assert!(component.is_selected_cell(0, 2));
+        assert_eq!(component.value_at(0, 1), Some(&Value::from("ccccc")));
+
+        component.selected_column = 1;
+        component.toggle_frozen_columns();
+        assert_eq!(component.frozen_count(), 2);
+        component.selected_column = 0;
+        component.toggle_frozen_columns();
+        assert_eq!(component.frozen_count(), 0);
+    }
+
+    #[test]
+    fn test_resize_column() {
+        let mut component = TableComponent::new(KeyConfig::default());
+        component.headers = vec!["a", "b"].iter().map(|h| h.to_string()).collect();
+        component.rows = vec![vec!["aaaaa", "b"].iter().map(|h| Value::from(*h)).collect()];
+        component.resize_column(true);
+        assert_eq!(component.column_width(0), 7);
+        component.selected_column = 1;
+        component.resize_column(false);
+        assert_eq!(component.column_width(1), 3);
+
+        component.headers = vec!["b", "a"].iter().map(|h| h.to_string()).collect();
+        assert_eq!(component.column_width(1), 7);
+    }
+
+    #[test]
+    fn test_selection_stats() {
+        let mut component = TableComponent::new(KeyConfig::default());
+        component.headers = vec!["1", "2", "3"].iter().map(|h| h.to_string()).collect();
+        component.rows = vec![
+            vec![Value::Int(1), Value::Int(2), Value::from("a")],
+            vec![Value::Int(3), Value::Null, Value::from("b")],
+        ];
+        component.selected_row.select(Some(0));
+        assert_eq!(component.selection_stats(), None);
+
+        component.expand_selected_area_x(true);
+        component.expand_selected_area_y(true);
+        assert_eq!(
+            component.selection_stats().map(|stats| stats.to_string()),
+            Some("count: 4, non-null: 3, distinct: 3, sum: 6, min: 1, max: 3, avg: 2".to_string())
+        );
+    }
+
+    #[test]
+    fn test_columns_of() {
+        let mut component = TableComponent::new(KeyConfig::default());
+        component.headers = vec!["1", "2", "3"].iter().map(|h| h.to_string()).collect();
+        component.rows = vec![
+            vec![Value::Int(1), Value::Int(2), Value::Int(3)],
+            vec![Value::Int(4), Value::Int(5), Value::Int(6)],
+        ];
+        component.selected_column = 1;
+        component.hide_column();
+        assert_eq!(
+            component.columns_of(&component.rows[1..], 0..2),
+            (
+                vec!["1".to_string(), "3".to_string()],
+                vec![vec![Value::Int(4), Value::Int(6)]]
+            )
+        );
+    }
 }
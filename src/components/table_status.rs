@@ -12,6 +12,7 @@ use tui::{
 use database_tree::Table;
 
 use crate::components::command::CommandInfo;
+use crate::components::utils::selection_stats::SelectionStats;
 
 use super::{Component, DrawableComponent, EventState};
 
@@ -19,6 +20,8 @@ pub struct TableStatusComponent {
     column_count: Option<usize>,
     row_count: Option<usize>,
     table: Option<Table>,
+    search: Option<String>,
+    selection: Option<SelectionStats>,
 }
 
 impl Default for TableStatusComponent {
@@ -27,6 +30,8 @@ impl Default for TableStatusComponent {
             row_count: None,
             column_count: None,
             table: None,
+            search: None,
+            selection: None,
         }
     }
 }
@@ -41,13 +46,27 @@ impl TableStatusComponent {
             row_count,
             column_count,
             table,
+            search: None,
+            selection: None,
         }
     }
+
+    /// Shows the state of the search in the table, e.g. `match 2 of 5`.
+    pub fn with_search(mut self, search: Option<String>) -> Self {
+        self.search = search;
+        self
+    }
+
+    /// Shows the aggregates of the selected cells.
+    pub fn with_selection(mut self, selection: Option<SelectionStats>) -> Self {
+        self.selection = selection;
+        self
+    }
 }
 
 impl DrawableComponent for TableStatusComponent {
     fn draw<B: Backend>(&self, f: &mut Frame<B>, area: Rect, focused: bool) -> Result<()> {
-        let status = Paragraph::new(Spans::from(vec![
+        let mut spans = vec![
             Span::from(format!(
                 "rows: {}, ",
                 self.row_count.map_or("-".to_string(), |c| c.to_string())
@@ -62,12 +81,23 @@ impl DrawableComponent for TableStatusComponent {
                     c.engine.as_ref().map_or("-".to_string(), |e| e.to_string())
                 })
             )),
-        ]))
-        .block(Block::default().borders(Borders::TOP).style(if focused {
-            Style::default()
-        } else {
-            Style::default().fg(Color::DarkGray)
-        }));
+        ];
+        if let Some(selection) = &self.selection {
+            spans.push(Span::from(format!(", {}", selection)));
+        }
+        if let Some(search) = &self.search {
+            spans.push(Span::styled(
+                format!(", {}", search),
+                Style::default().fg(Color::Rgb(0xea, 0x59, 0x0b)),
+            ));
+        }
+        let status = Paragraph::new(Spans::from(spans)).block(
+            Block::default().borders(Borders::TOP).style(if focused {
+                Style::default()
+            } else {
+                Style::default().fg(Color::DarkGray)
+            }),
+        );
         f.render_widget(status, area);
         Ok(())
     }
@@ -1,35 +1,202 @@
+use std::any::Any;
+use std::cell::Cell;
+
 use super::{Component, DrawableComponent, EventState};
+use crate::app::{AppMessage, GlobalMessageQueue};
+use crate::clipboard::copy_to_clipboard;
 use crate::components::command::CommandInfo;
+use crate::config::KeyConfig;
+use crate::database::Value;
+use crate::event::Key;
+use crate::handle_message;
+use crate::ui::stateful_paragraph::{ParagraphState, ScrollPos, StatefulParagraph};
+use crate::ui::syntax_text::SyntaxText;
 use anyhow::Result;
 use async_trait::async_trait;
 use tui::{
     backend::Backend,
-    layout::Rect,
+    layout::{Constraint, Direction, Layout, Margin, Rect},
     style::{Color, Style},
-    widgets::{Block, Borders, Paragraph},
+    text::Text,
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
     Frame,
 };
 
+/// The number of bytes shown on each line of a hex dump.
+const HEX_DUMP_WIDTH: usize = 16;
+
+/// Posted by a `TableComponent` to show the full value of the selected cell.
+pub struct InspectValueRequest {
+    pub header: String,
+    pub value: Value,
+}
+
+impl AppMessage for InspectValueRequest {
+    fn as_any(&self) -> &(dyn Any + Send + Sync) {
+        self
+    }
+}
+
+/// How a value is laid out in the inspector.
+enum Content {
+    Plain(String),
+    Highlighted(SyntaxText),
+}
+
+impl Content {
+    /// JSON documents are pretty-printed and highlighted, binary data is shown as a hex dump and
+    /// anything else as its text.
+    fn new(value: &Value) -> Self {
+        let json = match value {
+            Value::Json(json) => Some(json.clone()),
+            Value::Text(text) if text.trim_start().starts_with(&['{', '['][..]) => {
+                serde_json::from_str(text).ok()
+            }
+            _ => None,
+        };
+        if let Some(json) = json.and_then(|json| serde_json::to_string_pretty(&json).ok()) {
+            return Self::Highlighted(SyntaxText::with_extension(json, "json"));
+        }
+        match value {
+            Value::Bytes(bytes) => Self::Plain(hex_dump(bytes)),
+            value => Self::Plain(value.to_string()),
+        }
+    }
+
+    fn text(&self) -> Text<'_> {
+        match self {
+            Self::Plain(text) => Text::raw(text.as_str()),
+            Self::Highlighted(text) => text.convert(),
+        }
+    }
+}
+
+/// Formats `bytes` like `hexdump -C`: the offset, 16 bytes in hex and the same bytes as ASCII.
+fn hex_dump(bytes: &[u8]) -> String {
+    bytes
+        .chunks(HEX_DUMP_WIDTH)
+        .enumerate()
+        .map(|(index, chunk)| {
+            let hex = (0..HEX_DUMP_WIDTH)
+                .map(|i| {
+                    let byte = chunk
+                        .get(i)
+                        .map_or("  ".to_string(), |b| format!("{:02x}", b));
+                    if i == HEX_DUMP_WIDTH / 2 {
+                        format!(" {}", byte)
+                    } else {
+                        byte
+                    }
+                })
+                .collect::<Vec<String>>()
+                .join(" ");
+            let ascii = chunk
+                .iter()
+                .map(|b| {
+                    if b.is_ascii_graphic() || *b == b' ' {
+                        *b as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect::<String>();
+            format!("{:08x}  {}  |{}|", index * HEX_DUMP_WIDTH, hex, ascii)
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Popup showing the full value of a cell. Long text is soft-wrapped and can be scrolled.
 pub struct TableValueComponent {
-    value: String,
+    header: String,
+    value: Value,
+    content: Content,
+    state: Cell<ParagraphState>,
+    visible: bool,
+    key_config: KeyConfig,
 }
 
 impl TableValueComponent {
-    pub fn new(value: String) -> Self {
-        Self { value }
+    pub fn new(key_config: KeyConfig) -> Self {
+        Self {
+            header: String::new(),
+            value: Value::Null,
+            content: Content::Plain(String::new()),
+            state: Cell::new(ParagraphState::default()),
+            visible: false,
+            key_config,
+        }
+    }
+
+    pub fn set(&mut self, header: String, value: Value) -> Result<()> {
+        self.content = Content::new(&value);
+        self.header = header;
+        self.value = value;
+        self.state.set(ParagraphState::default());
+        self.show()
+    }
+
+    fn title(&self) -> String {
+        match &self.value {
+            Value::Bytes(bytes) => format!("{} ({} bytes)", self.header, bytes.len()),
+            Value::Text(text) => format!("{} ({} characters)", self.header, text.chars().count()),
+            _ => self.header.clone(),
+        }
+    }
+
+    fn scroll(&mut self, lines: i32) {
+        let mut state = self.state.get();
+        let max = state.lines().saturating_sub(state.height());
+        let y = (state.scroll().y as i32 + lines).clamp(0, max as i32);
+        state.set_scroll(ScrollPos::new(0, y as u16));
+        self.state.set(state);
     }
 }
 
 impl DrawableComponent for TableValueComponent {
-    fn draw<B: Backend>(&self, f: &mut Frame<B>, area: Rect, focused: bool) -> Result<()> {
-        let paragraph = Paragraph::new(self.value.clone())
-            .block(Block::default().borders(Borders::BOTTOM))
-            .style(if focused {
-                Style::default()
-            } else {
-                Style::default().fg(Color::DarkGray)
-            });
-        f.render_widget(paragraph, area);
+    fn draw<B: Backend>(&self, f: &mut Frame<B>, _area: Rect, _focused: bool) -> Result<()> {
+        if self.visible {
+            let width = f.size().width * 4 / 5;
+            let height = f.size().height * 4 / 5;
+            let area = Rect::new(
+                (f.size().width.saturating_sub(width)) / 2,
+                (f.size().height.saturating_sub(height)) / 2,
+                width,
+                height,
+            );
+            f.render_widget(Clear, area);
+            f.render_widget(
+                Block::default().title(self.title()).borders(Borders::ALL),
+                area,
+            );
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(1), Constraint::Length(1)])
+                .split(area.inner(&Margin {
+                    vertical: 1,
+                    horizontal: 1,
+                }));
+
+            let mut state = self.state.get();
+            f.render_stateful_widget(
+                StatefulParagraph::new(self.content.text()).wrap(Wrap { trim: false }),
+                chunks[0],
+                &mut state,
+            );
+            self.state.set(state);
+
+            f.render_widget(
+                Paragraph::new(format!(
+                    "{}/{}: scroll, {}: copy, {}: close",
+                    self.key_config.scroll_down,
+                    self.key_config.scroll_up,
+                    self.key_config.copy,
+                    self.key_config.exit_popup
+                ))
+                .style(Style::default().fg(Color::DarkGray)),
+                chunks[1],
+            );
+        }
         Ok(())
     }
 }
@@ -40,9 +207,83 @@ impl Component for TableValueComponent {
 
     async fn event(
         &mut self,
-        _key: crate::event::Key,
-        _message_queue: &mut crate::app::GlobalMessageQueue,
+        key: Key,
+        _message_queue: &mut GlobalMessageQueue,
     ) -> Result<EventState> {
-        todo!("scroll");
+        if !self.visible {
+            return Ok(EventState::NotConsumed);
+        }
+        if key == self.key_config.exit_popup || key == self.key_config.inspect_value {
+            self.hide();
+        } else if key == self.key_config.copy {
+            copy_to_clipboard(&self.value.to_string())?;
+        } else if key == self.key_config.scroll_down {
+            self.scroll(1);
+        } else if key == self.key_config.scroll_up {
+            self.scroll(-1);
+        } else if key == self.key_config.scroll_down_multiple_lines {
+            self.scroll(10);
+        } else if key == self.key_config.scroll_up_multiple_lines {
+            self.scroll(-10);
+        } else if key == self.key_config.scroll_to_top {
+            self.scroll(-(self.state.get().lines() as i32));
+        } else if key == self.key_config.scroll_to_bottom {
+            self.scroll(self.state.get().lines() as i32);
+        }
+        Ok(EventState::Consumed)
+    }
+
+    async fn handle_messages(&mut self, messages: &Vec<Box<dyn AppMessage>>) -> Result<()> {
+        for m in messages.iter() {
+            handle_message!(m, InspectValueRequest,
+                request => self.set(request.header.clone(), request.value.clone())?
+            );
+        }
+        Ok(())
+    }
+
+    fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    fn hide(&mut self) {
+        self.visible = false;
+    }
+
+    fn show(&mut self) -> Result<()> {
+        self.visible = true;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{hex_dump, Content};
+    use crate::database::Value;
+
+    #[test]
+    fn test_hex_dump() {
+        assert_eq!(
+            hex_dump(b"gobang\x00\x01 database tree"),
+            "00000000  67 6f 62 61 6e 67 00 01  20 64 61 74 61 62 61 73  |gobang.. databas|\n\
+             00000010  65 20 74 72 65 65                                 |e tree|"
+        );
+    }
+
+    #[test]
+    fn test_content() {
+        assert!(matches!(
+            Content::new(&Value::Text("{\"a\": [1, 2]}".to_string())),
+            Content::Highlighted(_)
+        ));
+        assert!(matches!(
+            Content::new(&Value::Text("[not json".to_string())),
+            Content::Plain(text) if text == "[not json"
+        ));
+        assert!(matches!(
+            Content::new(&Value::Bytes(vec![0x41])),
+            Content::Plain(text) if text.ends_with("|A|")
+        ));
     }
 }
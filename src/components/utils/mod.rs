@@ -1 +1,4 @@
+pub mod query_task;
 pub mod scroll_vertical;
+pub mod selection_stats;
+pub mod table_search;
@@ -0,0 +1,124 @@
+use std::any::Any;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use log::error;
+use tokio::task::JoinHandle;
+
+use crate::app::{AppMessage, MessageSender};
+use crate::database::{Pool, QueryHandle};
+use crate::event::Key;
+
+static NEXT_TASK_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Posted to the global message queue once a `QueryTask` has finished. Components compare
+/// `task_id` with the id of the task they spawned to pick up their own results.
+pub struct QueryTaskFinished<T> {
+    pub task_id: usize,
+    result: Mutex<Option<anyhow::Result<T>>>,
+}
+
+impl<T> QueryTaskFinished<T> {
+    /// Takes the result out of the message. Returns None if it has already been taken.
+    pub fn take_result(&self) -> Option<anyhow::Result<T>> {
+        self.result.lock().ok().and_then(|mut result| result.take())
+    }
+}
+
+impl<T: Send + 'static> AppMessage for QueryTaskFinished<T> {
+    fn as_any(&self) -> &(dyn Any + Send + Sync) {
+        self
+    }
+}
+
+/// Posted to the global message queue once a cancelled `QueryTask` has ended, for queries whose
+/// outcome is unknown when cancelled, e.g. a commit which may already have been applied.
+pub struct QueryTaskCancelled {
+    pub task_id: usize,
+}
+
+impl AppMessage for QueryTaskCancelled {
+    fn as_any(&self) -> &(dyn Any + Send + Sync) {
+        self
+    }
+}
+
+/// A query running on a tokio task, so that slow queries do not block the event loop.
+pub struct QueryTask {
+    id: usize,
+    started: Instant,
+    handle: JoinHandle<()>,
+    query_handle: QueryHandle,
+    pool: Arc<dyn Pool>,
+    sender: MessageSender,
+}
+
+impl QueryTask {
+    /// Spawns `query` on a new task. Its result is posted back as a `QueryTaskFinished<T>`.
+    pub fn spawn<T, F, Fut>(pool: Arc<dyn Pool>, sender: MessageSender, query: F) -> Self
+    where
+        T: Send + 'static,
+        F: FnOnce(Arc<dyn Pool>, QueryHandle) -> Fut,
+        Fut: Future<Output = anyhow::Result<T>> + Send + 'static,
+    {
+        let id = NEXT_TASK_ID.fetch_add(1, Ordering::Relaxed);
+        let query_handle = QueryHandle::default();
+        let future = query(pool.clone(), query_handle.clone());
+        let task_sender = sender.clone();
+        let handle = tokio::spawn(async move {
+            let result = future.await;
+            task_sender.send(Box::new(QueryTaskFinished {
+                task_id: id,
+                result: Mutex::new(Some(result)),
+            }));
+        });
+        Self {
+            id,
+            started: Instant::now(),
+            handle,
+            query_handle,
+            pool,
+            sender,
+        }
+    }
+
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Progress text shown while the task is running, e.g. "Running query… 1.2s".
+    pub fn progress_message(&self, action: &str, cancel_key: Key) -> String {
+        format!(
+            "{}\u{2026} {:.1}s (press {} to cancel)",
+            action,
+            self.elapsed().as_secs_f32(),
+            cancel_key
+        )
+    }
+
+    /// Asks the server to abort the query where supported, and then drops the query future. The
+    /// future holds on to its connection until the server has been reached, so that the cancel
+    /// cannot hit another query which reused the connection. A `QueryTaskCancelled` is posted
+    /// once the task has ended.
+    pub fn cancel(self) {
+        let id = self.id;
+        let handle = self.handle;
+        let pool = self.pool;
+        let query_handle = self.query_handle;
+        let sender = self.sender;
+        tokio::spawn(async move {
+            if let Err(err) = pool.cancel_query(&query_handle).await {
+                error!("failed to cancel query: {}", err);
+            }
+            handle.abort();
+            let _ = handle.await;
+            sender.send(Box::new(QueryTaskCancelled { task_id: id }));
+        });
+    }
+}
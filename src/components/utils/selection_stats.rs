@@ -0,0 +1,132 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+
+use crate::database::Value;
+
+/// The number of decimal places the average is rounded to.
+const AVERAGE_DECIMAL_PLACES: u32 = 6;
+
+/// Aggregates of the selected cells, shown in the status bar like in a spreadsheet.
+#[derive(Debug, PartialEq)]
+pub struct SelectionStats {
+    count: usize,
+    non_null: usize,
+    distinct: usize,
+    /// Set if all the non-null values are numbers.
+    numbers: Option<NumberStats>,
+}
+
+#[derive(Debug, PartialEq)]
+struct NumberStats {
+    sum: Decimal,
+    min: Decimal,
+    max: Decimal,
+    avg: Decimal,
+}
+
+impl SelectionStats {
+    pub fn new<'a>(values: impl Iterator<Item = &'a Value>) -> Self {
+        let mut count = 0;
+        let mut non_null = 0;
+        let mut distinct = HashSet::new();
+        let mut numbers = Some(vec![]);
+        for value in values {
+            count += 1;
+            if value.is_null() {
+                continue;
+            }
+            non_null += 1;
+            distinct.insert(value.to_string());
+            numbers = numbers.and_then(|mut numbers| {
+                numbers.push(to_decimal(value)?);
+                Some(numbers)
+            });
+        }
+        Self {
+            count,
+            non_null,
+            distinct: distinct.len(),
+            numbers: numbers.and_then(|numbers| NumberStats::new(&numbers)),
+        }
+    }
+}
+
+impl NumberStats {
+    fn new(numbers: &[Decimal]) -> Option<Self> {
+        let sum = numbers
+            .iter()
+            .try_fold(Decimal::ZERO, |sum, number| sum.checked_add(*number))?;
+        Some(Self {
+            sum,
+            min: *numbers.iter().min()?,
+            max: *numbers.iter().max()?,
+            avg: (sum / Decimal::from(numbers.len()))
+                .round_dp(AVERAGE_DECIMAL_PLACES)
+                .normalize(),
+        })
+    }
+}
+
+/// Converts a number to a decimal, so that sums of decimals are exact.
+fn to_decimal(value: &Value) -> Option<Decimal> {
+    match value {
+        Value::Int(value) => Some(Decimal::from(*value)),
+        Value::Float(value) => Decimal::from_f64(*value),
+        Value::Decimal(value) => Some(*value),
+        _ => None,
+    }
+}
+
+impl fmt::Display for SelectionStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "count: {}, non-null: {}, distinct: {}",
+            self.count, self.non_null, self.distinct
+        )?;
+        if let Some(numbers) = &self.numbers {
+            write!(
+                f,
+                ", sum: {}, min: {}, max: {}, avg: {}",
+                numbers.sum, numbers.min, numbers.max, numbers.avg
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SelectionStats;
+    use crate::database::Value;
+    use rust_decimal::Decimal;
+
+    #[test]
+    fn test_selection_stats() {
+        let values = vec![
+            Value::Int(1),
+            Value::Null,
+            Value::Decimal(Decimal::new(25, 1)),
+            Value::Int(1),
+        ];
+        assert_eq!(
+            SelectionStats::new(values.iter()).to_string(),
+            "count: 4, non-null: 3, distinct: 2, sum: 4.5, min: 1, max: 2.5, avg: 1.5"
+        );
+
+        let values = vec![Value::Int(1), Value::from("a"), Value::Null];
+        assert_eq!(
+            SelectionStats::new(values.iter()).to_string(),
+            "count: 3, non-null: 2, distinct: 2"
+        );
+
+        let values = vec![Value::Null, Value::Null];
+        assert_eq!(
+            SelectionStats::new(values.iter()).to_string(),
+            "count: 2, non-null: 0, distinct: 0"
+        );
+    }
+}
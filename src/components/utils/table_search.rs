@@ -0,0 +1,230 @@
+use regex::{Regex, RegexBuilder};
+
+use crate::database::Value;
+
+/// Incremental search through the cells of a table. Matches are kept as `(row, position)`, the
+/// position being the index of the column among the shown columns, in the order they are shown.
+#[derive(Default)]
+pub struct TableSearch {
+    query: String,
+    /// Set while the query is being typed.
+    editing: bool,
+    regex: bool,
+    ignore_case: bool,
+    pattern: Option<Regex>,
+    /// Set if the query is not a valid regular expression.
+    invalid: bool,
+    /// The cell selected when the search was started, where the first match is looked for.
+    origin: (usize, usize),
+    matches: Vec<(usize, usize)>,
+}
+
+impl TableSearch {
+    /// Starts typing a new query, looking for matches from `origin` onwards.
+    pub fn start(&mut self, origin: (usize, usize)) {
+        self.query.clear();
+        self.pattern = None;
+        self.invalid = false;
+        self.matches.clear();
+        self.origin = origin;
+        self.editing = true;
+    }
+
+    /// Stops typing, keeping the matches highlighted.
+    pub fn finish(&mut self) {
+        self.editing = false;
+    }
+
+    /// Stops the search and removes the highlights.
+    pub fn clear(&mut self) {
+        *self = Self {
+            regex: self.regex,
+            ignore_case: self.ignore_case,
+            ..Self::default()
+        };
+    }
+
+    pub fn is_editing(&self) -> bool {
+        self.editing
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.pattern.is_some()
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.query.push(c);
+        self.compile();
+    }
+
+    pub fn pop(&mut self) {
+        self.query.pop();
+        self.compile();
+    }
+
+    pub fn toggle_regex(&mut self) {
+        self.regex = !self.regex;
+        self.compile();
+    }
+
+    pub fn toggle_ignore_case(&mut self) {
+        self.ignore_case = !self.ignore_case;
+        self.compile();
+    }
+
+    /// Describes the search modes, e.g. `regex, ignore case`.
+    pub fn modes(&self) -> String {
+        [
+            if self.regex { "regex" } else { "text" },
+            if self.ignore_case {
+                "ignore case"
+            } else {
+                "match case"
+            },
+        ]
+        .join(", ")
+    }
+
+    fn compile(&mut self) {
+        self.invalid = false;
+        self.pattern = if self.query.is_empty() {
+            None
+        } else {
+            let pattern = if self.regex {
+                self.query.clone()
+            } else {
+                regex::escape(&self.query)
+            };
+            match RegexBuilder::new(&pattern)
+                .case_insensitive(self.ignore_case)
+                .build()
+            {
+                Ok(pattern) => Some(pattern),
+                Err(_) => {
+                    self.invalid = true;
+                    None
+                }
+            }
+        };
+    }
+
+    pub fn is_match(&self, value: &Value) -> bool {
+        matches!(&self.pattern, Some(pattern) if pattern.is_match(&value.to_string()))
+    }
+
+    /// Looks for the matching cells of `rows`, in the shown `columns`.
+    pub fn find(&mut self, rows: &[Vec<Value>], columns: &[usize]) {
+        self.matches.clear();
+        if self.pattern.is_none() {
+            return;
+        }
+        for (row_index, row) in rows.iter().enumerate() {
+            for (position, column) in columns.iter().enumerate() {
+                if matches!(row.get(*column), Some(value) if self.is_match(value)) {
+                    self.matches.push((row_index, position));
+                }
+            }
+        }
+    }
+
+    /// Returns the first match at or after the cell the search was started from.
+    pub fn first_match(&self) -> Option<(usize, usize)> {
+        self.matches
+            .iter()
+            .find(|cell| **cell >= self.origin)
+            .or_else(|| self.matches.first())
+            .copied()
+    }
+
+    /// Returns the match following `cell`, or preceding it, wrapping around at the ends.
+    pub fn next_match(&self, cell: (usize, usize), forward: bool) -> Option<(usize, usize)> {
+        if forward {
+            self.matches
+                .iter()
+                .find(|m| **m > cell)
+                .or_else(|| self.matches.first())
+                .copied()
+        } else {
+            self.matches
+                .iter()
+                .rev()
+                .find(|m| **m < cell)
+                .or_else(|| self.matches.last())
+                .copied()
+        }
+    }
+
+    /// Describes the matches, e.g. `match 2 of 5` if `selected` is one of them.
+    pub fn status(&self, selected: Option<(usize, usize)>) -> Option<String> {
+        if self.invalid {
+            return Some("invalid regex".to_string());
+        }
+        self.pattern.as_ref()?;
+        Some(
+            match selected.and_then(|cell| self.matches.iter().position(|m| *m == cell)) {
+                Some(index) => format!("match {} of {}", index + 1, self.matches.len()),
+                None if self.matches.is_empty() => "no matches".to_string(),
+                None => format!("{} matches", self.matches.len()),
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::TableSearch;
+    use crate::database::Value;
+
+    fn rows() -> Vec<Vec<Value>> {
+        vec![
+            vec![Value::from("Apple"), Value::Int(12)],
+            vec![Value::from("pear"), Value::Null],
+            vec![Value::from("apricot"), Value::Int(21)],
+        ]
+    }
+
+    fn search_for(query: &str) -> TableSearch {
+        let mut search = TableSearch::default();
+        search.start((1, 0));
+        query.chars().for_each(|c| search.push(c));
+        search.find(&rows(), &[0, 1]);
+        search
+    }
+
+    #[test]
+    fn test_find() {
+        let mut search = search_for("ap");
+        assert_eq!(search.matches, vec![(2, 0)]);
+        search.toggle_ignore_case();
+        search.find(&rows(), &[0, 1]);
+        assert_eq!(search.matches, vec![(0, 0), (2, 0)]);
+
+        let mut search = search_for("\\d");
+        assert!(search.matches.is_empty());
+        search.toggle_regex();
+        search.find(&rows(), &[1]);
+        assert_eq!(search.matches, vec![(0, 0), (2, 0)]);
+
+        search.push('(');
+        assert_eq!(search.status(None), Some("invalid regex".to_string()));
+    }
+
+    #[test]
+    fn test_next_match() {
+        let search = search_for("p");
+        assert_eq!(search.matches, vec![(0, 0), (1, 0), (2, 0)]);
+        assert_eq!(search.first_match(), Some((1, 0)));
+        assert_eq!(search.next_match((2, 0), true), Some((0, 0)));
+        assert_eq!(search.next_match((0, 0), false), Some((2, 0)));
+        assert_eq!(search.next_match((1, 1), false), Some((1, 0)));
+        assert_eq!(
+            search.status(Some((1, 0))),
+            Some("match 2 of 3".to_string())
+        );
+        assert_eq!(search.status(Some((1, 1))), Some("3 matches".to_string()));
+    }
+}
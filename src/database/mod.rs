@@ -1,3 +1,5 @@
+use std::sync::{Arc, Mutex};
+
 use async_trait::async_trait;
 use sqlx::mysql::MySqlRow;
 use sqlx::postgres::PgRow;
@@ -6,35 +8,93 @@ use sqlx::Row;
 use sqlx::TypeInfo as _;
 
 use database_tree::{Child, Database, Table};
+
+use crate::config::Connection;
+
 pub use mysql::MySqlPool;
 pub use postgres::PostgresPool;
 pub use sqlite::SqlitePool;
+pub use statement::{Dialect, OrderBy, RecordsRequest};
+pub use value::Value;
 
 pub mod mysql;
 pub mod postgres;
 pub mod sqlite;
+pub mod statement;
+pub mod value;
+
+/// Opens a pool for the given connection from the config file.
+pub async fn connect(connection: &Connection) -> anyhow::Result<Arc<dyn Pool>> {
+    let database_url = connection.database_url()?;
+    Ok(if connection.is_mysql() {
+        Arc::new(MySqlPool::new(&database_url).await?)
+    } else if connection.is_postgres() {
+        Arc::new(PostgresPool::new(&database_url).await?)
+    } else {
+        Arc::new(SqlitePool::new(&database_url).await?)
+    })
+}
 
 pub const RECORDS_LIMIT_PER_PAGE: u8 = 200;
 
+/// Returns the number of rows to skip in order to fetch the given (zero based) page of records.
+pub fn page_offset(page: u16) -> u64 {
+    page as u64 * RECORDS_LIMIT_PER_PAGE as u64
+}
+
+/// Identifies the server session a query is running on, so that the query can be cancelled from
+/// another connection while it is still in flight.
+#[derive(Clone, Default)]
+pub struct QueryHandle(Arc<Mutex<Option<u64>>>);
+
+impl QueryHandle {
+    pub fn set_connection_id(&self, id: u64) {
+        if let Ok(mut connection_id) = self.0.lock() {
+            *connection_id = Some(id);
+        }
+    }
+
+    pub fn connection_id(&self) -> Option<u64> {
+        self.0.lock().ok().and_then(|id| *id)
+    }
+}
+
 #[async_trait]
 pub trait Pool: Send + Sync {
-    async fn execute(&self, query: &String) -> anyhow::Result<ExecuteResult>;
+    /// Executes `query`, which may hold several statements, and returns the result set of each
+    /// statement, or each result set of statements returning several, e.g. stored procedures.
+    async fn execute(&self, query: &String) -> anyhow::Result<Vec<ExecuteResult>>;
+    /// Starts a session on a connection of its own, so that statements executed one after the
+    /// other share transactions and session state. The id of the connection is recorded in
+    /// `handle` so that it may later be passed to `cancel_query`.
+    async fn session(&self, handle: &QueryHandle) -> anyhow::Result<Box<dyn PoolSession>>;
+    /// Asks the server to abort the query running on the connection recorded in `handle`.
+    /// Backends without server side cancellation rely on the caller dropping the query future.
+    async fn cancel_query(&self, _handle: &QueryHandle) -> anyhow::Result<()> {
+        Ok(())
+    }
     async fn get_databases(&self) -> anyhow::Result<Vec<Database>>;
     // TODO: Change argument to &String
     async fn get_tables(&self, database: String) -> anyhow::Result<Vec<Child>>;
+    /// Fetches the records of `table` described by `request`.
     async fn get_records(
         &self,
         database: &Database,
         table: &Table,
-        page: u16,
-        filter: Option<String>,
-    ) -> anyhow::Result<(Vec<String>, Vec<Vec<String>>)>;
+        request: &RecordsRequest,
+    ) -> anyhow::Result<(Vec<String>, Vec<Vec<Value>>)>;
     async fn get_columns(&self, table: &Table) -> anyhow::Result<Vec<Column>>;
     async fn get_constraints(
         &self,
         database: &Database,
         table: &Table,
     ) -> anyhow::Result<Vec<Box<dyn TableRow>>>;
+    /// Returns the names of the primary key columns of `table`, in key order.
+    async fn get_primary_keys(
+        &self,
+        database: &Database,
+        table: &Table,
+    ) -> anyhow::Result<Vec<String>>;
     async fn get_foreign_keys(
         &self,
         database: &Database,
@@ -45,7 +105,24 @@ pub trait Pool: Send + Sync {
         database: &Database,
         table: &Table,
     ) -> anyhow::Result<Vec<Box<dyn TableRow>>>;
+    /// Returns the foreign keys of the tables of `database` which reference `table`.
+    async fn get_references(
+        &self,
+        database: &Database,
+        table: &Table,
+    ) -> anyhow::Result<Vec<Reference>>;
+    /// Counts the records of `table` matching the SQL expression `filter`.
+    async fn count_records(
+        &self,
+        database: &Database,
+        table: &Table,
+        filter: &str,
+    ) -> anyhow::Result<i64>;
+    /// Starts a transaction on a connection held until it is committed or rolled back. The id of
+    /// the connection is recorded in `handle`, as for `session`.
+    async fn begin(&self, handle: &QueryHandle) -> anyhow::Result<Box<dyn PoolTransaction>>;
     async fn close(&self);
+    fn dialect(&self) -> Dialect;
 
     async fn get_keywords(&self) -> anyhow::Result<Vec<String>> {
         Ok(vec![
@@ -58,10 +135,28 @@ pub trait Pool: Send + Sync {
     }
 }
 
+/// A session started with `Pool::session`. Its connection is not shared with other queries.
+#[async_trait]
+pub trait PoolSession: Send {
+    async fn execute(&mut self, query: &str) -> anyhow::Result<Vec<ExecuteResult>>;
+    /// Ends the session, rolling back the transaction left open by its statements, if any.
+    async fn close(self: Box<Self>) -> anyhow::Result<()>;
+}
+
+/// A transaction started with `Pool::begin`. It is rolled back if dropped without being
+/// committed.
+#[async_trait]
+pub trait PoolTransaction: Send {
+    /// Executes `query` inside the transaction and returns the number of affected rows.
+    async fn execute(&mut self, query: &str) -> anyhow::Result<u64>;
+    async fn commit(self: Box<Self>) -> anyhow::Result<()>;
+    async fn rollback(self: Box<Self>) -> anyhow::Result<()>;
+}
+
 pub enum ExecuteResult {
     Read {
         headers: Vec<String>,
-        rows: Vec<Vec<String>>,
+        rows: Vec<Vec<Value>>,
         database: Database,
         table: Table,
     },
@@ -69,6 +164,29 @@ pub enum ExecuteResult {
         updated_rows: u64,
     },
 }
+
+impl ExecuteResult {
+    /// The rows returned by a query, which are not bound to a table.
+    pub fn read(headers: Vec<String>, rows: Vec<Vec<Value>>) -> Self {
+        Self::Read {
+            headers,
+            rows,
+            database: Database {
+                name: "-".to_string(),
+                children: Vec::new(),
+            },
+            table: Table {
+                name: "-".to_string(),
+                create_time: None,
+                update_time: None,
+                engine: None,
+                schema: None,
+                database: None,
+            },
+        }
+    }
+}
+
 pub trait TableRow: std::marker::Send {
     fn fields(&self) -> Vec<String>;
     fn columns(&self) -> Vec<String>;
@@ -179,6 +297,37 @@ impl TableRow for ForeignKey {
     }
 }
 
+/// A foreign key of `table` referencing the table it was looked up for.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Reference {
+    pub name: String,
+    pub schema: Option<String>,
+    pub table: String,
+    /// The referencing columns, each paired with the column it references, in key order.
+    pub columns: Vec<(String, String)>,
+}
+
+impl Reference {
+    /// Merges the consecutive references of the same foreign key, as listed one column per row
+    /// by the backends.
+    fn group(references: Vec<Reference>) -> Vec<Reference> {
+        let mut grouped: Vec<Reference> = vec![];
+        for reference in references {
+            match grouped.last_mut() {
+                Some(last)
+                    if last.name == reference.name
+                        && last.schema == reference.schema
+                        && last.table == reference.table =>
+                {
+                    last.columns.extend(reference.columns)
+                }
+                _ => grouped.push(reference),
+            }
+        }
+        grouped
+    }
+}
+
 pub struct Constraint {
     name: String,
     column_name: String,
@@ -209,24 +358,42 @@ macro_rules! pool_exec_impl {
         use log::debug;
         let query = $query.trim();
         debug!("Executing query {}", query);
-        let mut result_sets = sqlx::query(query).fetch_many($pool);
+        // Queries without arguments go through the text protocol, so values of any type arrive in
+        // a form which can at least be shown as text.
+        // The columns of a result set are otherwise only known from its rows, so the first
+        // statement is described up front to tell an empty result set from no result set. Scripts
+        // of several statements may not be described, and then rely on the rows alone.
+        let mut described = sqlx::Executor::describe($pool, query)
+            .await
+            .map(|describe| {
+                describe
+                    .columns()
+                    .iter()
+                    .map(|column| column.name().to_string())
+                    .collect::<Vec<String>>()
+            })
+            .unwrap_or_default();
+        let mut result_sets = sqlx::Executor::fetch_many($pool, query);
+        let mut results = vec![];
         let mut headers = vec![];
         let mut records = vec![];
 
+        // Each result set is followed by a query result, which ends it
         while let Some(r) = result_sets.try_next().await? {
-            debug!(
-                "Query result is {}",
-                if r.is_left() {
-                    "write operation"
+            if r.is_left() {
+                if headers.is_empty() && results.is_empty() {
+                    headers = std::mem::take(&mut described);
+                }
+                if headers.is_empty() {
+                    results.push(ExecuteResult::Write {
+                        updated_rows: r.left().unwrap().rows_affected(),
+                    });
                 } else {
-                    "row"
+                    results.push(ExecuteResult::read(
+                        std::mem::take(&mut headers),
+                        std::mem::take(&mut records),
+                    ));
                 }
-            );
-            if r.is_left() && records.is_empty() {
-                debug!("Returning ExecuteResult::Write");
-                return Ok(ExecuteResult::Write {
-                    updated_rows: r.left().unwrap().rows_affected(),
-                });
             } else if let Some(row) = r.right() {
                 if headers.is_empty() {
                     headers = row
@@ -237,40 +404,64 @@ macro_rules! pool_exec_impl {
                 }
                 let mut new_row = vec![];
                 for column in row.columns() {
-                    new_row.push(convert_column_val_to_str(&row, column)?)
+                    new_row.push(convert_column_value(&row, column)?)
                 }
                 records.push(new_row)
             }
         }
-        debug!("Returning ExecuteResult::Read");
-        return Ok(ExecuteResult::Read {
-            headers,
-            rows: records,
-            database: Database {
-                name: "-".to_string(),
-                children: Vec::new(),
-            },
-            table: Table {
-                name: "-".to_string(),
-                create_time: None,
-                update_time: None,
-                engine: None,
-                schema: None,
-                database: None,
-            },
-        });
+        if !headers.is_empty() {
+            results.push(ExecuteResult::read(headers, records));
+        }
+        debug!("Returning {} result sets", results.len());
+        return Ok(results);
     };
 }
 
-// #[macro_export]
-// macro_rules! get_or_null {
-//     ($value:expr) => {
-//         $value.map_or("NULL".to_string(), |v| v.to_string())
-//     };
-// }
-#[inline(always)]
-fn get_or_null<T: ToString>(val: Option<T>) -> String {
-    val.map_or("NULL".to_string(), |v| v.to_string())
+/// Implements `PoolSession` on a connection opened for the session alone. Closing the connection
+/// ends the session state and rolls back an open transaction.
+#[macro_export]
+macro_rules! pool_session_impl {
+    ($name : ident, $connection : ty) => {
+        pub struct $name($connection);
+
+        #[async_trait]
+        impl $crate::database::PoolSession for $name {
+            async fn execute(&mut self, query: &str) -> anyhow::Result<Vec<ExecuteResult>> {
+                pool_exec_impl!(&mut self.0, query);
+            }
+
+            async fn close(self: Box<Self>) -> anyhow::Result<()> {
+                sqlx::Connection::close(self.0).await?;
+                Ok(())
+            }
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! pool_transaction_impl {
+    ($name : ident, $database : ty) => {
+        pub struct $name(sqlx::Transaction<'static, $database>);
+
+        #[async_trait]
+        impl $crate::database::PoolTransaction for $name {
+            async fn execute(&mut self, query: &str) -> anyhow::Result<u64> {
+                log::debug!("Executing query {} in transaction", query.trim());
+                let result = sqlx::query(query.trim()).execute(&mut self.0).await?;
+                Ok(result.rows_affected())
+            }
+
+            async fn commit(self: Box<Self>) -> anyhow::Result<()> {
+                self.0.commit().await?;
+                Ok(())
+            }
+
+            async fn rollback(self: Box<Self>) -> anyhow::Result<()> {
+                self.0.rollback().await?;
+                Ok(())
+            }
+        }
+    };
 }
 
 macro_rules! convert_column {
@@ -278,12 +469,26 @@ macro_rules! convert_column {
         $(
         if let Ok(value) = $row.try_get($column_name) {
             let value : Option<$typ> = value;
-            return Ok(get_or_null(value))
+            return Ok(Value::from(value))
         }
         )+
     };
 }
 
+/// Falls back to the text form of the value, or to its raw bytes if it is not valid UTF-8.
+macro_rules! convert_column_to_text {
+    ($row : expr, $column_name : expr) => {
+        if let Ok(value) = $row.try_get_unchecked($column_name) {
+            let value: Option<&str> = value;
+            return Ok(value.map_or(Value::Null, |value| Value::Unknown(value.to_string())));
+        }
+        if let Ok(value) = $row.try_get_unchecked($column_name) {
+            let value: Option<&[u8]> = value;
+            return Ok(Value::from(value));
+        }
+    };
+}
+
 macro_rules! convert_column_to_common_types {
     ($row : expr, $column_name : expr) => {
         convert_column!(
@@ -309,38 +514,50 @@ macro_rules! convert_column_to_common_types {
     };
 }
 
-pub fn convert_column_val_to_str<R: sqlx::Row + std::any::Any, C: sqlx::Column>(
+/// Decodes the value of `column` in `row`, trying the types each backend is able to return.
+pub fn convert_column_value<R: sqlx::Row + std::any::Any, C: sqlx::Column>(
     row: &R,
     column: &C,
-) -> anyhow::Result<String> {
+) -> anyhow::Result<Value> {
     let row: &dyn std::any::Any = row;
     let column_name = column.name();
     if let Some(row) = row.downcast_ref::<MySqlRow>() {
         convert_column_to_common_types!(row, column_name);
-        convert_column!(row, column_name, rust_decimal::Decimal, u16, u64);
-        // convert_column(row, column_name, u64);
+        convert_column!(
+            row,
+            column_name,
+            rust_decimal::Decimal,
+            u8,
+            u16,
+            u64,
+            Vec<u8>
+        );
+        convert_column_to_text!(row, column_name);
     } else if let Some(row) = row.downcast_ref::<SqliteRow>() {
         convert_column_to_common_types!(row, column_name);
-        convert_column!(row, column_name, u16);
+        convert_column!(row, column_name, u16, Vec<u8>);
+        convert_column_to_text!(row, column_name);
     } else if let Some(row) = row.downcast_ref::<PgRow>() {
-        convert_column_to_common_types!(row, column_name);
-        convert_column!(row, column_name, rust_decimal::Decimal);
-        if let Ok(value) = row.try_get(column_name) {
-            let value: Option<&[u8]> = value;
-            return Ok(value.map_or("NULL".to_string(), |values| {
-                format!(
-                    "\\x{}",
-                    values
-                        .iter()
-                        .map(|v| format!("{:02x}", v))
-                        .collect::<String>()
-                )
-            }));
-        }
-        if let Ok(value) = row.try_get(column_name) {
-            let value: Option<Vec<String>> = value;
-            return Ok(value.map_or("NULL".to_string(), |v| v.join(",")));
+        if column.type_info().name() == "UUID" {
+            let value: Option<&str> = row.try_get_unchecked(column_name)?;
+            return Ok(value.map_or(Value::Null, |value| Value::Uuid(value.to_string())));
         }
+        convert_column_to_common_types!(row, column_name);
+        convert_column!(
+            row,
+            column_name,
+            rust_decimal::Decimal,
+            Vec<u8>,
+            Vec<String>,
+            Vec<i16>,
+            Vec<i32>,
+            Vec<i64>,
+            Vec<f32>,
+            Vec<f64>,
+            Vec<bool>,
+            Vec<rust_decimal::Decimal>
+        );
+        convert_column_to_text!(row, column_name);
     }
     anyhow::bail!(
         "column type not implemented: `{}` {}",
@@ -348,3 +565,20 @@ pub fn convert_column_val_to_str<R: sqlx::Row + std::any::Any, C: sqlx::Column>(
         column.type_info().clone().name()
     )
 }
+
+/// Returns an empty database holding no tables, together with a table called `name` in it, for
+/// tests generating statements.
+#[cfg(test)]
+pub fn test_table(name: &str) -> (Database, Table) {
+    (
+        Database::new("shop".to_string(), vec![]),
+        Table {
+            name: name.to_string(),
+            create_time: None,
+            update_time: None,
+            engine: None,
+            schema: None,
+            database: None,
+        },
+    )
+}
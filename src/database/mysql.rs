@@ -4,17 +4,21 @@ use async_trait::async_trait;
 
 use futures::TryStreamExt;
 use sqlx::mysql::MySqlPoolOptions;
-use sqlx::{Column as _, Row as _};
+use sqlx::{Column as _, Connection as _, Executor as _, Row as _};
 
 use database_tree::{Child, Database, Table};
 
-use crate::database::{convert_column_val_to_str, Column, Constraint, ForeignKey, Index};
-use crate::pool_exec_impl;
+use crate::database::{
+    convert_column_value, Column, Constraint, ForeignKey, Index, RecordsRequest, Reference, Value,
+};
+use crate::{pool_exec_impl, pool_session_impl, pool_transaction_impl};
 
-use super::{ExecuteResult, Pool, TableRow, RECORDS_LIMIT_PER_PAGE};
+use super::{Dialect, ExecuteResult, Pool, PoolSession, PoolTransaction, QueryHandle, TableRow};
 
 pub struct MySqlPool {
     pool: sqlx::mysql::MySqlPool,
+    /// Sessions open connections of their own to this URL.
+    database_url: String,
 }
 
 impl MySqlPool {
@@ -24,16 +28,38 @@ impl MySqlPool {
                 .connect_timeout(Duration::from_secs(5))
                 .connect(database_url)
                 .await?,
+            database_url: database_url.to_string(),
         })
     }
 }
 
+pool_session_impl!(MySqlSession, sqlx::MySqlConnection);
+pool_transaction_impl!(MySqlTransaction, sqlx::MySql);
+
 #[async_trait]
 impl Pool for MySqlPool {
-    async fn execute(&self, query: &String) -> anyhow::Result<ExecuteResult> {
+    async fn execute(&self, query: &String) -> anyhow::Result<Vec<ExecuteResult>> {
         pool_exec_impl!(&self.pool, query);
     }
 
+    async fn session(&self, handle: &QueryHandle) -> anyhow::Result<Box<dyn PoolSession>> {
+        let mut conn = sqlx::MySqlConnection::connect(&self.database_url).await?;
+        let connection_id: u64 = sqlx::query_scalar("SELECT CONNECTION_ID()")
+            .fetch_one(&mut conn)
+            .await?;
+        handle.set_connection_id(connection_id);
+        Ok(Box::new(MySqlSession(conn)))
+    }
+
+    async fn cancel_query(&self, handle: &QueryHandle) -> anyhow::Result<()> {
+        if let Some(connection_id) = handle.connection_id() {
+            sqlx::query(format!("KILL QUERY {}", connection_id).as_str())
+                .execute(&self.pool)
+                .await?;
+        }
+        Ok(())
+    }
+
     async fn get_databases(&self) -> anyhow::Result<Vec<Database>> {
         let databases = sqlx::query("SHOW DATABASES")
             .fetch_all(&self.pool)
@@ -72,28 +98,10 @@ impl Pool for MySqlPool {
         &self,
         database: &Database,
         table: &Table,
-        page: u16,
-        filter: Option<String>,
-    ) -> anyhow::Result<(Vec<String>, Vec<Vec<String>>)> {
-        let query = if let Some(filter) = filter {
-            format!(
-                "SELECT * FROM `{database}`.`{table}` WHERE {filter} LIMIT {page}, {limit}",
-                database = database.name,
-                table = table.name,
-                filter = filter,
-                page = page,
-                limit = RECORDS_LIMIT_PER_PAGE
-            )
-        } else {
-            format!(
-                "SELECT * FROM `{}`.`{}` LIMIT {page}, {limit}",
-                database.name,
-                table.name,
-                page = page,
-                limit = RECORDS_LIMIT_PER_PAGE
-            )
-        };
-        let mut rows = sqlx::query(query.as_str()).fetch(&self.pool);
+        request: &RecordsRequest,
+    ) -> anyhow::Result<(Vec<String>, Vec<Vec<Value>>)> {
+        let query = self.dialect().select(database, table, request);
+        let mut rows = self.pool.fetch(query.as_str());
         let mut headers = vec![];
         let mut records = vec![];
         while let Some(row) = rows.try_next().await? {
@@ -104,7 +112,7 @@ impl Pool for MySqlPool {
                 .collect();
             let mut new_row = vec![];
             for column in row.columns() {
-                new_row.push(convert_column_val_to_str(&row, column)?)
+                new_row.push(convert_column_value(&row, column)?)
             }
             records.push(new_row)
         }
@@ -167,6 +175,32 @@ impl Pool for MySqlPool {
         Ok(constraints)
     }
 
+    async fn get_primary_keys(
+        &self,
+        database: &Database,
+        table: &Table,
+    ) -> anyhow::Result<Vec<String>> {
+        let keys = sqlx::query_scalar(
+            "
+        SELECT
+            COLUMN_NAME
+        FROM
+            information_schema.KEY_COLUMN_USAGE
+        WHERE
+            CONSTRAINT_NAME = 'PRIMARY'
+            AND TABLE_SCHEMA = ?
+            AND TABLE_NAME = ?
+        ORDER BY
+            ORDINAL_POSITION
+        ",
+        )
+        .bind(&database.name)
+        .bind(&table.name)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(keys)
+    }
+
     async fn get_foreign_keys(
         &self,
         database: &Database,
@@ -205,6 +239,59 @@ impl Pool for MySqlPool {
         Ok(foreign_keys)
     }
 
+    async fn get_references(
+        &self,
+        database: &Database,
+        table: &Table,
+    ) -> anyhow::Result<Vec<Reference>> {
+        let mut rows = sqlx::query(
+            "
+        SELECT
+            CONSTRAINT_NAME,
+            TABLE_NAME,
+            COLUMN_NAME,
+            REFERENCED_COLUMN_NAME
+        FROM
+            INFORMATION_SCHEMA.KEY_COLUMN_USAGE
+        WHERE
+            TABLE_SCHEMA = ?
+            AND REFERENCED_TABLE_SCHEMA = ?
+            AND REFERENCED_TABLE_NAME = ?
+        ORDER BY
+            TABLE_NAME,
+            CONSTRAINT_NAME,
+            ORDINAL_POSITION
+        ",
+        )
+        .bind(&database.name)
+        .bind(&database.name)
+        .bind(&table.name)
+        .fetch(&self.pool);
+        let mut references = vec![];
+        while let Some(row) = rows.try_next().await? {
+            references.push(Reference {
+                name: row.try_get("CONSTRAINT_NAME")?,
+                schema: None,
+                table: row.try_get("TABLE_NAME")?,
+                columns: vec![(
+                    row.try_get("COLUMN_NAME")?,
+                    row.try_get("REFERENCED_COLUMN_NAME")?,
+                )],
+            })
+        }
+        Ok(Reference::group(references))
+    }
+
+    async fn count_records(
+        &self,
+        database: &Database,
+        table: &Table,
+        filter: &str,
+    ) -> anyhow::Result<i64> {
+        let query = self.dialect().count(database, table, filter);
+        Ok(sqlx::query_scalar(&query).fetch_one(&self.pool).await?)
+    }
+
     async fn get_indexes(
         &self,
         database: &Database,
@@ -238,7 +325,20 @@ impl Pool for MySqlPool {
         Ok(foreign_keys)
     }
 
+    async fn begin(&self, handle: &QueryHandle) -> anyhow::Result<Box<dyn PoolTransaction>> {
+        let mut transaction = self.pool.begin().await?;
+        let connection_id: u64 = sqlx::query_scalar("SELECT CONNECTION_ID()")
+            .fetch_one(&mut transaction)
+            .await?;
+        handle.set_connection_id(connection_id);
+        Ok(Box::new(MySqlTransaction(transaction)))
+    }
+
     async fn close(&self) {
         self.pool.close().await;
     }
+
+    fn dialect(&self) -> Dialect {
+        Dialect::MySql
+    }
 }
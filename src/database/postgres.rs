@@ -5,17 +5,21 @@ use async_trait::async_trait;
 use futures::TryStreamExt;
 use itertools::Itertools;
 use sqlx::postgres::{PgPool, PgPoolOptions};
-use sqlx::{Column as _, Row as _};
+use sqlx::{Column as _, Connection as _, Executor as _, Row as _};
 
 use database_tree::{Child, Database, Schema, Table};
 
-use crate::database::{convert_column_val_to_str, Column, Constraint, ForeignKey, Index};
-use crate::pool_exec_impl;
+use crate::database::{
+    convert_column_value, Column, Constraint, ForeignKey, Index, RecordsRequest, Reference, Value,
+};
+use crate::{pool_exec_impl, pool_session_impl, pool_transaction_impl};
 
-use super::{ExecuteResult, Pool, TableRow, RECORDS_LIMIT_PER_PAGE};
+use super::{Dialect, ExecuteResult, Pool, PoolSession, PoolTransaction, QueryHandle, TableRow};
 
 pub struct PostgresPool {
     pool: PgPool,
+    /// Sessions open connections of their own to this URL.
+    database_url: String,
 }
 
 impl PostgresPool {
@@ -25,16 +29,39 @@ impl PostgresPool {
                 .connect_timeout(Duration::from_secs(5))
                 .connect(database_url)
                 .await?,
+            database_url: database_url.to_string(),
         })
     }
 }
 
+pool_session_impl!(PostgresSession, sqlx::PgConnection);
+pool_transaction_impl!(PostgresTransaction, sqlx::Postgres);
+
 #[async_trait]
 impl Pool for PostgresPool {
-    async fn execute(&self, query: &String) -> anyhow::Result<ExecuteResult> {
+    async fn execute(&self, query: &String) -> anyhow::Result<Vec<ExecuteResult>> {
         pool_exec_impl!(&self.pool, query);
     }
 
+    async fn session(&self, handle: &QueryHandle) -> anyhow::Result<Box<dyn PoolSession>> {
+        let mut conn = sqlx::PgConnection::connect(&self.database_url).await?;
+        let backend_pid: i32 = sqlx::query_scalar("SELECT pg_backend_pid()")
+            .fetch_one(&mut conn)
+            .await?;
+        handle.set_connection_id(backend_pid as u64);
+        Ok(Box::new(PostgresSession(conn)))
+    }
+
+    async fn cancel_query(&self, handle: &QueryHandle) -> anyhow::Result<()> {
+        if let Some(backend_pid) = handle.connection_id() {
+            sqlx::query("SELECT pg_cancel_backend($1)")
+                .bind(backend_pid as i32)
+                .execute(&self.pool)
+                .await?;
+        }
+        Ok(())
+    }
+
     async fn get_databases(&self) -> anyhow::Result<Vec<Database>> {
         let databases = sqlx::query("SELECT datname FROM pg_database")
             .fetch_all(&self.pool)
@@ -91,33 +118,12 @@ impl Pool for PostgresPool {
         &self,
         database: &Database,
         table: &Table,
-        page: u16,
-        filter: Option<String>,
-    ) -> anyhow::Result<(Vec<String>, Vec<Vec<String>>)> {
-        let query = if let Some(filter) = filter.as_ref() {
-            format!(
-                r#"SELECT * FROM "{database}"."{table_schema}"."{table}" WHERE {filter} LIMIT {limit} OFFSET {page}"#,
-                database = database.name,
-                table = table.name,
-                filter = filter,
-                table_schema = table.schema.clone().unwrap_or_else(|| "public".to_string()),
-                page = page,
-                limit = RECORDS_LIMIT_PER_PAGE
-            )
-        } else {
-            format!(
-                r#"SELECT * FROM "{database}"."{table_schema}"."{table}" LIMIT {limit} OFFSET {page}"#,
-                database = database.name,
-                table = table.name,
-                table_schema = table.schema.clone().unwrap_or_else(|| "public".to_string()),
-                page = page,
-                limit = RECORDS_LIMIT_PER_PAGE
-            )
-        };
-        let mut rows = sqlx::query(query.as_str()).fetch(&self.pool);
+        request: &RecordsRequest,
+    ) -> anyhow::Result<(Vec<String>, Vec<Vec<Value>>)> {
+        let query = self.dialect().select(database, table, request);
+        let mut rows = self.pool.fetch(query.as_str());
         let mut headers = vec![];
         let mut records = vec![];
-        let mut json_records = None;
         while let Some(row) = rows.try_next().await? {
             headers = row
                 .columns()
@@ -126,40 +132,7 @@ impl Pool for PostgresPool {
                 .collect();
             let mut new_row = vec![];
             for column in row.columns() {
-                match convert_column_val_to_str(&row, column) {
-                    Ok(v) => new_row.push(v),
-                    Err(_) => {
-                        if json_records.is_none() {
-                            json_records = Some(
-                                self.get_json_records(database, table, page, filter.clone())
-                                    .await?,
-                            );
-                        }
-                        if let Some(json_records) = &json_records {
-                            match json_records
-                                .get(records.len())
-                                .unwrap()
-                                .get(column.name())
-                                .unwrap()
-                            {
-                                serde_json::Value::String(v) => new_row.push(v.to_string()),
-                                serde_json::Value::Null => new_row.push("NULL".to_string()),
-                                serde_json::Value::Array(v) => {
-                                    new_row.push(v.iter().map(|v| v.to_string()).join(","))
-                                }
-                                serde_json::Value::Number(v) => new_row.push(v.to_string()),
-                                serde_json::Value::Bool(v) => new_row.push(v.to_string()),
-                                others => {
-                                    panic!(
-                                        "column type not implemented: `{}` {}",
-                                        column.name(),
-                                        others
-                                    )
-                                }
-                            }
-                        }
-                    }
-                }
+                new_row.push(convert_column_value(&row, column)?)
             }
             records.push(new_row)
         }
@@ -232,6 +205,35 @@ impl Pool for PostgresPool {
         Ok(constraints)
     }
 
+    async fn get_primary_keys(
+        &self,
+        _database: &Database,
+        table: &Table,
+    ) -> anyhow::Result<Vec<String>> {
+        let keys = sqlx::query_scalar(
+            "
+        SELECT
+            kcu.column_name
+        FROM
+            information_schema.table_constraints AS tc
+            JOIN information_schema.key_column_usage AS kcu ON tc.constraint_name = kcu.constraint_name
+            AND tc.table_schema = kcu.table_schema
+            AND tc.table_name = kcu.table_name
+        WHERE
+            tc.constraint_type = 'PRIMARY KEY'
+            AND tc.table_schema = $1
+            AND tc.table_name = $2
+        ORDER BY
+            kcu.ordinal_position
+        ",
+        )
+        .bind(table.schema.clone().unwrap_or_else(|| "public".to_string()))
+        .bind(&table.name)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(keys)
+    }
+
     async fn get_foreign_keys(
         &self,
         _database: &Database,
@@ -272,6 +274,71 @@ impl Pool for PostgresPool {
         Ok(constraints)
     }
 
+    async fn get_references(
+        &self,
+        _database: &Database,
+        table: &Table,
+    ) -> anyhow::Result<Vec<Reference>> {
+        // Constraint names are only unique per table, so the columns are taken from the
+        // constraint itself, pairing each referencing column with the referenced one.
+        let mut rows = sqlx::query(
+            "
+        SELECT
+            con.conname::text AS constraint_name,
+            ns.nspname::text AS table_schema,
+            cl.relname::text AS table_name,
+            att.attname::text AS column_name,
+            fatt.attname::text AS foreign_column_name
+        FROM
+            pg_catalog.pg_constraint AS con
+            CROSS JOIN LATERAL unnest(con.conkey, con.confkey) WITH ORDINALITY AS k(attnum, fattnum, position)
+            JOIN pg_catalog.pg_class AS cl ON cl.oid = con.conrelid
+            JOIN pg_catalog.pg_namespace AS ns ON ns.oid = cl.relnamespace
+            JOIN pg_catalog.pg_class AS fcl ON fcl.oid = con.confrelid
+            JOIN pg_catalog.pg_namespace AS fns ON fns.oid = fcl.relnamespace
+            JOIN pg_catalog.pg_attribute AS att ON att.attrelid = con.conrelid
+            AND att.attnum = k.attnum
+            JOIN pg_catalog.pg_attribute AS fatt ON fatt.attrelid = con.confrelid
+            AND fatt.attnum = k.fattnum
+        WHERE
+            con.contype = 'f'
+            AND fns.nspname = $1
+            AND fcl.relname = $2
+        ORDER BY
+            ns.nspname,
+            cl.relname,
+            con.conname,
+            k.position
+        ",
+        )
+        .bind(table.schema.clone().unwrap_or_else(|| "public".to_string()))
+        .bind(&table.name)
+        .fetch(&self.pool);
+        let mut references = vec![];
+        while let Some(row) = rows.try_next().await? {
+            references.push(Reference {
+                name: row.try_get("constraint_name")?,
+                schema: row.try_get("table_schema")?,
+                table: row.try_get("table_name")?,
+                columns: vec![(
+                    row.try_get("column_name")?,
+                    row.try_get("foreign_column_name")?,
+                )],
+            })
+        }
+        Ok(Reference::group(references))
+    }
+
+    async fn count_records(
+        &self,
+        database: &Database,
+        table: &Table,
+        filter: &str,
+    ) -> anyhow::Result<i64> {
+        let query = self.dialect().count(database, table, filter);
+        Ok(sqlx::query_scalar(&query).fetch_one(&self.pool).await?)
+    }
+
     async fn get_indexes(
         &self,
         _database: &Database,
@@ -316,41 +383,20 @@ impl Pool for PostgresPool {
         Ok(foreign_keys)
     }
 
+    async fn begin(&self, handle: &QueryHandle) -> anyhow::Result<Box<dyn PoolTransaction>> {
+        let mut transaction = self.pool.begin().await?;
+        let backend_pid: i32 = sqlx::query_scalar("SELECT pg_backend_pid()")
+            .fetch_one(&mut transaction)
+            .await?;
+        handle.set_connection_id(backend_pid as u64);
+        Ok(Box::new(PostgresTransaction(transaction)))
+    }
+
     async fn close(&self) {
         self.pool.close().await;
     }
-}
 
-impl PostgresPool {
-    async fn get_json_records(
-        &self,
-        database: &Database,
-        table: &Table,
-        page: u16,
-        filter: Option<String>,
-    ) -> anyhow::Result<Vec<serde_json::Value>> {
-        let query = if let Some(filter) = filter {
-            format!(
-                r#"SELECT to_json("{table}".*) FROM "{database}"."{table_schema}"."{table}" WHERE {filter} LIMIT {limit} OFFSET {page}"#,
-                database = database.name,
-                table = table.name,
-                filter = filter,
-                table_schema = table.schema.clone().unwrap_or_else(|| "public".to_string()),
-                page = page,
-                limit = RECORDS_LIMIT_PER_PAGE
-            )
-        } else {
-            format!(
-                r#"SELECT to_json("{table}".*) FROM "{database}"."{table_schema}"."{table}" LIMIT {limit} OFFSET {page}"#,
-                database = database.name,
-                table = table.name,
-                table_schema = table.schema.clone().unwrap_or_else(|| "public".to_string()),
-                page = page,
-                limit = RECORDS_LIMIT_PER_PAGE
-            )
-        };
-        let json: Vec<(serde_json::Value,)> =
-            sqlx::query_as(query.as_str()).fetch_all(&self.pool).await?;
-        Ok(json.iter().map(|v| v.clone().0).collect())
+    fn dialect(&self) -> Dialect {
+        Dialect::Postgres
     }
 }
@@ -2,15 +2,18 @@ use std::time::Duration;
 
 use async_trait::async_trait;
 use futures::TryStreamExt;
+use sqlx::pool::PoolConnection;
 use sqlx::sqlite::SqlitePoolOptions;
-use sqlx::{Column as _, Row as _};
+use sqlx::{Column as _, Executor as _, Row as _};
 
 use database_tree::{Child, Database, Table};
 
-use crate::database::{convert_column_val_to_str, Column, Constraint, ForeignKey, Index};
-use crate::pool_exec_impl;
+use crate::database::{
+    convert_column_value, Column, Constraint, ForeignKey, Index, RecordsRequest, Reference, Value,
+};
+use crate::{pool_exec_impl, pool_transaction_impl};
 
-use super::{ExecuteResult, Pool, TableRow, RECORDS_LIMIT_PER_PAGE};
+use super::{Dialect, ExecuteResult, Pool, PoolSession, PoolTransaction, QueryHandle, TableRow};
 
 pub struct SqlitePool {
     pool: sqlx::sqlite::SqlitePool,
@@ -27,12 +30,35 @@ impl SqlitePool {
     }
 }
 
+/// Uses a connection of the pool, as a new connection to an in-memory database would open
+/// another, empty database.
+pub struct SqliteSession(PoolConnection<sqlx::Sqlite>);
+
+#[async_trait]
+impl PoolSession for SqliteSession {
+    async fn execute(&mut self, query: &str) -> anyhow::Result<Vec<ExecuteResult>> {
+        pool_exec_impl!(&mut self.0, query);
+    }
+
+    async fn close(mut self: Box<Self>) -> anyhow::Result<()> {
+        // Fails if no transaction is open, which is the usual case
+        let _ = sqlx::query("ROLLBACK").execute(&mut self.0).await;
+        Ok(())
+    }
+}
+
+pool_transaction_impl!(SqliteTransaction, sqlx::Sqlite);
+
 #[async_trait]
 impl Pool for SqlitePool {
-    async fn execute(&self, query: &String) -> anyhow::Result<ExecuteResult> {
+    async fn execute(&self, query: &String) -> anyhow::Result<Vec<ExecuteResult>> {
         pool_exec_impl!(&self.pool, query);
     }
 
+    async fn session(&self, _handle: &QueryHandle) -> anyhow::Result<Box<dyn PoolSession>> {
+        Ok(Box::new(SqliteSession(self.pool.acquire().await?)))
+    }
+
     async fn get_databases(&self) -> anyhow::Result<Vec<Database>> {
         let databases = sqlx::query("SELECT name FROM pragma_database_list")
             .fetch_all(&self.pool)
@@ -69,28 +95,12 @@ impl Pool for SqlitePool {
 
     async fn get_records(
         &self,
-        _database: &Database,
+        database: &Database,
         table: &Table,
-        page: u16,
-        filter: Option<String>,
-    ) -> anyhow::Result<(Vec<String>, Vec<Vec<String>>)> {
-        let query = if let Some(filter) = filter {
-            format!(
-                "SELECT * FROM `{table}` WHERE {filter} LIMIT {page}, {limit}",
-                table = table.name,
-                filter = filter,
-                page = page,
-                limit = RECORDS_LIMIT_PER_PAGE
-            )
-        } else {
-            format!(
-                "SELECT * FROM `{}` LIMIT {page}, {limit}",
-                table.name,
-                page = page,
-                limit = RECORDS_LIMIT_PER_PAGE
-            )
-        };
-        let mut rows = sqlx::query(query.as_str()).fetch(&self.pool);
+        request: &RecordsRequest,
+    ) -> anyhow::Result<(Vec<String>, Vec<Vec<Value>>)> {
+        let query = self.dialect().select(database, table, request);
+        let mut rows = self.pool.fetch(query.as_str());
         let mut headers = vec![];
         let mut records = vec![];
         while let Some(row) = rows.try_next().await? {
@@ -101,7 +111,7 @@ impl Pool for SqlitePool {
                 .collect();
             let mut new_row = vec![];
             for column in row.columns() {
-                new_row.push(convert_column_val_to_str(&row, column)?)
+                new_row.push(convert_column_value(&row, column)?)
             }
             records.push(new_row)
         }
@@ -163,6 +173,19 @@ impl Pool for SqlitePool {
         Ok(constraints)
     }
 
+    async fn get_primary_keys(
+        &self,
+        _database: &Database,
+        table: &Table,
+    ) -> anyhow::Result<Vec<String>> {
+        let keys =
+            sqlx::query_scalar("SELECT name FROM pragma_table_info(?) WHERE pk > 0 ORDER BY pk")
+                .bind(&table.name)
+                .fetch_all(&self.pool)
+                .await?;
+        Ok(keys)
+    }
+
     async fn get_foreign_keys(
         &self,
         _database: &Database,
@@ -187,6 +210,59 @@ impl Pool for SqlitePool {
         Ok(foreign_keys)
     }
 
+    async fn get_references(
+        &self,
+        database: &Database,
+        table: &Table,
+    ) -> anyhow::Result<Vec<Reference>> {
+        let mut rows = sqlx::query(
+            "SELECT m.name, p.id, p.seq, p.`from`, p.`to` FROM sqlite_master m
+            JOIN pragma_foreign_key_list(m.name) p
+            WHERE m.type = 'table' AND p.`table` = ? COLLATE NOCASE
+            ORDER BY m.name, p.id, p.seq",
+        )
+        .bind(&table.name)
+        .fetch(&self.pool);
+        let mut references = vec![];
+        while let Some(row) = rows.try_next().await? {
+            references.push((
+                row.try_get::<String, _>("name")?,
+                row.try_get::<i64, _>("id")?,
+                row.try_get::<i64, _>("seq")?,
+                row.try_get::<String, _>("from")?,
+                row.try_get::<Option<String>, _>("to")?,
+            ));
+        }
+        // Foreign keys without columns reference the primary key, column by column.
+        let primary_key = self.get_primary_keys(database, table).await?;
+        Ok(Reference::group(
+            references
+                .into_iter()
+                .filter_map(|(name, id, seq, column, ref_column)| {
+                    Some(Reference {
+                        name: id.to_string(),
+                        schema: None,
+                        table: name,
+                        columns: vec![(
+                            column,
+                            ref_column.or_else(|| primary_key.get(seq as usize).cloned())?,
+                        )],
+                    })
+                })
+                .collect(),
+        ))
+    }
+
+    async fn count_records(
+        &self,
+        database: &Database,
+        table: &Table,
+        filter: &str,
+    ) -> anyhow::Result<i64> {
+        let query = self.dialect().count(database, table, filter);
+        Ok(sqlx::query_scalar(&query).fetch_one(&self.pool).await?)
+    }
+
     async fn get_indexes(
         &self,
         _database: &Database,
@@ -218,7 +294,162 @@ impl Pool for SqlitePool {
         Ok(foreign_keys)
     }
 
+    async fn begin(&self, _handle: &QueryHandle) -> anyhow::Result<Box<dyn PoolTransaction>> {
+        Ok(Box::new(SqliteTransaction(self.pool.begin().await?)))
+    }
+
     async fn close(&self) {
         self.pool.close().await;
     }
+
+    fn dialect(&self) -> Dialect {
+        Dialect::Sqlite
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Pool, SqlitePool};
+    use crate::database::QueryHandle;
+    use crate::database::{test_table, ExecuteResult, Reference, Value};
+
+    #[tokio::test]
+    async fn test_execute_values() {
+        let pool = SqlitePool::new("sqlite::memory:").await.unwrap();
+        let mut results = pool
+            .execute(&"SELECT 1, 1.5, 'NULL', NULL, x'dead'".to_string())
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        match results.remove(0) {
+            ExecuteResult::Read { rows, .. } => assert_eq!(
+                rows,
+                vec![vec![
+                    Value::Int(1),
+                    Value::Float(1.5),
+                    Value::Text("NULL".to_string()),
+                    Value::Null,
+                    Value::Bytes(vec![0xde, 0xad]),
+                ]]
+            ),
+            ExecuteResult::Write { .. } => panic!("expected rows"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_result_sets() {
+        let pool = SqlitePool::new("sqlite::memory:").await.unwrap();
+        let results = pool
+            .execute(
+                &"CREATE TABLE t (a INTEGER); INSERT INTO t VALUES (1), (2); \
+                  SELECT a FROM t; SELECT COUNT(*) AS n FROM t"
+                    .to_string(),
+            )
+            .await
+            .unwrap();
+        let results = results
+            .into_iter()
+            .map(|result| match result {
+                ExecuteResult::Read { headers, rows, .. } => format!("{:?} {:?}", headers, rows),
+                ExecuteResult::Write { updated_rows } => format!("{} rows affected", updated_rows),
+            })
+            .collect::<Vec<String>>();
+        assert_eq!(
+            results,
+            vec![
+                "0 rows affected",
+                "2 rows affected",
+                "[\"a\"] [[Int(1)], [Int(2)]]",
+                "[\"n\"] [[Int(2)]]",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_execute_empty_result_set() {
+        let pool = SqlitePool::new("sqlite::memory:").await.unwrap();
+        pool.execute(&"CREATE TABLE t (a INTEGER, b TEXT)".to_string())
+            .await
+            .unwrap();
+        match pool
+            .execute(&"SELECT a, b FROM t WHERE a > 5".to_string())
+            .await
+            .unwrap()
+            .remove(0)
+        {
+            ExecuteResult::Read { headers, rows, .. } => {
+                assert_eq!(headers, vec!["a".to_string(), "b".to_string()]);
+                assert!(rows.is_empty());
+            }
+            ExecuteResult::Write { .. } => panic!("expected an empty result set"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_session() {
+        let pool = SqlitePool::new("sqlite::memory:").await.unwrap();
+        let mut session = pool.session(&QueryHandle::default()).await.unwrap();
+        for statement in [
+            "CREATE TABLE t (a INTEGER)",
+            "CREATE TEMP TABLE session_state (a INTEGER)",
+            "BEGIN",
+            "INSERT INTO t VALUES (1)",
+            "ROLLBACK",
+            "INSERT INTO t VALUES (2)",
+            "BEGIN",
+            "INSERT INTO t VALUES (3)",
+        ] {
+            session.execute(statement).await.unwrap();
+        }
+        // The temporary table is only visible on the connection of the session
+        session
+            .execute("SELECT a FROM session_state")
+            .await
+            .unwrap();
+        match session.execute("SELECT a FROM t").await.unwrap().remove(0) {
+            ExecuteResult::Read { rows, .. } => {
+                assert_eq!(rows, vec![vec![Value::Int(2)], vec![Value::Int(3)]])
+            }
+            ExecuteResult::Write { .. } => panic!("expected rows"),
+        }
+        // Closing the session rolls back the open transaction
+        session.close().await.unwrap();
+        let mut session = pool.session(&QueryHandle::default()).await.unwrap();
+        match session.execute("SELECT a FROM t").await.unwrap().remove(0) {
+            ExecuteResult::Read { rows, .. } => assert_eq!(rows, vec![vec![Value::Int(2)]]),
+            ExecuteResult::Write { .. } => panic!("expected rows"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_references() {
+        let pool = SqlitePool::new("sqlite::memory:").await.unwrap();
+        pool.execute(
+            &"CREATE TABLE shops (id INTEGER, region TEXT, PRIMARY KEY (id, region)); \
+              CREATE TABLE orders (id INTEGER PRIMARY KEY, shop_id INTEGER, shop_region TEXT, \
+                FOREIGN KEY (shop_region, shop_id) REFERENCES shops (region, id)); \
+              CREATE TABLE stocks (shop_id INTEGER, shop_region TEXT, \
+                FOREIGN KEY (shop_id, shop_region) REFERENCES shops)"
+                .to_string(),
+        )
+        .await
+        .unwrap();
+        let (database, table) = test_table("shops");
+        let reference = |table: &str, columns: [(&str, &str); 2]| Reference {
+            name: "0".to_string(),
+            schema: None,
+            table: table.to_string(),
+            columns: columns
+                .iter()
+                .map(|(column, ref_column)| (column.to_string(), ref_column.to_string()))
+                .collect(),
+        };
+        assert_eq!(
+            pool.get_references(&database, &table).await.unwrap(),
+            vec![
+                reference("orders", [("shop_region", "region"), ("shop_id", "id")]),
+                reference("stocks", [("shop_id", "id"), ("shop_region", "region")]),
+            ]
+        );
+    }
 }
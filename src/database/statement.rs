@@ -0,0 +1,615 @@
+use std::fmt;
+
+use database_tree::{Database, Table};
+
+use super::{page_offset, Value, RECORDS_LIMIT_PER_PAGE};
+
+/// The SQL flavour spoken by a `Pool`. Used to build statements from values shown in the UI.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Dialect {
+    MySql,
+    Postgres,
+    Sqlite,
+}
+
+/// A column the records are ordered by.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OrderBy {
+    pub column: String,
+    pub descending: bool,
+}
+
+impl OrderBy {
+    /// Parses a list of columns such as `name, created_at DESC`. Each column may be followed by
+    /// `ASC` or `DESC`, and may be quoted if its name contains spaces or commas.
+    pub fn parse_list(text: &str) -> anyhow::Result<Vec<Self>> {
+        let mut list = vec![];
+        for item in split_list(text) {
+            let item = item.trim();
+            if item.is_empty() {
+                if text.trim().is_empty() {
+                    break;
+                }
+                anyhow::bail!("empty column in `{}`", text);
+            }
+            let (column, descending) = match item.rsplit_once(char::is_whitespace) {
+                Some((column, direction)) if direction.eq_ignore_ascii_case("desc") => {
+                    (column.trim_end(), true)
+                }
+                Some((column, direction)) if direction.eq_ignore_ascii_case("asc") => {
+                    (column.trim_end(), false)
+                }
+                _ => (item, false),
+            };
+            list.push(Self {
+                column: unquote(column).to_string(),
+                descending,
+            })
+        }
+        Ok(list)
+    }
+}
+
+impl fmt::Display for OrderBy {
+    /// Writes the order in the form read by `parse_list`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self
+            .column
+            .contains(|c: char| c == ',' || c.is_whitespace())
+        {
+            write!(f, "\"{}\"", self.column)?;
+        } else {
+            write!(f, "{}", self.column)?;
+        }
+        if self.descending {
+            write!(f, " DESC")?;
+        }
+        Ok(())
+    }
+}
+
+/// Splits `text` on the commas which are not quoted.
+fn split_list(text: &str) -> Vec<&str> {
+    let mut items = vec![];
+    let mut quote = None;
+    let mut start = 0;
+    for (index, c) in text.char_indices() {
+        match (quote, c) {
+            (None, '"' | '`') => quote = Some(c),
+            (Some(q), c) if q == c => quote = None,
+            (None, ',') => {
+                items.push(&text[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    items.push(&text[start..]);
+    items
+}
+
+/// Removes the quotes around an identifier, if any.
+fn unquote(identifier: &str) -> &str {
+    for quote in ['"', '`'] {
+        if let Some(unquoted) = identifier
+            .strip_prefix(quote)
+            .and_then(|identifier| identifier.strip_suffix(quote))
+        {
+            return unquoted;
+        }
+    }
+    identifier
+}
+
+/// Describes the records fetched by `Pool::get_records`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordsRequest {
+    /// An SQL expression the records have to match.
+    pub filter: Option<String>,
+    pub order_by: Vec<OrderBy>,
+    pub limit: u64,
+    pub offset: u64,
+    /// The columns to fetch, or all of them if empty.
+    pub columns: Vec<String>,
+}
+
+impl RecordsRequest {
+    /// Requests all columns of the given (zero based) page of `RECORDS_LIMIT_PER_PAGE` records.
+    pub fn page(page: u16) -> Self {
+        Self {
+            filter: None,
+            order_by: vec![],
+            limit: RECORDS_LIMIT_PER_PAGE as u64,
+            offset: page_offset(page),
+            columns: vec![],
+        }
+    }
+}
+
+impl Dialect {
+    pub fn quote_identifier(&self, identifier: &str) -> String {
+        match self {
+            Self::MySql => format!("`{}`", identifier.replace('`', "``")),
+            Self::Postgres | Self::Sqlite => format!("\"{}\"", identifier.replace('"', "\"\"")),
+        }
+    }
+
+    /// Quotes `value` as a string literal, or returns `NULL` if there is no value. Values are
+    /// always sent as strings and left to the server to convert to the column type.
+    pub fn quote_literal(&self, value: Option<&str>) -> String {
+        let value = match value {
+            Some(value) => value,
+            None => return "NULL".to_string(),
+        };
+        let escaped = match self {
+            Self::MySql => value.replace('\\', "\\\\").replace('\'', "''"),
+            Self::Postgres | Self::Sqlite => value.replace('\'', "''"),
+        };
+        format!("'{}'", escaped)
+    }
+
+    /// Renders `value` as a literal. Numbers and booleans are kept as they are, bytes are written
+    /// as a binary literal, Postgres arrays as an array constructor and anything else is quoted
+    /// as a string.
+    pub fn literal(&self, value: &Value) -> String {
+        match (self, value) {
+            (_, Value::Bytes(bytes)) => self.bytes_literal(bytes),
+            (_, Value::Bool(value)) => value.to_string().to_uppercase(),
+            (Self::Postgres, Value::Array(values)) if !values.is_empty() => format!(
+                "ARRAY[{}]",
+                values
+                    .iter()
+                    .map(|value| self.literal(value))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            (Self::Postgres, Value::Array(_)) => "'{}'".to_string(),
+            (_, value) if value.is_number() => value.to_string(),
+            (_, value) => self.quote_literal(value.to_sql_text().as_deref()),
+        }
+    }
+
+    /// Renders `bytes` as a binary literal, in hexadecimal.
+    pub fn bytes_literal(&self, bytes: &[u8]) -> String {
+        let hex = bytes
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<String>();
+        match self {
+            Self::MySql | Self::Sqlite => format!("X'{}'", hex),
+            Self::Postgres => format!("'\\x{}'::bytea", hex),
+        }
+    }
+
+    /// The fully qualified name of `table`, quoted.
+    pub fn table_name(&self, database: &Database, table: &Table) -> String {
+        match self {
+            Self::MySql => format!(
+                "{}.{}",
+                self.quote_identifier(&database.name),
+                self.quote_identifier(&table.name)
+            ),
+            Self::Postgres => format!(
+                "{}.{}",
+                self.quote_identifier(table.schema.as_deref().unwrap_or("public")),
+                self.quote_identifier(&table.name)
+            ),
+            Self::Sqlite => self.quote_identifier(&table.name),
+        }
+    }
+
+    /// Builds the SELECT fetching the records described by `request`.
+    pub fn select(&self, database: &Database, table: &Table, request: &RecordsRequest) -> String {
+        let columns = if request.columns.is_empty() {
+            "*".to_string()
+        } else {
+            request
+                .columns
+                .iter()
+                .map(|column| self.quote_identifier(column))
+                .collect::<Vec<String>>()
+                .join(", ")
+        };
+        let mut query = format!(
+            "SELECT {} FROM {}",
+            columns,
+            self.table_name(database, table)
+        );
+        if let Some(filter) = &request.filter {
+            query.push_str(&format!(" WHERE {}", filter));
+        }
+        if !request.order_by.is_empty() {
+            query.push_str(&format!(
+                " ORDER BY {}",
+                request
+                    .order_by
+                    .iter()
+                    .map(|order_by| format!(
+                        "{} {}",
+                        self.quote_identifier(&order_by.column),
+                        if order_by.descending { "DESC" } else { "ASC" }
+                    ))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ));
+        }
+        query.push_str(&format!(
+            " LIMIT {} OFFSET {}",
+            request.limit, request.offset
+        ));
+        query
+    }
+
+    /// Builds a condition matching the row identified by the `(column, value)` pairs in `keys`.
+    pub fn key_condition(&self, keys: &[(String, Value)]) -> String {
+        keys.iter()
+            .map(|(column, value)| {
+                format!(
+                    "{} = {}",
+                    self.quote_identifier(column),
+                    self.literal(value)
+                )
+            })
+            .collect::<Vec<String>>()
+            .join(" AND ")
+    }
+
+    /// Builds an UPDATE setting `column` to `value` on the row identified by `keys`.
+    pub fn update(
+        &self,
+        database: &Database,
+        table: &Table,
+        column: &str,
+        value: Option<&str>,
+        keys: &[(String, Value)],
+    ) -> String {
+        format!(
+            "UPDATE {} SET {} = {} WHERE {}",
+            self.table_name(database, table),
+            self.quote_identifier(column),
+            self.quote_literal(value),
+            self.key_condition(keys)
+        )
+    }
+
+    /// Builds an INSERT of the given `(column, value)` pairs. Columns which are not listed are
+    /// left to their default.
+    pub fn insert(
+        &self,
+        database: &Database,
+        table: &Table,
+        values: &[(String, Option<String>)],
+    ) -> String {
+        self.insert_literals(
+            database,
+            table,
+            values
+                .iter()
+                .map(|(column, value)| (column.as_str(), self.quote_literal(value.as_deref())))
+                .collect(),
+        )
+    }
+
+    /// Builds an INSERT of the given `(column, value)` pairs, writing each value as a literal of
+    /// its type.
+    pub fn insert_values(
+        &self,
+        database: &Database,
+        table: &Table,
+        values: &[(String, Value)],
+    ) -> String {
+        self.insert_literals(
+            database,
+            table,
+            values
+                .iter()
+                .map(|(column, value)| (column.as_str(), self.literal(value)))
+                .collect(),
+        )
+    }
+
+    fn insert_literals(
+        &self,
+        database: &Database,
+        table: &Table,
+        values: Vec<(&str, String)>,
+    ) -> String {
+        let table_name = self.table_name(database, table);
+        if values.is_empty() {
+            return match self {
+                Self::MySql => format!("INSERT INTO {} () VALUES ()", table_name),
+                Self::Postgres | Self::Sqlite => {
+                    format!("INSERT INTO {} DEFAULT VALUES", table_name)
+                }
+            };
+        }
+        format!(
+            "INSERT INTO {} ({}) VALUES ({})",
+            table_name,
+            values
+                .iter()
+                .map(|(column, _)| self.quote_identifier(column))
+                .collect::<Vec<String>>()
+                .join(", "),
+            values
+                .iter()
+                .map(|(_, value)| value.as_str())
+                .collect::<Vec<&str>>()
+                .join(", ")
+        )
+    }
+
+    /// Builds a single INSERT of several rows, each holding one value per entry of `columns`.
+    pub fn insert_rows(
+        &self,
+        database: &Database,
+        table: &Table,
+        columns: &[String],
+        rows: &[Vec<Option<String>>],
+    ) -> String {
+        format!(
+            "INSERT INTO {} ({}) VALUES {}",
+            self.table_name(database, table),
+            columns
+                .iter()
+                .map(|column| self.quote_identifier(column))
+                .collect::<Vec<String>>()
+                .join(", "),
+            rows.iter()
+                .map(|row| {
+                    format!(
+                        "({})",
+                        row.iter()
+                            .map(|value| self.quote_literal(value.as_deref()))
+                            .collect::<Vec<String>>()
+                            .join(", ")
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join(", ")
+        )
+    }
+
+    /// Builds a CREATE TABLE with the given `(column, type)` pairs.
+    pub fn create_table(
+        &self,
+        database: &Database,
+        table: &Table,
+        columns: &[(String, String)],
+    ) -> String {
+        format!(
+            "CREATE TABLE {} ({})",
+            self.table_name(database, table),
+            columns
+                .iter()
+                .map(|(column, r#type)| format!("{} {}", self.quote_identifier(column), r#type))
+                .collect::<Vec<String>>()
+                .join(", ")
+        )
+    }
+
+    /// Builds a SELECT counting the records of `table` matching `filter`.
+    pub fn count(&self, database: &Database, table: &Table, filter: &str) -> String {
+        format!(
+            "SELECT COUNT(*) FROM {} WHERE {}",
+            self.table_name(database, table),
+            filter
+        )
+    }
+
+    /// Builds a DELETE of the rows identified by each entry of `rows`.
+    pub fn delete(
+        &self,
+        database: &Database,
+        table: &Table,
+        rows: &[Vec<(String, Value)>],
+    ) -> String {
+        let condition = if rows.len() == 1 {
+            self.key_condition(&rows[0])
+        } else {
+            rows.iter()
+                .map(|keys| format!("({})", self.key_condition(keys)))
+                .collect::<Vec<String>>()
+                .join(" OR ")
+        };
+        format!(
+            "DELETE FROM {} WHERE {}",
+            self.table_name(database, table),
+            condition
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Dialect, OrderBy, RecordsRequest, Value};
+    use crate::database::test_table;
+
+    #[test]
+    fn test_quote_literal() {
+        assert_eq!(Dialect::Postgres.quote_literal(Some("it's")), "'it''s'");
+        assert_eq!(Dialect::MySql.quote_literal(Some("a\\'b")), "'a\\\\''b'");
+        assert_eq!(Dialect::Sqlite.quote_literal(None), "NULL");
+        assert_eq!(Dialect::Sqlite.literal(&Value::Int(3)), "3");
+        assert_eq!(Dialect::Sqlite.literal(&Value::from("3")), "'3'");
+        let bytes = Value::from(vec![0xde_u8, 0xad]);
+        assert_eq!(Dialect::MySql.literal(&bytes), "X'dead'");
+        assert_eq!(Dialect::Sqlite.literal(&bytes), "X'dead'");
+        assert_eq!(Dialect::Postgres.literal(&bytes), "'\\xdead'::bytea");
+        assert_eq!(Dialect::MySql.literal(&Value::Bool(true)), "TRUE");
+        let array = Value::from(vec!["a".to_string(), "b'c".to_string()]);
+        assert_eq!(Dialect::Postgres.literal(&array), "ARRAY['a', 'b''c']");
+        assert_eq!(Dialect::Postgres.literal(&Value::Array(vec![])), "'{}'");
+        assert_eq!(Dialect::Sqlite.literal(&array), "'a,b''c'");
+    }
+
+    #[test]
+    fn test_quote_identifier() {
+        assert_eq!(Dialect::MySql.quote_identifier("a`b"), "`a``b`");
+        assert_eq!(Dialect::Postgres.quote_identifier("a\"b"), "\"a\"\"b\"");
+    }
+
+    #[test]
+    fn test_parse_order_by() {
+        let order_by = |column: &str, descending| OrderBy {
+            column: column.to_string(),
+            descending,
+        };
+        assert_eq!(
+            OrderBy::parse_list("name, \"created, at\" desc ,id ASC").unwrap(),
+            vec![
+                order_by("name", false),
+                order_by("created, at", true),
+                order_by("id", false)
+            ]
+        );
+        assert_eq!(OrderBy::parse_list(" ").unwrap(), vec![]);
+        assert_eq!(
+            order_by("created, at", true).to_string(),
+            "\"created, at\" DESC"
+        );
+        assert!(OrderBy::parse_list("name,").is_err());
+    }
+
+    #[test]
+    fn test_select() {
+        let (database, table) = test_table("orders");
+        assert_eq!(
+            Dialect::MySql.select(&database, &table, &RecordsRequest::page(1)),
+            "SELECT * FROM `shop`.`orders` LIMIT 200 OFFSET 200"
+        );
+        let request = RecordsRequest {
+            filter: Some("total > 10".to_string()),
+            order_by: vec![
+                OrderBy {
+                    column: "created at".to_string(),
+                    descending: true,
+                },
+                OrderBy {
+                    column: "id".to_string(),
+                    descending: false,
+                },
+            ],
+            limit: 10,
+            offset: 0,
+            columns: vec!["id".to_string(), "total".to_string()],
+        };
+        assert_eq!(
+            Dialect::Postgres.select(&database, &table, &request),
+            r#"SELECT "id", "total" FROM "public"."orders" WHERE total > 10 ORDER BY "created at" DESC, "id" ASC LIMIT 10 OFFSET 0"#
+        );
+    }
+
+    #[test]
+    fn test_update() {
+        let (database, table) = test_table("orders");
+        let keys = vec![
+            ("id".to_string(), Value::Int(1)),
+            ("line".to_string(), Value::from("2")),
+        ];
+        assert_eq!(
+            Dialect::MySql.update(&database, &table, "note", Some("x"), &keys),
+            "UPDATE `shop`.`orders` SET `note` = 'x' WHERE `id` = 1 AND `line` = '2'"
+        );
+        assert_eq!(
+            Dialect::Postgres.update(&database, &table, "note", None, &keys[..1]),
+            r#"UPDATE "public"."orders" SET "note" = NULL WHERE "id" = 1"#
+        );
+    }
+
+    #[test]
+    fn test_insert() {
+        let (database, table) = test_table("orders");
+        assert_eq!(
+            Dialect::Sqlite.insert(
+                &database,
+                &table,
+                &[
+                    ("id".to_string(), Some("1".to_string())),
+                    ("note".to_string(), None)
+                ]
+            ),
+            r#"INSERT INTO "orders" ("id", "note") VALUES ('1', NULL)"#
+        );
+        assert_eq!(
+            Dialect::Postgres.insert_values(
+                &database,
+                &table,
+                &[
+                    ("id".to_string(), Value::Int(1)),
+                    ("tags".to_string(), Value::from(vec![1_i32, 2])),
+                    ("note".to_string(), Value::Null)
+                ]
+            ),
+            r#"INSERT INTO "public"."orders" ("id", "tags", "note") VALUES (1, ARRAY[1, 2], NULL)"#
+        );
+        assert_eq!(
+            Dialect::MySql.insert(&database, &table, &[]),
+            "INSERT INTO `shop`.`orders` () VALUES ()"
+        );
+        assert_eq!(
+            Dialect::Postgres.insert(&database, &table, &[]),
+            r#"INSERT INTO "public"."orders" DEFAULT VALUES"#
+        );
+    }
+
+    #[test]
+    fn test_delete() {
+        let (database, table) = test_table("orders");
+        let row = |id: Value| vec![("id".to_string(), id)];
+        assert_eq!(
+            Dialect::MySql.delete(&database, &table, &[row(Value::Int(1))]),
+            "DELETE FROM `shop`.`orders` WHERE `id` = 1"
+        );
+        assert_eq!(
+            Dialect::Sqlite.delete(
+                &database,
+                &table,
+                &[row(Value::from("a")), row(Value::from(vec![0xff_u8]))]
+            ),
+            r#"DELETE FROM "orders" WHERE ("id" = 'a') OR ("id" = X'ff')"#
+        );
+    }
+
+    #[test]
+    fn test_count() {
+        let (database, table) = test_table("orders");
+        assert_eq!(
+            Dialect::Postgres.count(&database, &table, "\"user_id\" = 3"),
+            r#"SELECT COUNT(*) FROM "public"."orders" WHERE "user_id" = 3"#
+        );
+    }
+
+    #[test]
+    fn test_insert_rows() {
+        let (database, table) = test_table("orders");
+        assert_eq!(
+            Dialect::MySql.insert_rows(
+                &database,
+                &table,
+                &["id".to_string(), "note".to_string()],
+                &[
+                    vec![Some("1".to_string()), None],
+                    vec![Some("2".to_string()), Some("x".to_string())]
+                ]
+            ),
+            "INSERT INTO `shop`.`orders` (`id`, `note`) VALUES ('1', NULL), ('2', 'x')"
+        );
+    }
+
+    #[test]
+    fn test_create_table() {
+        let (database, table) = test_table("orders");
+        assert_eq!(
+            Dialect::Sqlite.create_table(
+                &database,
+                &table,
+                &[
+                    ("id".to_string(), "INTEGER".to_string()),
+                    ("note".to_string(), "TEXT".to_string())
+                ]
+            ),
+            r#"CREATE TABLE "orders" ("id" INTEGER, "note" TEXT)"#
+        );
+    }
+}
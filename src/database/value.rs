@@ -0,0 +1,333 @@
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+
+/// A single value of a result set, as decoded from the database. How it is shown is left to the
+/// UI: `Display` renders the plain text form, with `NULL` for null values.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Null,
+    Int(i64),
+    Float(f64),
+    Decimal(Decimal),
+    Text(String),
+    Bytes(Vec<u8>),
+    Bool(bool),
+    Date(NaiveDate),
+    Time(NaiveTime),
+    Timestamp(NaiveDateTime),
+    TimestampTz(DateTime<FixedOffset>),
+    Json(serde_json::Value),
+    Uuid(String),
+    Array(Vec<Value>),
+    /// A value of a type which could not be decoded, in the text form sent by the server.
+    Unknown(String),
+}
+
+impl Value {
+    pub fn is_null(&self) -> bool {
+        matches!(self, Self::Null)
+    }
+
+    pub fn is_number(&self) -> bool {
+        matches!(self, Self::Int(_) | Self::Float(_) | Self::Decimal(_))
+    }
+
+    /// The text used when the value is written back in a statement, or `None` for `NULL`.
+    pub fn to_sql_text(&self) -> Option<String> {
+        match self {
+            Self::Null => None,
+            value => Some(value.to_string()),
+        }
+    }
+
+    /// Orders values for sorting. Nulls come first, then numbers, compared by value also when
+    /// they are only known as text, then everything else grouped by type. Within a group values
+    /// are compared by value or by their text forms, so that the order is total.
+    pub fn compare(&self, other: &Self) -> Ordering {
+        self.sort_group()
+            .cmp(&other.sort_group())
+            .then_with(|| match (self, other) {
+                (Self::Int(a), Self::Int(b)) => a.cmp(b),
+                (Self::Decimal(a), Self::Decimal(b)) => a.cmp(b),
+                (Self::Bool(a), Self::Bool(b)) => a.cmp(b),
+                (Self::Date(a), Self::Date(b)) => a.cmp(b),
+                (Self::Time(a), Self::Time(b)) => a.cmp(b),
+                (Self::Timestamp(a), Self::Timestamp(b)) => a.cmp(b),
+                (Self::TimestampTz(a), Self::TimestampTz(b)) => a.cmp(b),
+                (Self::Bytes(a), Self::Bytes(b)) => a.cmp(b),
+                (a, b) => match (a.to_f64(), b.to_f64()) {
+                    // Numbers which are equal as floats are told apart by their exact form.
+                    (Some(x), Some(y)) => x
+                        .total_cmp(&y)
+                        .then_with(|| a.number_kind().cmp(&b.number_kind()))
+                        .then_with(|| a.to_string().cmp(&b.to_string())),
+                    _ => a.to_string().cmp(&b.to_string()),
+                },
+            })
+    }
+
+    /// The group a value is sorted into, see `compare`.
+    fn sort_group(&self) -> u8 {
+        match self {
+            Self::Null => 0,
+            Self::Int(_) | Self::Float(_) | Self::Decimal(_) => 1,
+            Self::Text(_) | Self::Unknown(_) if self.to_f64().is_some() => 1,
+            Self::Bool(_) => 2,
+            Self::Date(_) => 3,
+            Self::Time(_) => 4,
+            Self::Timestamp(_) => 5,
+            Self::TimestampTz(_) => 6,
+            Self::Bytes(_) => 7,
+            _ => 8,
+        }
+    }
+
+    fn number_kind(&self) -> u8 {
+        match self {
+            Self::Int(_) => 0,
+            Self::Decimal(_) => 1,
+            Self::Float(_) => 2,
+            _ => 3,
+        }
+    }
+
+    /// The value as a number, parsing text which holds a finite one.
+    fn to_f64(&self) -> Option<f64> {
+        match self {
+            Self::Int(value) => Some(*value as f64),
+            Self::Float(value) => Some(*value),
+            Self::Decimal(value) => value.to_f64(),
+            Self::Text(value) | Self::Unknown(value) => value
+                .trim()
+                .parse()
+                .ok()
+                .filter(|value: &f64| value.is_finite()),
+            _ => None,
+        }
+    }
+
+    /// Converts the value to JSON, keeping numbers, booleans and JSON documents as they are.
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            Self::Null => serde_json::Value::Null,
+            Self::Int(value) => (*value).into(),
+            Self::Float(value) => (*value).into(),
+            Self::Bool(value) => (*value).into(),
+            Self::Json(value) => value.clone(),
+            Self::Array(values) => values.iter().map(Value::to_json).collect(),
+            value => value.to_string().into(),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Null => write!(f, "NULL"),
+            Self::Int(value) => write!(f, "{}", value),
+            Self::Float(value) => write!(f, "{}", value),
+            Self::Decimal(value) => write!(f, "{}", value),
+            Self::Text(value) | Self::Uuid(value) | Self::Unknown(value) => write!(f, "{}", value),
+            Self::Bytes(value) => {
+                write!(f, "\\x")?;
+                value.iter().try_for_each(|byte| write!(f, "{:02x}", byte))
+            }
+            Self::Bool(value) => write!(f, "{}", value),
+            Self::Date(value) => write!(f, "{}", value),
+            Self::Time(value) => write!(f, "{}", value),
+            Self::Timestamp(value) => write!(f, "{}", value),
+            Self::TimestampTz(value) => write!(f, "{}", value),
+            Self::Json(value) => write!(f, "{}", value),
+            Self::Array(values) => write!(
+                f,
+                "{}",
+                values
+                    .iter()
+                    .map(|value| value.to_string())
+                    .collect::<Vec<String>>()
+                    .join(",")
+            ),
+        }
+    }
+}
+
+macro_rules! value_from {
+    ($variant : ident, $($typ : ty),+) => {
+        $(
+        impl From<$typ> for Value {
+            fn from(value: $typ) -> Self {
+                Self::$variant(value.into())
+            }
+        }
+        )+
+    };
+}
+
+value_from!(Int, i8, i16, i32, i64, u8, u16, u32);
+value_from!(Decimal, Decimal);
+value_from!(Text, String, &str);
+value_from!(Bytes, Vec<u8>, &[u8]);
+value_from!(Bool, bool);
+value_from!(Date, NaiveDate);
+value_from!(Time, NaiveTime);
+value_from!(Timestamp, NaiveDateTime);
+value_from!(
+    TimestampTz,
+    DateTime<FixedOffset>,
+    DateTime<Utc>,
+    DateTime<Local>
+);
+value_from!(Json, serde_json::Value);
+
+impl From<u64> for Value {
+    fn from(value: u64) -> Self {
+        i64::try_from(value).map_or_else(|_| Self::Decimal(value.into()), Self::Int)
+    }
+}
+
+impl From<f32> for Value {
+    fn from(value: f32) -> Self {
+        // Going through the shortest text form keeps e.g. 0.1 from being shown as
+        // 0.10000000149011612.
+        Self::Float(value.to_string().parse().unwrap_or_else(|_| value.into()))
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Self::Float(value)
+    }
+}
+
+macro_rules! value_from_array {
+    ($($typ : ty),+) => {
+        $(
+        impl From<Vec<$typ>> for Value {
+            fn from(values: Vec<$typ>) -> Self {
+                Self::Array(values.into_iter().map(Into::into).collect())
+            }
+        }
+        )+
+    };
+}
+
+value_from_array!(i16, i32, i64, f32, f64, bool, Decimal, String);
+
+impl From<Vec<Value>> for Value {
+    fn from(values: Vec<Value>) -> Self {
+        Self::Array(values)
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Self::Null, Into::into)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cmp::Ordering;
+
+    use super::Value;
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Value::Null.to_string(), "NULL");
+        assert_eq!(Value::Text("NULL".to_string()).to_string(), "NULL");
+        assert_eq!(Value::from(0.1_f32).to_string(), "0.1");
+        assert_eq!(Value::from(vec![0xde_u8, 0xad]).to_string(), "\\xdead");
+        assert_eq!(
+            Value::from(vec!["a".to_string(), "b".to_string()]).to_string(),
+            "a,b"
+        );
+        assert_eq!(Value::from(vec![1.5_f32, 2.0]).to_string(), "1.5,2");
+        assert_eq!(Value::from(None::<i32>), Value::Null);
+    }
+
+    #[test]
+    fn test_to_sql_text() {
+        assert_eq!(Value::Null.to_sql_text(), None);
+        assert_eq!(
+            Value::Text("NULL".to_string()).to_sql_text(),
+            Some("NULL".to_string())
+        );
+        assert_eq!(
+            Value::from(u64::MAX).to_sql_text(),
+            Some(u64::MAX.to_string())
+        );
+    }
+
+    #[test]
+    fn test_to_json() {
+        assert_eq!(
+            Value::from(vec![Value::Int(1), Value::Null, Value::from("a")]).to_json(),
+            serde_json::json!([1, null, "a"])
+        );
+    }
+
+    #[test]
+    fn test_compare() {
+        assert_eq!(Value::Null.compare(&Value::Int(-1)), Ordering::Less);
+        assert_eq!(Value::Int(2).compare(&Value::Int(10)), Ordering::Less);
+        assert_eq!(Value::Float(2.5).compare(&Value::Int(2)), Ordering::Greater);
+        assert_eq!(Value::from("9").compare(&Value::from("10")), Ordering::Less);
+        assert_eq!(
+            Value::from("b").compare(&Value::from("a10")),
+            Ordering::Greater
+        );
+        assert_eq!(
+            Value::from("10").compare(&Value::from("1a")),
+            Ordering::Less
+        );
+        assert_eq!(
+            Value::from("nan").compare(&Value::from("1a")),
+            Ordering::Greater
+        );
+        assert_eq!(
+            Value::Float(f64::NAN).compare(&Value::Float(1.0)),
+            Ordering::Greater
+        );
+        assert_eq!(
+            Value::Int(i64::MAX).compare(&Value::Int(i64::MAX - 1)),
+            Ordering::Greater
+        );
+    }
+
+    #[test]
+    fn test_compare_is_total() {
+        let values = vec![
+            Value::from("10"),
+            Value::from("9"),
+            Value::from("1a"),
+            Value::Null,
+            Value::Float(f64::NAN),
+            Value::Float(9.0),
+            Value::Int(9),
+            Value::from(" 9"),
+            Value::from("NaN"),
+            Value::Bool(true),
+        ];
+        for a in &values {
+            for b in &values {
+                assert_eq!(a.compare(b), b.compare(a).reverse());
+                for c in &values {
+                    if a.compare(b) != Ordering::Greater && b.compare(c) != Ordering::Greater {
+                        assert_ne!(a.compare(c), Ordering::Greater, "{:?} {:?} {:?}", a, b, c);
+                    }
+                }
+            }
+        }
+        let mut sorted = values[..3].to_vec();
+        sorted.sort_by(Value::compare);
+        assert_eq!(
+            sorted,
+            vec![Value::from("9"), Value::from("10"), Value::from("1a")]
+        );
+    }
+}
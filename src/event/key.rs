@@ -70,6 +70,8 @@ pub enum Key {
     Char(char),
     Ctrl(event::KeyCode),
     Alt(char),
+    /// Arrow, Home and End keys with Shift held
+    Shift(event::KeyCode),
     Unknown,
 }
 
@@ -138,6 +140,7 @@ impl fmt::Display for Key {
             Key::Char(' ') => write!(f, "<Space>"),
             Key::Alt(c) => write!(f, "<Alt+{}>", c),
             Key::Ctrl(KeyCode::Char(c)) => write!(f, "<Ctrl+{}>", c),
+            Key::Shift(code) => write!(f, "<Shift+{:?}>", code),
             Key::Char(c) => write!(f, "{}", c),
             Key::Left => write!(f, "\u{2190}"),  //←
             Key::Right => write!(f, "\u{2192}"), //→
@@ -165,6 +168,16 @@ impl From<event::KeyEvent> for Key {
                 modifiers: event::KeyModifiers::CONTROL,
                 code,
             } => Key::Ctrl(code),
+            event::KeyEvent {
+                modifiers: event::KeyModifiers::SHIFT,
+                code:
+                    code @ (event::KeyCode::Left
+                    | event::KeyCode::Right
+                    | event::KeyCode::Up
+                    | event::KeyCode::Down
+                    | event::KeyCode::Home
+                    | event::KeyCode::End),
+            } => Key::Shift(code),
             event::KeyEvent {
                 code: event::KeyCode::Esc,
                 ..
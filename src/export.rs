@@ -0,0 +1,297 @@
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use database_tree::{Database, Table};
+
+use crate::database::{Dialect, Value};
+
+/// File formats a result set can be exported to. The format is picked from the file extension.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExportFormat {
+    Csv,
+    Tsv,
+    Json,
+    Markdown,
+    Insert,
+}
+
+impl ExportFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "csv" => Some(Self::Csv),
+            "tsv" => Some(Self::Tsv),
+            "json" => Some(Self::Json),
+            "md" => Some(Self::Markdown),
+            "sql" => Some(Self::Insert),
+            _ => None,
+        }
+    }
+}
+
+/// The table INSERT statements are generated for, and the dialect used to quote them.
+pub struct InsertTarget<'a> {
+    pub dialect: Dialect,
+    pub database: &'a Database,
+    pub table: &'a Table,
+}
+
+/// Renders `headers` and `rows` in the given format. Null values are exported as nulls where the
+/// format supports them, as empty unquoted fields in CSV, and as `NULL` otherwise.
+pub fn export(
+    format: ExportFormat,
+    headers: &[String],
+    rows: &[Vec<Value>],
+    target: Option<InsertTarget>,
+) -> Result<String> {
+    Ok(match format {
+        ExportFormat::Csv => delimited(headers, rows, ',', csv_value),
+        ExportFormat::Tsv => delimited(headers, rows, '\t', |value| tsv_field(&value.to_string())),
+        ExportFormat::Json => json(headers, rows),
+        ExportFormat::Markdown => markdown(headers, rows),
+        ExportFormat::Insert => {
+            let target = target.ok_or_else(|| anyhow!("INSERT statements need a source table"))?;
+            inserts(headers, rows, target)
+        }
+    })
+}
+
+/// Renders the values of a single column as an SQL `IN (...)` list, leaving out duplicates. Nulls
+/// are left out too, as `IN` never matches them and `NOT IN` matches nothing once they are listed.
+pub fn in_list(dialect: Dialect, headers: &[String], rows: &[Vec<Value>]) -> Result<String> {
+    if headers.len() != 1 {
+        return Err(anyhow!("an IN list needs a single column"));
+    }
+    let mut values: Vec<String> = vec![];
+    for value in rows
+        .iter()
+        .filter_map(|row| row.first())
+        .filter(|value| !value.is_null())
+    {
+        let value = dialect.literal(value);
+        if !values.contains(&value) {
+            values.push(value);
+        }
+    }
+    if values.is_empty() {
+        return Err(anyhow!("an IN list needs a value which is not NULL"));
+    }
+    Ok(format!("IN ({})", values.join(", ")))
+}
+
+fn delimited(
+    headers: &[String],
+    rows: &[Vec<Value>],
+    delimiter: char,
+    field: fn(&Value) -> String,
+) -> String {
+    std::iter::once(
+        headers
+            .iter()
+            .map(|header| field(&Value::from(header.as_str())))
+            .collect::<Vec<String>>(),
+    )
+    .chain(
+        rows.iter()
+            .map(|row| row.iter().map(field).collect::<Vec<String>>()),
+    )
+    .map(|row| row.join(&delimiter.to_string()) + "\n")
+    .collect()
+}
+
+/// Writes null as an empty field and quotes empty text, so that the two can be told apart.
+fn csv_value(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        value => csv_field(&value.to_string()),
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.is_empty() || value.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn tsv_field(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+fn json(headers: &[String], rows: &[Vec<Value>]) -> String {
+    let objects = rows
+        .iter()
+        .map(|row| {
+            let fields = headers
+                .iter()
+                .zip(row.iter())
+                .map(|(header, cell)| {
+                    format!(
+                        "{}: {}",
+                        serde_json::Value::from(header.as_str()),
+                        cell.to_json()
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join(", ");
+            format!("  {{{}}}", fields)
+        })
+        .collect::<Vec<String>>();
+    if objects.is_empty() {
+        "[]\n".to_string()
+    } else {
+        format!("[\n{}\n]\n", objects.join(",\n"))
+    }
+}
+
+fn markdown(headers: &[String], rows: &[Vec<Value>]) -> String {
+    let line = |cells: Vec<String>| {
+        format!(
+            "| {} |\n",
+            cells
+                .iter()
+                .map(|cell| cell.replace('|', "\\|").replace('\n', "<br>"))
+                .collect::<Vec<String>>()
+                .join(" | ")
+        )
+    };
+    let mut markdown = line(headers.to_vec());
+    markdown.push_str(&format!("|{}\n", " --- |".repeat(headers.len())));
+    for row in rows {
+        markdown.push_str(&line(row.iter().map(Value::to_string).collect()));
+    }
+    markdown
+}
+
+fn inserts(headers: &[String], rows: &[Vec<Value>], target: InsertTarget) -> String {
+    rows.iter()
+        .map(|row| {
+            let values = headers
+                .iter()
+                .cloned()
+                .zip(row.iter().cloned())
+                .collect::<Vec<(String, Value)>>();
+            target
+                .dialect
+                .insert_values(target.database, target.table, &values)
+                + ";\n"
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use super::{export, in_list, ExportFormat, InsertTarget};
+    use crate::database::{test_table, Dialect, Value};
+
+    fn data() -> (Vec<String>, Vec<Vec<Value>>) {
+        (
+            vec!["id".to_string(), "name".to_string()],
+            vec![
+                vec![Value::Int(1), Value::from("a,\"b\"")],
+                vec![Value::Int(2), Value::Null],
+            ],
+        )
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(
+            ExportFormat::from_path(Path::new("out.CSV")),
+            Some(ExportFormat::Csv)
+        );
+        assert_eq!(
+            ExportFormat::from_path(Path::new("~/out.sql")),
+            Some(ExportFormat::Insert)
+        );
+        assert_eq!(ExportFormat::from_path(Path::new("out")), None);
+    }
+
+    #[test]
+    fn test_export_csv() {
+        let (headers, rows) = data();
+        assert_eq!(
+            export(ExportFormat::Csv, &headers, &rows, None).unwrap(),
+            "id,name\n1,\"a,\"\"b\"\"\"\n2,\n"
+        );
+        let rows = vec![vec![Value::from("NULL"), Value::from("")]];
+        assert_eq!(
+            export(ExportFormat::Csv, &headers, &rows, None).unwrap(),
+            "id,name\nNULL,\"\"\n"
+        );
+    }
+
+    #[test]
+    fn test_export_tsv() {
+        let headers = vec!["a".to_string()];
+        let rows = vec![vec![Value::from("x\ty")]];
+        assert_eq!(
+            export(ExportFormat::Tsv, &headers, &rows, None).unwrap(),
+            "a\nx\\ty\n"
+        );
+    }
+
+    #[test]
+    fn test_export_json() {
+        let (headers, rows) = data();
+        assert_eq!(
+            export(ExportFormat::Json, &headers, &rows, None).unwrap(),
+            "[\n  {\"id\": 1, \"name\": \"a,\\\"b\\\"\"},\n  {\"id\": 2, \"name\": null}\n]\n"
+        );
+    }
+
+    #[test]
+    fn test_export_markdown() {
+        let headers = vec!["a".to_string(), "b".to_string()];
+        let rows = vec![vec![Value::from("x|y"), Value::from("z")]];
+        assert_eq!(
+            export(ExportFormat::Markdown, &headers, &rows, None).unwrap(),
+            "| a | b |\n| --- | --- |\n| x\\|y | z |\n"
+        );
+    }
+
+    #[test]
+    fn test_export_insert() {
+        let (headers, rows) = data();
+        let (database, table) = test_table("users");
+        assert_eq!(
+            export(
+                ExportFormat::Insert,
+                &headers,
+                &rows,
+                Some(InsertTarget {
+                    dialect: Dialect::MySql,
+                    database: &database,
+                    table: &table,
+                })
+            )
+            .unwrap(),
+            "INSERT INTO `shop`.`users` (`id`, `name`) VALUES (1, 'a,\"b\"');\n\
+             INSERT INTO `shop`.`users` (`id`, `name`) VALUES (2, NULL);\n"
+        );
+        assert!(export(ExportFormat::Insert, &headers, &rows, None).is_err());
+    }
+
+    #[test]
+    fn test_in_list() {
+        let (headers, rows) = data();
+        assert!(in_list(Dialect::Postgres, &headers, &rows).is_err());
+        let rows = vec![
+            vec![Value::Int(1)],
+            vec![Value::from("it's")],
+            vec![Value::Int(1)],
+            vec![Value::Null],
+        ];
+        assert_eq!(
+            in_list(Dialect::Postgres, &["id".to_string()], &rows).unwrap(),
+            "IN (1, 'it''s')"
+        );
+        assert!(in_list(Dialect::Postgres, &["id".to_string()], &[vec![Value::Null]]).is_err());
+    }
+}
@@ -0,0 +1,394 @@
+use std::collections::HashSet;
+
+use anyhow::{anyhow, bail, Result};
+use database_tree::{Database, Table};
+
+use crate::database::Dialect;
+
+/// The number of rows inserted by each INSERT statement of an import.
+pub const BATCH_SIZE: usize = 500;
+
+/// A record read from a CSV file, along with the line it starts on. Empty fields which are not
+/// quoted are `None`, as the CSV export writes null values that way.
+#[derive(Debug, PartialEq)]
+pub struct CsvRecord {
+    pub line: usize,
+    pub fields: Vec<Option<String>>,
+}
+
+/// Parses `contents` as RFC 4180 CSV. Quoted fields may contain commas, quotes and line breaks.
+/// Blank lines are skipped.
+pub fn parse_csv(contents: &str) -> Result<Vec<CsvRecord>> {
+    let mut records = vec![];
+    let mut fields = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    // Whether the current field was quoted, which tells an empty string from a null.
+    let mut was_quoted = false;
+    let mut started = false;
+    let mut line = 1;
+    let mut start_line = 1;
+    let mut chars = contents.chars().peekable();
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => quoted = false,
+                _ => {
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    field.push(c);
+                }
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() => {
+                quoted = true;
+                was_quoted = true;
+                started = true;
+            }
+            ',' => {
+                fields.push(take_field(&mut field, &mut was_quoted));
+                started = true;
+            }
+            '\r' if chars.peek() == Some(&'\n') => (),
+            '\n' => {
+                if started {
+                    fields.push(take_field(&mut field, &mut was_quoted));
+                    records.push(CsvRecord {
+                        line: start_line,
+                        fields: std::mem::take(&mut fields),
+                    });
+                    started = false;
+                }
+                line += 1;
+                start_line = line;
+            }
+            _ => {
+                field.push(c);
+                started = true;
+            }
+        }
+    }
+    if quoted {
+        bail!("line {}: unterminated quoted field", start_line);
+    }
+    if started {
+        fields.push(take_field(&mut field, &mut was_quoted));
+        records.push(CsvRecord {
+            line: start_line,
+            fields,
+        });
+    }
+    Ok(records)
+}
+
+fn take_field(field: &mut String, was_quoted: &mut bool) -> Option<String> {
+    let quoted = std::mem::take(was_quoted);
+    if field.is_empty() && !quoted {
+        None
+    } else {
+        Some(std::mem::take(field))
+    }
+}
+
+/// Column types a new table may be created with.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColumnType {
+    Integer,
+    Real,
+    Text,
+}
+
+impl ColumnType {
+    pub const ALL: [Self; 3] = [Self::Integer, Self::Real, Self::Text];
+
+    /// Infers the narrowest type able to hold all `values`. Null values are ignored. Numbers are
+    /// only inferred if they read back the same, so that e.g. codes with leading zeros stay text.
+    pub fn infer<'a>(values: impl Iterator<Item = Option<&'a str>>) -> Self {
+        let mut inferred = None;
+        for value in values.flatten() {
+            let integer = matches!(value.parse::<i64>(), Ok(number) if number.to_string() == value);
+            let real = is_decimal(value)
+                && matches!(value.parse::<f64>(), Ok(number) if number.is_finite());
+            inferred = Some(match inferred {
+                None | Some(Self::Integer) if integer => Self::Integer,
+                None | Some(Self::Integer) | Some(Self::Real) if real => Self::Real,
+                _ => Self::Text,
+            });
+        }
+        inferred.unwrap_or(Self::Text)
+    }
+
+    pub fn sql_type(&self, dialect: Dialect) -> &'static str {
+        match (self, dialect) {
+            (Self::Integer, Dialect::Sqlite) => "INTEGER",
+            (Self::Integer, _) => "BIGINT",
+            (Self::Real, Dialect::MySql) => "DOUBLE",
+            (Self::Real, Dialect::Postgres) => "DOUBLE PRECISION",
+            (Self::Real, Dialect::Sqlite) => "REAL",
+            (Self::Text, _) => "TEXT",
+        }
+    }
+}
+
+/// Whether `value` is a number in plain decimal notation, e.g. `-2.50`, without leading zeros or
+/// an exponent.
+fn is_decimal(value: &str) -> bool {
+    let digits = |part: &str| !part.is_empty() && part.bytes().all(|byte| byte.is_ascii_digit());
+    let mut parts = value.strip_prefix('-').unwrap_or(value).splitn(2, '.');
+    let integer = parts.next().unwrap_or_default();
+    digits(integer)
+        && (integer == "0" || !integer.starts_with('0'))
+        && parts.next().into_iter().all(digits)
+}
+
+/// Where the values of a CSV column are imported to.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ColumnMapping {
+    Skip,
+    /// An existing column of the target table.
+    Column(String),
+    /// A column of a new table, named after the CSV header.
+    New(ColumnType),
+}
+
+/// The contents of a CSV file. The first record holds the headers, data records with a different
+/// number of fields are rejected.
+pub struct CsvData {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<Option<String>>>,
+    pub rejected_lines: Vec<usize>,
+}
+
+impl CsvData {
+    /// Parses `contents`, turning empty unquoted fields into null values like the CSV export does.
+    pub fn parse(contents: &str) -> Result<Self> {
+        let mut records = parse_csv(contents)?.into_iter();
+        let headers: Vec<String> = records
+            .next()
+            .ok_or_else(|| anyhow!("the file is empty"))?
+            .fields
+            .into_iter()
+            .map(Option::unwrap_or_default)
+            .collect();
+        let mut rows = vec![];
+        let mut rejected_lines = vec![];
+        for record in records {
+            if record.fields.len() == headers.len() {
+                rows.push(record.fields);
+            } else {
+                rejected_lines.push(record.line);
+            }
+        }
+        Ok(Self {
+            headers,
+            rows,
+            rejected_lines,
+        })
+    }
+
+    pub fn column_type(&self, column: usize) -> ColumnType {
+        ColumnType::infer(self.rows.iter().map(|row| row[column].as_deref()))
+    }
+
+    /// Maps each header to the column of `columns` with the same name, ignoring case.
+    pub fn default_mapping(&self, columns: &[String]) -> Vec<ColumnMapping> {
+        self.headers
+            .iter()
+            .map(|header| {
+                columns
+                    .iter()
+                    .find(|column| column.eq_ignore_ascii_case(header.trim()))
+                    .map_or(ColumnMapping::Skip, |column| {
+                        ColumnMapping::Column(column.clone())
+                    })
+            })
+            .collect()
+    }
+
+    /// Builds the statements importing the rows into `table`, `BATCH_SIZE` rows per INSERT.
+    /// `mapping` holds an entry per CSV column. The table is created first if any column is
+    /// mapped to a new one.
+    pub fn import_statements(
+        &self,
+        dialect: Dialect,
+        database: &Database,
+        table: &Table,
+        mapping: &[ColumnMapping],
+    ) -> Result<Vec<String>> {
+        let mapped = self
+            .headers
+            .iter()
+            .zip(mapping.iter())
+            .enumerate()
+            .filter_map(|(index, (header, mapping))| match mapping {
+                ColumnMapping::Skip => None,
+                ColumnMapping::Column(column) => Some((index, column.clone(), None)),
+                ColumnMapping::New(r#type) => Some((index, header.clone(), Some(*r#type))),
+            })
+            .collect::<Vec<(usize, String, Option<ColumnType>)>>();
+        if mapped.is_empty() {
+            bail!("no column is mapped");
+        }
+        let mut names = HashSet::new();
+        for (_, name, _) in &mapped {
+            if !names.insert(name.as_str()) {
+                bail!("column `{}` is mapped more than once", name);
+            }
+        }
+
+        let mut statements = vec![];
+        let new_columns = mapped
+            .iter()
+            .filter_map(|(_, name, r#type)| {
+                r#type.map(|r#type| (name.clone(), r#type.sql_type(dialect).to_string()))
+            })
+            .collect::<Vec<(String, String)>>();
+        if !new_columns.is_empty() {
+            statements.push(dialect.create_table(database, table, &new_columns));
+        }
+        let columns = mapped
+            .iter()
+            .map(|(_, name, _)| name.clone())
+            .collect::<Vec<String>>();
+        for batch in self.rows.chunks(BATCH_SIZE) {
+            let rows = batch
+                .iter()
+                .map(|row| {
+                    mapped
+                        .iter()
+                        .map(|(index, _, _)| row[*index].clone())
+                        .collect()
+                })
+                .collect::<Vec<Vec<Option<String>>>>();
+            statements.push(dialect.insert_rows(database, table, &columns, &rows));
+        }
+        Ok(statements)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_csv, ColumnMapping, ColumnType, CsvData, CsvRecord, BATCH_SIZE};
+    use crate::database::{test_table, Dialect};
+
+    #[test]
+    fn test_parse_csv() {
+        assert_eq!(
+            parse_csv("id,name\r\n1,\"a,\"\"b\"\"\"\n\n2,\"x\ny\"\n3,\n\"\",NULL").unwrap(),
+            vec![
+                CsvRecord {
+                    line: 1,
+                    fields: vec![Some("id".to_string()), Some("name".to_string())]
+                },
+                CsvRecord {
+                    line: 2,
+                    fields: vec![Some("1".to_string()), Some("a,\"b\"".to_string())]
+                },
+                CsvRecord {
+                    line: 4,
+                    fields: vec![Some("2".to_string()), Some("x\ny".to_string())]
+                },
+                CsvRecord {
+                    line: 6,
+                    fields: vec![Some("3".to_string()), None]
+                },
+                CsvRecord {
+                    line: 7,
+                    fields: vec![Some("".to_string()), Some("NULL".to_string())]
+                },
+            ]
+        );
+        assert!(parse_csv("id\n\"1").is_err());
+    }
+
+    #[test]
+    fn test_rejected_lines() {
+        let data = CsvData::parse("id,name\n1,a\n2\n3,\n4,b,c\n").unwrap();
+        assert_eq!(
+            data.rows,
+            vec![
+                vec![Some("1".to_string()), Some("a".to_string())],
+                vec![Some("3".to_string()), None]
+            ]
+        );
+        assert_eq!(data.rejected_lines, vec![3, 5]);
+    }
+
+    #[test]
+    fn test_infer_column_type() {
+        let data = CsvData::parse("a,b,c,d\n1,1,x,\n-2,2.5,3,\n").unwrap();
+        assert_eq!(
+            (0..4).map(|i| data.column_type(i)).collect::<Vec<_>>(),
+            vec![
+                ColumnType::Integer,
+                ColumnType::Real,
+                ColumnType::Text,
+                ColumnType::Text
+            ]
+        );
+        let infer = |values: &[&str]| ColumnType::infer(values.iter().map(|value| Some(*value)));
+        assert_eq!(infer(&["0", "-10", "1.0", "0.50"]), ColumnType::Real);
+        assert_eq!(infer(&["007", "12"]), ColumnType::Text);
+        assert_eq!(infer(&["1e3"]), ColumnType::Text);
+        assert_eq!(infer(&["+1"]), ColumnType::Text);
+        assert_eq!(infer(&["01.5"]), ColumnType::Text);
+    }
+
+    #[test]
+    fn test_import_statements() {
+        let (database, table) = test_table("users");
+        let data = CsvData::parse("Name,ignored,ID\na,x,1\nb,y,2\n").unwrap();
+        let mapping = data.default_mapping(&["id".to_string(), "name".to_string()]);
+        assert_eq!(
+            mapping,
+            vec![
+                ColumnMapping::Column("name".to_string()),
+                ColumnMapping::Skip,
+                ColumnMapping::Column("id".to_string())
+            ]
+        );
+        assert_eq!(
+            data.import_statements(Dialect::MySql, &database, &table, &mapping)
+                .unwrap(),
+            vec!["INSERT INTO `shop`.`users` (`name`, `id`) VALUES ('a', '1'), ('b', '2')"]
+        );
+        assert!(data
+            .import_statements(
+                Dialect::MySql,
+                &database,
+                &table,
+                &vec![ColumnMapping::Skip; 3]
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_import_statements_new_table() {
+        let (database, table) = test_table("users");
+        let contents = (0..=BATCH_SIZE).fold("id\n".to_string(), |contents, id| {
+            contents + &format!("{}\n", id)
+        });
+        let data = CsvData::parse(&contents).unwrap();
+        let statements = data
+            .import_statements(
+                Dialect::Sqlite,
+                &database,
+                &table,
+                &[ColumnMapping::New(ColumnType::Integer)],
+            )
+            .unwrap();
+        assert_eq!(statements.len(), 3);
+        assert_eq!(statements[0], r#"CREATE TABLE "users" ("id" INTEGER)"#);
+        assert_eq!(
+            statements[2],
+            format!(r#"INSERT INTO "users" ("id") VALUES ('{}')"#, BATCH_SIZE)
+        );
+    }
+}
@@ -14,12 +14,15 @@ use crate::app::App;
 use crate::event::{Event, Key};
 
 mod app;
+mod batch;
 mod cli;
 mod clipboard;
 mod components;
 mod config;
 mod database;
 mod event;
+mod export;
+mod import;
 mod saturating_types;
 mod sql_utils;
 mod ui;
@@ -31,12 +34,26 @@ async fn main() -> anyhow::Result<()> {
     let value = crate::cli::parse();
     let config = config::Config::new(&value.config)?;
 
+    let connection = value.connection(&config)?;
+    if let Some(query) = value.query()? {
+        let connection = connection.ok_or_else(|| {
+            anyhow::anyhow!("--execute and --file need a database URL, --sqlite or --connection")
+        })?;
+        return batch::run(&connection, &query, value.format).await;
+    }
+
+    // Connecting before the terminal is set up lets a connection error end the program with its
+    // message, rather than leave an empty UI behind.
+    let mut app = App::new(config.clone()).await;
+    if let Some(connection) = connection {
+        app.connect(connection).await?;
+    }
+
     setup_terminal()?;
 
     let backend = CrosstermBackend::new(io::stdout());
     let mut terminal = Terminal::new(backend)?;
     let events = event::Events::new(250);
-    let mut app = App::new(config.clone()).await;
 
     terminal.clear()?;
 
@@ -71,7 +88,12 @@ async fn main() -> anyhow::Result<()> {
                     app.error.set(err.to_string())?;
                 }
             },
-            Event::Tick => (),
+            Event::Tick => {
+                if let Err(err) = app.tick().await {
+                    error!("error: {}", err);
+                    app.error.set(err.to_string())?;
+                }
+            }
         }
     }
 
@@ -1,4 +1,7 @@
 use std::fmt::{Display, Formatter};
+use std::ops::Range;
+
+use crate::database::Dialect;
 
 #[derive(Debug)]
 pub struct PatternPosition {
@@ -33,3 +36,220 @@ pub fn find_last_separator<S: Into<String>>(input: S) -> Option<PatternPosition>
     let val = find_separators!(input.into(), pattern).last();
     val
 }
+
+/// Returns the byte ranges of the statements of `script`, without the `;` ending them and the
+/// whitespace around them. `;` in quotes, comments and, in PostgreSQL, dollar-quoted strings does
+/// not end a statement, and parts holding only comments are skipped.
+pub fn split_statements(dialect: Dialect, script: &str) -> Vec<Range<usize>> {
+    let bytes = script.as_bytes();
+    let mut statements = vec![];
+    let mut start = 0;
+    let mut has_code = false;
+    let mut index = 0;
+    while index < bytes.len() {
+        let rest = &script[index..];
+        let length = match bytes[index] {
+            b';' => {
+                if has_code {
+                    statements.push(trim_range(script, start..index));
+                }
+                start = index + 1;
+                has_code = false;
+                index += 1;
+                continue;
+            }
+            b'-' if rest.starts_with("--") => rest.find('\n').unwrap_or(rest.len()),
+            b'#' if dialect == Dialect::MySql => rest.find('\n').unwrap_or(rest.len()),
+            b'/' if rest.starts_with("/*") => {
+                rest[2..].find("*/").map_or(rest.len(), |end| end + 4)
+            }
+            quote @ (b'\'' | b'"' | b'`') => {
+                has_code = true;
+                let escape_string = quote == b'\''
+                    && index > 0
+                    && bytes[index - 1].eq_ignore_ascii_case(&b'e')
+                    && (index == 1 || !is_identifier_byte(bytes[index - 2]));
+                let backslash_escapes = match dialect {
+                    Dialect::MySql => quote != b'`',
+                    Dialect::Postgres => escape_string,
+                    Dialect::Sqlite => false,
+                };
+                quoted_length(rest, quote, backslash_escapes)
+            }
+            b'$' if dialect == Dialect::Postgres
+                && (index == 0 || !is_identifier_byte(bytes[index - 1])) =>
+            {
+                has_code = true;
+                dollar_quoted_length(rest).unwrap_or(1)
+            }
+            _ => {
+                let c = rest.chars().next().unwrap_or_default();
+                has_code |= !c.is_whitespace();
+                c.len_utf8()
+            }
+        };
+        index += length;
+    }
+    if has_code {
+        statements.push(trim_range(script, start..script.len()));
+    }
+    statements
+}
+
+/// Returns the range of the statement the cursor at `offset` is in. Between two statements, this
+/// is the one before the cursor.
+pub fn statement_at(statements: &[Range<usize>], offset: usize) -> Option<Range<usize>> {
+    statements
+        .iter()
+        .rev()
+        .find(|statement| statement.start <= offset)
+        .or_else(|| statements.first())
+        .cloned()
+}
+
+fn trim_range(script: &str, range: Range<usize>) -> Range<usize> {
+    let text = &script[range.clone()];
+    let start = range.start + (text.len() - text.trim_start().len());
+    start..start + text.trim().len()
+}
+
+/// Returns the length of the quoted string or identifier `rest` starts with, up to the end of
+/// `rest` if it is not closed. Quotes are escaped by doubling them or, if `backslash_escapes`,
+/// with a backslash.
+fn quoted_length(rest: &str, quote: u8, backslash_escapes: bool) -> usize {
+    let bytes = rest.as_bytes();
+    let mut index = 1;
+    while index < bytes.len() {
+        if bytes[index] == b'\\' && backslash_escapes {
+            index += 2;
+        } else if bytes[index] == quote {
+            if bytes.get(index + 1) != Some(&quote) {
+                return index + 1;
+            }
+            index += 2;
+        } else {
+            index += 1;
+        }
+    }
+    bytes.len()
+}
+
+/// Returns the length of the PostgreSQL dollar-quoted string, e.g. `$body$ ... $body$`, `rest`
+/// starts with, or None if it does not start with one.
+fn dollar_quoted_length(rest: &str) -> Option<usize> {
+    let tag_length = rest[1..].find('$')? + 2;
+    let tag = &rest[1..tag_length - 1];
+    if tag.starts_with(|c: char| c.is_ascii_digit()) || !tag.bytes().all(is_identifier_byte) {
+        return None;
+    }
+    let delimiter = &rest[..tag_length];
+    Some(
+        rest[tag_length..]
+            .find(delimiter)
+            .map_or(rest.len(), |end| end + 2 * tag_length),
+    )
+}
+
+fn is_identifier_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_'
+}
+
+#[cfg(test)]
+mod test {
+    use super::{split_statements, statement_at};
+    use crate::database::Dialect;
+
+    fn statements(dialect: Dialect, script: &str) -> Vec<&str> {
+        split_statements(dialect, script)
+            .into_iter()
+            .map(|range| &script[range])
+            .collect()
+    }
+
+    #[test]
+    fn test_split_statements() {
+        assert_eq!(
+            statements(Dialect::Sqlite, "SELECT 1;\n  SELECT 2 ;\n\nSELECT 3"),
+            vec!["SELECT 1", "SELECT 2", "SELECT 3"]
+        );
+        assert_eq!(
+            statements(
+                Dialect::MySql,
+                "SELECT 'a;b', \"c;\", `d;`, 'it''s;', 'e\\';'; SELECT 2;"
+            ),
+            vec!["SELECT 'a;b', \"c;\", `d;`, 'it''s;', 'e\\';'", "SELECT 2"]
+        );
+        assert_eq!(
+            statements(
+                Dialect::Postgres,
+                "-- first; query\nSELECT 1; /* second; */ ;\n-- none;"
+            ),
+            vec!["-- first; query\nSELECT 1"]
+        );
+        assert_eq!(
+            statements(
+                Dialect::Postgres,
+                "CREATE FUNCTION f() RETURNS int AS $body$ SELECT 1; $body$ LANGUAGE sql; \
+                 SELECT $$a;b$$, $1;"
+            ),
+            vec![
+                "CREATE FUNCTION f() RETURNS int AS $body$ SELECT 1; $body$ LANGUAGE sql",
+                "SELECT $$a;b$$, $1"
+            ]
+        );
+        assert_eq!(
+            statements(Dialect::Sqlite, "SELECT 'é;ü'; SELECT 'unclosed;"),
+            vec!["SELECT 'é;ü'", "SELECT 'unclosed;"]
+        );
+        assert!(statements(Dialect::Sqlite, " ; \n-- comment").is_empty());
+    }
+
+    #[test]
+    fn test_split_statements_backslashes() {
+        // A backslash ends a PostgreSQL or SQLite string, but escapes the quote in MySQL
+        let script = "SELECT 'C:\\'; DELETE FROM t; SELECT 1";
+        assert_eq!(
+            statements(Dialect::Postgres, script),
+            vec!["SELECT 'C:\\'", "DELETE FROM t", "SELECT 1"]
+        );
+        assert_eq!(
+            statements(Dialect::Sqlite, script),
+            vec!["SELECT 'C:\\'", "DELETE FROM t", "SELECT 1"]
+        );
+        assert_eq!(
+            statements(
+                Dialect::MySql,
+                "SELECT 'C:\\\\'; SELECT 'it\\'s;'; SELECT 1"
+            ),
+            vec!["SELECT 'C:\\\\'", "SELECT 'it\\'s;'", "SELECT 1"]
+        );
+        assert_eq!(
+            statements(Dialect::Postgres, "SELECT E'it\\'s;'; SELECT 1"),
+            vec!["SELECT E'it\\'s;'", "SELECT 1"]
+        );
+    }
+
+    #[test]
+    fn test_split_statements_hash_comments() {
+        let script = "# first; query\nSELECT 1; SELECT 2 # two; halves";
+        assert_eq!(
+            statements(Dialect::MySql, script),
+            vec!["# first; query\nSELECT 1", "SELECT 2 # two; halves"]
+        );
+        assert_eq!(
+            statements(Dialect::Postgres, script),
+            vec!["# first", "query\nSELECT 1", "SELECT 2 # two", "halves"]
+        );
+    }
+
+    #[test]
+    fn test_statement_at() {
+        let script = "SELECT 1;\n\nSELECT 2;";
+        let statements = split_statements(Dialect::Sqlite, script);
+        assert_eq!(statement_at(&statements, 0), Some(0..8));
+        assert_eq!(statement_at(&statements, 9), Some(0..8));
+        assert_eq!(statement_at(&statements, 11), Some(11..19));
+        assert_eq!(statement_at(&statements, 20), Some(11..19));
+        assert_eq!(statement_at(&[], 0), None);
+    }
+}
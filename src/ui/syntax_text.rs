@@ -1,13 +1,17 @@
 use std::ops::Range;
 
+use log::warn;
 use syntect::{
     highlighting::{
-        FontStyle, HighlightState, Highlighter, RangedHighlightIterator, Style, ThemeSet,
+        FontStyle, HighlightState, Highlighter, RangedHighlightIterator, Style, Theme, ThemeSet,
     },
     parsing::{ParseState, ScopeStack, SyntaxSet},
 };
 use tui::text::{Span, Spans};
 
+/// The theme used when no other one is configured, or the configured one can't be loaded.
+pub const DEFAULT_THEME: &str = "base16-eighties.dark";
+
 struct SyntaxLine {
     items: Vec<(Style, usize, Range<usize>)>,
 }
@@ -19,11 +23,21 @@ pub struct SyntaxText {
 
 impl SyntaxText {
     pub fn new(text: String) -> Self {
+        Self::with_extension(text, "sql")
+    }
+
+    /// Highlights `text` with the syntax registered for files with the given extension, e.g.
+    /// `json`. Falls back to plain text for unknown extensions.
+    pub fn with_extension(text: String, extension: &str) -> Self {
         let syntax_set: SyntaxSet = SyntaxSet::load_defaults_nonewlines();
         let theme_set: ThemeSet = ThemeSet::load_defaults();
 
-        let mut state = ParseState::new(syntax_set.find_syntax_by_extension("sql").unwrap());
-        let highlighter = Highlighter::new(&theme_set.themes["base16-eighties.dark"]);
+        let mut state = ParseState::new(
+            syntax_set
+                .find_syntax_by_extension(extension)
+                .unwrap_or_else(|| syntax_set.find_syntax_plain_text()),
+        );
+        let highlighter = Highlighter::new(&theme_set.themes[DEFAULT_THEME]);
         let mut syntax_lines: Vec<SyntaxLine> = Vec::new();
         let mut highlight_state = HighlightState::new(&highlighter, ScopeStack::new());
 
@@ -86,6 +100,136 @@ impl<'a> From<&'a SyntaxText> for tui::text::Text<'a> {
     }
 }
 
+/// The parser and highlighter states between two lines.
+#[derive(Clone, PartialEq)]
+struct LineState {
+    parse: ParseState,
+    highlight: HighlightState,
+}
+
+struct HighlightedLine {
+    text: String,
+    items: Vec<(Style, Range<usize>)>,
+    /// The state at the end of the line, which the next line is highlighted from.
+    end: LineState,
+}
+
+/// Highlights the SQL in an editor buffer. After an edit only the lines from the first changed one
+/// on are parsed again, until a line that is unchanged is reached in the same state as before, so
+/// that editing large scripts stays responsive.
+pub struct SqlHighlighter {
+    syntax_set: SyntaxSet,
+    theme: Theme,
+    lines: Vec<HighlightedLine>,
+}
+
+impl SqlHighlighter {
+    /// `theme` is either the name of one of syntect's default themes, e.g. `InspiredGitHub`, or the
+    /// path to a `.tmTheme` file.
+    pub fn new(theme: &str) -> Self {
+        Self {
+            syntax_set: SyntaxSet::load_defaults_nonewlines(),
+            theme: load_theme(theme),
+            lines: vec![],
+        }
+    }
+
+    /// Highlights the changed lines of `buffer`, returning how many lines were parsed.
+    pub fn update(&mut self, buffer: &[String]) -> usize {
+        let prefix = self
+            .lines
+            .iter()
+            .zip(buffer)
+            .take_while(|(line, text)| &line.text == *text)
+            .count();
+        let suffix = self.lines[prefix..]
+            .iter()
+            .rev()
+            .zip(buffer[prefix..].iter().rev())
+            .take_while(|(line, text)| &line.text == *text)
+            .count();
+        let mut old_lines = self.lines.split_off(prefix);
+        let changed = old_lines.len() - suffix;
+        let mut unchanged_lines = old_lines.split_off(changed).into_iter();
+
+        let mut state = match self.lines.last() {
+            Some(line) => line.end.clone(),
+            None => self.initial_state(),
+        };
+        // The state the next unchanged line was highlighted from
+        let mut unchanged_state = match old_lines.last() {
+            Some(line) => line.end.clone(),
+            None => state.clone(),
+        };
+        let mut parsed = 0;
+        for (index, text) in buffer.iter().enumerate().skip(prefix) {
+            if index >= buffer.len() - suffix {
+                if unchanged_state == state {
+                    self.lines.extend(unchanged_lines);
+                    break;
+                }
+                if let Some(line) = unchanged_lines.next() {
+                    unchanged_state = line.end;
+                }
+            }
+            let line = self.highlight_line(text, &mut state);
+            self.lines.push(line);
+            parsed += 1;
+        }
+        parsed
+    }
+
+    /// Returns the highlighted parts of the line at `index`, if it has been highlighted.
+    pub fn line(&self, index: usize) -> Option<Vec<(tui::style::Style, &str)>> {
+        let line = self.lines.get(index)?;
+        Some(
+            line.items
+                .iter()
+                .map(|(style, range)| (syntact_style_to_tui(style), &line.text[range.clone()]))
+                .collect(),
+        )
+    }
+
+    fn initial_state(&self) -> LineState {
+        let syntax = self
+            .syntax_set
+            .find_syntax_by_extension("sql")
+            .unwrap_or_else(|| self.syntax_set.find_syntax_plain_text());
+        LineState {
+            parse: ParseState::new(syntax),
+            highlight: HighlightState::new(&Highlighter::new(&self.theme), ScopeStack::new()),
+        }
+    }
+
+    fn highlight_line(&self, text: &str, state: &mut LineState) -> HighlightedLine {
+        let highlighter = Highlighter::new(&self.theme);
+        let ops = state.parse.parse_line(text, &self.syntax_set);
+        let items =
+            RangedHighlightIterator::new(&mut state.highlight, &ops[..], text, &highlighter)
+                .map(|(style, _, range)| (style, range))
+                .collect();
+        HighlightedLine {
+            text: text.to_string(),
+            items,
+            end: state.clone(),
+        }
+    }
+}
+
+fn load_theme(theme: &str) -> Theme {
+    let mut themes = ThemeSet::load_defaults().themes;
+    if let Some(theme) = themes.remove(theme) {
+        return theme;
+    }
+    match ThemeSet::get_theme(theme) {
+        Ok(theme) => theme,
+        Err(err) => {
+            warn!("failed to load the editor theme `{}`: {}", theme, err);
+            themes.remove(DEFAULT_THEME).unwrap_or_default()
+        }
+    }
+}
+
 fn syntact_style_to_tui(style: &Style) -> tui::style::Style {
     let mut res = tui::style::Style::default().fg(tui::style::Color::Rgb(
         style.foreground.r,
@@ -105,3 +249,41 @@ fn syntact_style_to_tui(style: &Style) -> tui::style::Style {
 
     res
 }
+
+#[cfg(test)]
+mod test {
+    use super::{SqlHighlighter, DEFAULT_THEME};
+
+    #[test]
+    fn test_sql_highlighter() {
+        let mut buffer = vec![
+            "SELECT 1;".to_string(),
+            "SELECT 'a';".to_string(),
+            "SELECT 2;".to_string(),
+        ];
+        let mut highlighter = SqlHighlighter::new(DEFAULT_THEME);
+        assert_eq!(highlighter.update(&buffer), 3);
+        assert_eq!(highlighter.update(&buffer), 0);
+
+        buffer[1] = "SELECT 'b';".to_string();
+        assert_eq!(highlighter.update(&buffer), 1);
+
+        // Opening a comment changes the state of all the following lines
+        buffer.insert(1, "/*".to_string());
+        assert_eq!(highlighter.update(&buffer), 3);
+        buffer.remove(1);
+        assert_eq!(highlighter.update(&buffer), 2);
+        // The parser treats the first line specially, so only the new first line is parsed again
+        buffer.remove(0);
+        assert_eq!(highlighter.update(&buffer), 1);
+
+        let line = highlighter.line(0).unwrap();
+        assert_eq!(
+            line.iter().map(|(_, text)| *text).collect::<String>(),
+            "SELECT 'b';"
+        );
+        let style_of = |part: &str| line.iter().find(|(_, text)| text.contains(part)).unwrap().0;
+        assert_ne!(style_of("SELECT"), style_of("b"));
+        assert!(highlighter.line(2).is_none());
+    }
+}
@@ -3,9 +3,10 @@ use async_trait::async_trait;
 use crossterm::event::KeyCode;
 use itertools::Itertools;
 use log::debug;
+use std::ops::Range;
 use tui::backend::Backend;
 use tui::layout::Rect;
-use tui::style::{Color, Style};
+use tui::style::{Color, Modifier, Style};
 use tui::text::{Span, Spans, Text};
 use tui::widgets::{Block, Borders, Paragraph};
 use tui::Frame;
@@ -18,6 +19,7 @@ use crate::components::{CompletionComponent, Component};
 use crate::config::KeyConfig;
 use crate::saturating_types::SaturatingU16;
 use crate::sql_utils::find_last_separator;
+use crate::ui::syntax_text::SqlHighlighter;
 use crate::{handle_message, Key};
 
 #[derive(Clone)]
@@ -37,10 +39,13 @@ pub struct TextArea {
     app_state: AppStateRef,
     completion: CompletionComponent,
     cursor_position: CursorPos,
+    /// The position the selection was started at, the cursor being at its other end.
+    selection_anchor: Option<CursorPos>,
+    highlighter: SqlHighlighter,
 }
 
 impl TextArea {
-    pub async fn new(key_config: KeyConfig, app_state: AppStateRef) -> TextArea {
+    pub async fn new(key_config: KeyConfig, app_state: AppStateRef, theme: &str) -> TextArea {
         let mut completion = CompletionComponent::new(key_config.clone());
         if let Some(src) = app_state.clone().read().await.pool_completion_src().await {
             completion.completion_source = Box::new(src);
@@ -53,6 +58,8 @@ impl TextArea {
                 row: 0.into(),
                 col: 0.into(),
             },
+            selection_anchor: None,
+            highlighter: SqlHighlighter::new(theme),
         };
     }
 
@@ -60,39 +67,73 @@ impl TextArea {
         return self.buffer.join("\n");
     }
 
+    /// Returns the byte offset of the cursor in `get_text`.
+    pub fn cursor_offset(&self) -> usize {
+        self.offset_of(&self.cursor_position)
+    }
+
+    /// Returns the byte range of the selected text in `get_text`, if any text is selected.
+    pub fn selection(&self) -> Option<Range<usize>> {
+        let anchor = self.offset_of(self.selection_anchor.as_ref()?);
+        let cursor = self.cursor_offset();
+        if anchor == cursor {
+            return None;
+        }
+        Some(anchor.min(cursor)..anchor.max(cursor))
+    }
+
+    fn offset_of(&self, position: &CursorPos) -> usize {
+        let row = position.row.0 as usize;
+        self.buffer
+            .iter()
+            .take(row)
+            .map(|line| line.len() + 1)
+            .sum::<usize>()
+            + self
+                .buffer
+                .get(row)
+                .map_or(0, |line| line.len().min(position.col.0 as usize))
+    }
+
     /// Get input as vec of spans converted into text. Each 'Spans' element is composed of multiple
     /// graphemes each with their own symbol, style, and modifiers. Text encapsulates a vec of spans;
     /// so we convert the vector of spans into a text before returning.
     fn lines_as_text_model(&self) -> Text {
-        // TODO : Add different styling/highlights to keywords
-        // let lines: Vec<Spans> = self.buffer.split('\n').map(|l| Spans::from(l)).collect();
-        let lines: Vec<Spans> = self
-            .buffer
-            .iter()
-            .map(|l| {
-                // Renders spaces as a bullet \u{2022}
-                let mut buff = String::new();
-                let mut spans: Vec<Span> = vec![];
-                for c in l.chars() {
-                    if c == ' ' {
-                        if !buff.is_empty() {
-                            let temp = buff;
-                            buff = String::new();
-                            spans.push(Span::from(temp));
-                        }
-                        spans.push(
-                            Span::styled("\u{2022}", Style::default().fg(Color::DarkGray)).into(),
-                        );
-                    } else {
-                        buff.push(c);
+        let selection = self.selection().unwrap_or_default();
+        let mut lines: Vec<Spans> = Vec::with_capacity(self.buffer.len());
+        let mut line_start = 0;
+        for (index, l) in self.buffer.iter().enumerate() {
+            let parts = self
+                .highlighter
+                .line(index)
+                .unwrap_or_else(|| vec![(Style::default(), l.as_str())]);
+            // The selected part of the line
+            let selected = selection.start.saturating_sub(line_start)
+                ..selection.end.saturating_sub(line_start);
+            line_start += l.len() + 1;
+            // Renders spaces as a bullet \u{2022}
+            let mut spans: Vec<Span> = vec![];
+            let mut position = 0;
+            for (style, part) in parts {
+                for (i, word) in part.split(' ').enumerate() {
+                    if i > 0 {
+                        let style = Style::default().fg(Color::DarkGray);
+                        spans.push(Span::styled(
+                            "\u{2022}",
+                            if selected.contains(&position) {
+                                style.add_modifier(Modifier::REVERSED)
+                            } else {
+                                style
+                            },
+                        ));
+                        position += 1;
                     }
+                    push_span(&mut spans, word, position, style, &selected);
+                    position += word.len();
                 }
-                if !buff.is_empty() {
-                    spans.push(Span::from(buff));
-                }
-                Spans::from(spans)
-            })
-            .collect();
+            }
+            lines.push(Spans::from(spans));
+        }
         Text::from(lines)
     }
 
@@ -335,70 +376,8 @@ impl TextArea {
         }
         return false;
     }
-}
-
-impl DrawableComponent for TextArea {
-    fn draw<B: Backend>(&self, f: &mut Frame<B>, area: Rect, focused: bool) -> anyhow::Result<()> {
-        let row = self.cursor_position.row.0;
-        let col = self.cursor_position.col.0;
-        let block = Block::default().borders(Borders::ALL).style(if focused {
-            Style::default()
-        } else {
-            Style::default().fg(Color::DarkGray)
-        });
-        let text_area_frame = block.inner(area);
-        f.render_widget(block, area);
-
-        let p = Paragraph::new(self.lines_as_text_model())
-            .scroll((
-                if row >= text_area_frame.height {
-                    (row - text_area_frame.height) + 1
-                } else {
-                    0
-                },
-                if col > text_area_frame.width {
-                    col - text_area_frame.width
-                } else {
-                    0
-                },
-            ))
-            .style(if !focused {
-                Style::default().fg(Color::DarkGray)
-            } else {
-                Style::default()
-            });
-        f.render_widget(p, text_area_frame);
-
-        if focused {
-            let cursor_x = if col + text_area_frame.left() >= text_area_frame.right() {
-                text_area_frame.right()
-            } else {
-                col + text_area_frame.left()
-            };
-
-            let cursor_y = if row + text_area_frame.top() >= text_area_frame.bottom() {
-                text_area_frame.bottom() - 1
-            } else {
-                row + text_area_frame.top()
-            };
-            f.set_cursor(cursor_x, cursor_y);
-        }
-        self.completion.draw(
-            f,
-            text_area_frame,
-            false,
-            text_area_frame.x + self.cursor_position.col.0,
-            text_area_frame.y + (self.cursor_position.row + 1).0,
-        )?;
-        Ok(())
-    }
-}
-
-#[async_trait]
-impl Component for TextArea {
-    fn commands(&self, out: &mut Vec<CommandInfo>) {}
 
-    async fn event(
+    async fn key_event(
         &mut self,
         key: Key,
         _message_queue: &mut GlobalMessageQueue,
@@ -456,6 +435,33 @@ impl Component for TextArea {
             }
         }
 
+        if let Key::Shift(code) = key {
+            let anchor = self
+                .selection_anchor
+                .take()
+                .unwrap_or_else(|| self.cursor_position.clone());
+            match code {
+                KeyCode::Left => {
+                    self.move_left();
+                }
+                KeyCode::Right => {
+                    self.move_right();
+                }
+                KeyCode::Up => {
+                    self.move_up();
+                }
+                KeyCode::Down => {
+                    self.move_down();
+                }
+                KeyCode::Home => self.move_to_beginning_of_line(),
+                KeyCode::End => self.move_to_end_of_line(),
+                _ => (),
+            }
+            self.selection_anchor = Some(anchor);
+            self.completion.reset();
+            return Ok(Consumed);
+        }
+
         if key == Key::Home {
             self.move_to_beginning_of_line();
             self.completion.reset();
@@ -510,6 +516,106 @@ impl Component for TextArea {
 
         Ok(NotConsumed)
     }
+}
+
+/// Pushes `text`, starting at byte `start` of its line, reversing the colors of its part in
+/// `selected`.
+fn push_span<'a>(
+    spans: &mut Vec<Span<'a>>,
+    text: &'a str,
+    start: usize,
+    style: Style,
+    selected: &Range<usize>,
+) {
+    let end = start + text.len();
+    let from = selected.start.clamp(start, end) - start;
+    let to = selected.end.clamp(start, end) - start;
+    for (range, style) in [
+        (0..from, style),
+        (from..to, style.add_modifier(Modifier::REVERSED)),
+        (to..text.len(), style),
+    ] {
+        if !range.is_empty() {
+            spans.push(Span::styled(&text[range], style));
+        }
+    }
+}
+
+impl DrawableComponent for TextArea {
+    fn draw<B: Backend>(&self, f: &mut Frame<B>, area: Rect, focused: bool) -> anyhow::Result<()> {
+        let row = self.cursor_position.row.0;
+        let col = self.cursor_position.col.0;
+        let block = Block::default().borders(Borders::ALL).style(if focused {
+            Style::default()
+        } else {
+            Style::default().fg(Color::DarkGray)
+        });
+        let text_area_frame = block.inner(area);
+        f.render_widget(block, area);
+
+        let p = Paragraph::new(self.lines_as_text_model())
+            .scroll((
+                if row >= text_area_frame.height {
+                    (row - text_area_frame.height) + 1
+                } else {
+                    0
+                },
+                if col > text_area_frame.width {
+                    col - text_area_frame.width
+                } else {
+                    0
+                },
+            ))
+            .style(if !focused {
+                Style::default().fg(Color::DarkGray)
+            } else {
+                Style::default()
+            });
+        f.render_widget(p, text_area_frame);
+
+        if focused {
+            let cursor_x = if col + text_area_frame.left() >= text_area_frame.right() {
+                text_area_frame.right()
+            } else {
+                col + text_area_frame.left()
+            };
+
+            let cursor_y = if row + text_area_frame.top() >= text_area_frame.bottom() {
+                text_area_frame.bottom() - 1
+            } else {
+                row + text_area_frame.top()
+            };
+            f.set_cursor(cursor_x, cursor_y);
+        }
+        self.completion.draw(
+            f,
+            text_area_frame,
+            false,
+            text_area_frame.x + self.cursor_position.col.0,
+            text_area_frame.y + (self.cursor_position.row + 1).0,
+        )?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Component for TextArea {
+    fn commands(&self, out: &mut Vec<CommandInfo>) {}
+
+    async fn event(
+        &mut self,
+        key: Key,
+        message_queue: &mut GlobalMessageQueue,
+    ) -> anyhow::Result<EventState> {
+        let state = self.key_event(key, message_queue).await?;
+        if state.is_consumed() {
+            if !matches!(key, Key::Shift(_)) {
+                self.selection_anchor = None;
+            }
+            self.highlighter.update(&self.buffer);
+        }
+        Ok(state)
+    }
 
     async fn handle_messages(&mut self, messages: &Vec<Box<dyn AppMessage>>) -> Result<()> {
         for m in messages.iter() {
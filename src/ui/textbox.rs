@@ -42,7 +42,7 @@ impl Default for TextBox {
 
 impl TextBox {
     pub fn with_placeholder<S: Into<String>>(mut self, placeholder: S) -> Self {
-        self.placeholder = Some(placeholder.into());
+        self.set_placeholder(placeholder);
         self
     }
 
@@ -99,6 +99,11 @@ impl TextBox {
         self.label = Some(format!("{} ", label.into()));
     }
 
+    /// Sets the text shown while the text-box is empty
+    pub fn set_placeholder<S: Into<String>>(&mut self, placeholder: S) {
+        self.placeholder = Some(placeholder.into());
+    }
+
     /// Resets the text buffer and the embeded completion element (if present)
     pub fn reset(&mut self) {
         self.input = Vec::new();